@@ -1,3 +1,4 @@
+use crate::components::shader_editor::ShaderEditor;
 use crate::pages::{render_page, Page};
 use crate::sidemenu::Sidemenu;
 use yew::function_component;
@@ -10,7 +11,9 @@ pub fn app() -> yew::Html {
         <div class="wasmgl-layout">
             <Sidemenu />
             <div class="wasmgl-content">
-                <Switch<Page> render={render_page} />
+                <ShaderEditor>
+                    <Switch<Page> render={render_page} />
+                </ShaderEditor>
             </div>
         </div>
         </BrowserRouter>
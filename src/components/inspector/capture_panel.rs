@@ -0,0 +1,189 @@
+use std::rc::Rc;
+
+use js_sys::Array;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlElement, Url};
+use yew::{Callback, Properties};
+
+use crate::utils::capture::{self, Call, Capture, DrawState};
+
+pub enum Message {
+    // A capture started or ended
+    Refresh,
+    Capture,
+    CaptureSetup,
+    Export,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CapturePanelProps {
+    /// Reloads the page
+    pub onreload: Callback<()>,
+}
+
+/// Save `text` as a file named `filename`
+fn download(filename: &str, text: &str) -> Result<(), JsValue> {
+    let parts = Array::of1(&JsValue::from(text));
+    let options = BlobPropertyBag::new();
+    options.set_type("application/json");
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let link = gloo::utils::document().create_element("a")?;
+    link.set_attribute("href", &url)?;
+    link.set_attribute("download", filename)?;
+    link.unchecked_into::<HtmlElement>().click();
+    Url::revoke_object_url(&url)
+}
+
+fn signature(call: &Call) -> String {
+    if call.errors.is_empty() {
+        call.name.clone()
+    } else {
+        format!("{} → {}", call.name, call.errors.join(", "))
+    }
+}
+
+fn view_state(state: &DrawState) -> yew::Html {
+    let bound = [
+        ("Program", state.program.as_deref()),
+        ("Vertex array", state.vertex_array.as_deref()),
+        ("Array buffer", state.array_buffer.as_deref()),
+        (
+            "Element array buffer",
+            state.element_array_buffer.as_deref(),
+        ),
+        (
+            "Framebuffer",
+            Some(state.framebuffer.as_deref().unwrap_or("canvas")),
+        ),
+        ("Viewport", Some(state.viewport.as_str())),
+    ];
+    let enabled = if state.enabled.is_empty() {
+        "none".to_string()
+    } else {
+        state.enabled.join(", ")
+    };
+    yew::html! {
+        <table class="wasmgl-capture-state">
+            { for bound.iter().map(|(name, value)| yew::html! {
+                <tr><th>{ name }</th><td>{ value.unwrap_or("null") }</td></tr>
+            }) }
+            <tr><th>{ "Enabled" }</th><td>{ enabled }</td></tr>
+            { for state.uniforms.iter().map(|uniform| yew::html! {
+                <tr>
+                    <th>{ &uniform.name }</th>
+                    <td>
+                        { &uniform.value }
+                        if let Some(texture) = uniform.texture.as_ref() {
+                            { format!(" ({})", texture) }
+                        }
+                    </td>
+                </tr>
+            }) }
+        </table>
+    }
+}
+
+fn view_call(call: &Call) -> yew::Html {
+    let class = yew::classes!((!call.errors.is_empty()).then_some("wasmgl-capture-error"));
+    match call.state.as_ref() {
+        Some(state) => yew::html! {
+            <li {class}>
+                <details>
+                    <summary>{ signature(call) }</summary>
+                    { view_state(state) }
+                </details>
+            </li>
+        },
+        None => yew::html! {
+            <li {class}>{ signature(call) }</li>
+        },
+    }
+}
+
+/// Record the GL calls of a frame of the page shown, and list them with the
+/// state used by each draw
+pub struct CapturePanel {
+    capture: Option<Rc<Capture>>,
+}
+
+impl yew::Component for CapturePanel {
+    type Message = Message;
+    type Properties = CapturePanelProps;
+
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        capture::listen(Some(ctx.link().callback(|()| Message::Refresh)));
+        Self {
+            capture: capture::last(),
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Refresh => {
+                self.capture = capture::last();
+                true
+            }
+            Message::Capture => {
+                capture::arm();
+                false
+            }
+            Message::CaptureSetup => {
+                capture::arm();
+                ctx.props().onreload.emit(());
+                false
+            }
+            Message::Export => {
+                if let Some(capture) = self.capture.as_ref() {
+                    if let Err(error) = download("wasmgl-capture.json", &capture.to_json()) {
+                        log::error!("failed to export the capture: {:?}", error);
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        let link = ctx.link();
+        let capturing = capture::is_capturing();
+        yew::html! {
+            <div class="wasmgl-capture-panel">
+                <p>
+                    <button
+                        onclick={link.callback(|_| Message::Capture)}
+                        disabled={capturing}
+                        title="Record the calls from the next one to the end of its frame"
+                    >{ "CAPTURE FRAME" }</button>
+                    <button
+                        onclick={link.callback(|_| Message::CaptureSetup)}
+                        disabled={capturing}
+                        title="Create the page again and record its first calls"
+                    >{ "CAPTURE SETUP" }</button>
+                    <button
+                        onclick={link.callback(|_| Message::Export)}
+                        disabled={self.capture.is_none()}
+                    >{ "EXPORT JSON" }</button>
+                </p>
+                if capturing {
+                    <p>{ "Waiting for the page to draw" }</p>
+                } else if let Some(capture) = self.capture.as_ref() {
+                    <p>{ format!(
+                        "{} calls, {} draws, {} errors",
+                        capture.calls.len(),
+                        capture.draw_count(),
+                        capture.error_count(),
+                    ) }</p>
+                    <ol class="wasmgl-capture-calls">
+                        { for capture.calls.iter().map(view_call) }
+                    </ol>
+                }
+            </div>
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        capture::listen(None);
+    }
+}
@@ -0,0 +1,36 @@
+use crate::utils::context_loss;
+
+/// Simulate a loss of the WebGL contexts of the page shown, and restore them
+#[yew::function_component(ContextLossButton)]
+pub fn context_loss_button() -> yew::Html {
+    let force_update = yew::use_force_update();
+    // The pages set themselves up again on the restored contexts
+    yew::use_effect_with_deps(
+        move |_| {
+            context_loss::listen(Some(yew::Callback::from(move |()| {
+                force_update.force_update();
+            })));
+            || context_loss::listen(None)
+        },
+        (),
+    );
+
+    let context_lost = context_loss::is_lost();
+    let onclick = yew::Callback::from(move |_| {
+        if context_lost {
+            context_loss::restore_context();
+        } else {
+            context_loss::lose_context();
+        }
+    });
+
+    yew::html! {
+        <button
+            {onclick}
+            disabled={context_lost && !context_loss::is_simulated()}
+            title="Simulate a loss of the WebGL context with WEBGL_lose_context"
+        >
+            { if context_lost { "RESTORE CONTEXT" } else { "LOSE CONTEXT" } }
+        </button>
+    }
+}
@@ -0,0 +1,5 @@
+mod capture_panel;
+mod context_button;
+
+pub use self::capture_panel::CapturePanel;
+pub use self::context_button::ContextLossButton;
@@ -1 +1,3 @@
+pub mod inspector;
+pub mod shader_editor;
 pub mod sidenav;
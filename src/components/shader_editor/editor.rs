@@ -0,0 +1,99 @@
+use yew::{Children, Properties};
+use yew_router::hooks::use_route;
+
+use super::panel::ShaderPanel;
+use crate::components::inspector::{CapturePanel, ContextLossButton};
+use crate::pages::Page;
+use crate::utils::{capture, context_loss, shader_registry, GlslVersion};
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct ShaderEditorProps {
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// The page shown, with a side panel to edit its shaders
+#[yew::function_component(ShaderEditor)]
+pub fn shader_editor(props: &ShaderEditorProps) -> yew::Html {
+    let route = use_route::<Page>();
+    let last_route = yew::use_mut_ref(|| None);
+    // Before the new page creates its programs
+    if *last_route.borrow() != route {
+        shader_registry::clear();
+        capture::clear();
+        context_loss::clear();
+        *last_route.borrow_mut() = route;
+    }
+
+    let expanded = yew::use_state_eq(|| false);
+    let toggle = {
+        let expanded = expanded.clone();
+        yew::Callback::from(move |_| {
+            expanded.set(!*expanded);
+        })
+    };
+
+    // Bumped to create the page again
+    let generation = yew::use_mut_ref(|| 0u32);
+    let force_update = yew::use_force_update();
+    let onapply = {
+        let generation = generation.clone();
+        yew::Callback::from(move |()| {
+            shader_registry::forget_programs();
+            context_loss::clear();
+            *generation.borrow_mut() += 1;
+            force_update.force_update();
+        })
+    };
+
+    let version = shader_registry::version();
+    let toggle_version = {
+        let onapply = onapply.clone();
+        yew::Callback::from(move |_| {
+            shader_registry::set_version(match version {
+                GlslVersion::Es100 => GlslVersion::Es300,
+                GlslVersion::Es300 => GlslVersion::Es100,
+            });
+            onapply.emit(());
+        })
+    };
+
+    let capturing = yew::use_state_eq(|| false);
+    let toggle_capture = {
+        let capturing = capturing.clone();
+        let onapply = onapply.clone();
+        yew::Callback::from(move |_| {
+            // The page is created again on a wrapped context, or on the context
+            capture::enable(!*capturing);
+            capturing.set(!*capturing);
+            onapply.emit(());
+        })
+    };
+
+    let key = *generation.borrow();
+    yew::html! {
+        <div class="wasmgl-shader-editor">
+            <div class="wasmgl-shader-page" {key}>
+                {props.children.clone()}
+            </div>
+            <div class="wasmgl-shader-sidebar">
+                <button onclick={toggle}>
+                    { if *expanded { "HIDE SHADERS" } else { "SHADERS" } }
+                </button>
+                <button onclick={toggle_version} title="Switch the GLSL version of the shaders">
+                    { version.to_string().to_uppercase() }
+                </button>
+                <button onclick={toggle_capture} title="Record the GL calls of the page">
+                    { if *capturing { "HIDE CAPTURE" } else { "CAPTURE" } }
+                </button>
+                <ContextLossButton />
+                if *capturing {
+                    <CapturePanel onreload={onapply.clone()} />
+                }
+                if *expanded {
+                    <ShaderPanel {onapply} />
+                }
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,4 @@
+mod editor;
+mod panel;
+
+pub use self::editor::ShaderEditor;
@@ -0,0 +1,337 @@
+use web_sys::{HtmlTextAreaElement, InputEvent, WebGl2RenderingContext as GL};
+use yew::{Callback, Properties, TargetCast};
+
+use crate::utils::shader_registry::{self, RegisteredProgram, Sources};
+use crate::utils::{Diagnostic, ShaderError, ShaderStage, WebGl2RenderingContextExt};
+
+pub enum Message {
+    // The programs of the page changed
+    Refresh,
+    Select(usize),
+    Edit(ShaderStage, String),
+    Apply,
+    Reset,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ShaderPanelProps {
+    /// Called to create the page again with the edited shaders
+    pub onapply: Callback<()>,
+}
+
+/// Why the edited shaders could not be used
+#[derive(Debug, Default)]
+struct Errors {
+    vertex: Vec<Diagnostic>,
+    fragment: Vec<Diagnostic>,
+    // Failing to link or to create an object
+    other: Option<ShaderError>,
+}
+
+impl Errors {
+    fn is_empty(&self) -> bool {
+        self.vertex.is_empty() && self.fragment.is_empty() && self.other.is_none()
+    }
+}
+
+/// The shaders being edited, which may not compile
+#[derive(Debug)]
+struct Draft {
+    original: Sources,
+    vertex: String,
+    fragment: String,
+    errors: Errors,
+}
+
+impl Errors {
+    fn add(&mut self, error: ShaderError) {
+        match error {
+            ShaderError::Compile {
+                stage: ShaderStage::Vertex,
+                diagnostics,
+                ..
+            } => self.vertex = diagnostics,
+            ShaderError::Compile {
+                stage: ShaderStage::Fragment,
+                diagnostics,
+                ..
+            } => self.fragment = diagnostics,
+            error => self.other = Some(error),
+        }
+    }
+}
+
+/// Compile and link the shaders of `program`, only to check them
+fn check(program: &RegisteredProgram, vertex: &str, fragment: &str) -> Errors {
+    let (gl, preprocessor) = (&program.gl, &program.preprocessor);
+    let mut errors = Errors::default();
+    let vert_shader = gl
+        .compile_shader_with(preprocessor, GL::VERTEX_SHADER, vertex)
+        .map_err(|error| errors.add(error))
+        .ok();
+    let frag_shader = gl
+        .compile_shader_with(preprocessor, GL::FRAGMENT_SHADER, fragment)
+        .map_err(|error| errors.add(error))
+        .ok();
+    if let (Some(vert_shader), Some(frag_shader)) = (vert_shader.as_ref(), frag_shader.as_ref()) {
+        match gl.link_program_with_shaders(vert_shader, frag_shader) {
+            Ok(program) => gl.delete_program(Some(&program)),
+            Err(error) => errors.add(error),
+        }
+    }
+    gl.delete_shader(vert_shader.as_ref());
+    gl.delete_shader(frag_shader.as_ref());
+    errors
+}
+
+/// The source with the lines in error highlighted, followed by their messages
+fn listing(source: &str, diagnostics: &[Diagnostic]) -> yew::Html {
+    let lines = source.lines().enumerate().map(|(index, text)| {
+        let number = index as u32 + 1;
+        let messages = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.file.is_none() && diagnostic.line == Some(number))
+            .collect::<Vec<_>>();
+        let class = yew::classes!(
+            "wasmgl-shader-line",
+            (!messages.is_empty()).then_some("wasmgl-shader-line-error")
+        );
+        yew::html! {
+            <div {class}>
+                <span class="wasmgl-shader-line-number">{ number }</span>
+                { text }
+                { for messages.iter().map(|diagnostic| yew::html! {
+                    <div class="wasmgl-shader-message">
+                        { format!("{}: {}", diagnostic.severity, diagnostic.message) }
+                    </div>
+                }) }
+            </div>
+        }
+    });
+    let line_count = source.lines().count() as u32;
+    let others = diagnostics.iter().filter(|diagnostic| {
+        diagnostic.file.is_some() || diagnostic.line.is_none_or(|line| line > line_count)
+    });
+    yew::html! {
+        <>
+            if diagnostics.iter().any(|diagnostic| diagnostic.file.is_none() && diagnostic.line.is_some()) {
+                <pre class="wasmgl-shader-listing">{ for lines }</pre>
+            }
+            { for others.map(|diagnostic| yew::html! {
+                <p class="wasmgl-error">{ diagnostic.to_string() }</p>
+            }) }
+        </>
+    }
+}
+
+/// Edit the shaders of the programs created by the page shown
+pub struct ShaderPanel {
+    programs: Vec<RegisteredProgram>,
+    selected: usize,
+    draft: Option<Draft>,
+}
+
+impl ShaderPanel {
+    fn program(&self) -> Option<&RegisteredProgram> {
+        self.programs.get(self.selected)
+    }
+
+    fn edit(&mut self, stage: ShaderStage, text: String) {
+        let program = match self.program() {
+            Some(program) => program.clone(),
+            None => return,
+        };
+        let draft = self.draft.get_or_insert_with(|| {
+            let (vertex, fragment) = shader_registry::sources(&program.original);
+            Draft {
+                original: program.original.clone(),
+                vertex,
+                fragment,
+                errors: Errors::default(),
+            }
+        });
+        match stage {
+            ShaderStage::Vertex => draft.vertex = text,
+            ShaderStage::Fragment => draft.fragment = text,
+        }
+
+        draft.errors = check(&program, &draft.vertex, &draft.fragment);
+    }
+
+    /// Whether the draft compiles and differs from the shaders used
+    fn can_apply(&self) -> bool {
+        match (self.program(), self.draft.as_ref()) {
+            (Some(program), Some(draft)) => {
+                draft.errors.is_empty()
+                    && (draft.vertex.clone(), draft.fragment.clone())
+                        != shader_registry::sources(&program.original)
+            }
+            _ => false,
+        }
+    }
+
+    fn view_stage(
+        &self,
+        ctx: &yew::Context<Self>,
+        stage: ShaderStage,
+        source: String,
+        diagnostics: &[Diagnostic],
+    ) -> yew::Html {
+        let oninput = ctx.link().callback(move |event: InputEvent| {
+            let textarea = event.target_unchecked_into::<HtmlTextAreaElement>();
+            Message::Edit(stage, textarea.value())
+        });
+        let title = match stage {
+            ShaderStage::Vertex => "Vertex shader",
+            ShaderStage::Fragment => "Fragment shader",
+        };
+        let rows = source.lines().count().clamp(4, 24).to_string();
+        // What the source becomes once included files and headers are added,
+        // and once ported to GLSL ES 3.00
+        let compiled = self
+            .program()
+            .and_then(|program| program.preprocessor.process(stage, &source).ok());
+        yew::html! {
+            <div class="wasmgl-shader-stage">
+                <h4>{ title }</h4>
+                <textarea
+                    class="wasmgl-shader-source"
+                    spellcheck="false"
+                    {rows}
+                    value={source.clone()}
+                    {oninput}
+                />
+                { listing(&source, diagnostics) }
+                if let Some(compiled) = compiled {
+                    <details class="wasmgl-shader-compiled">
+                        <summary>{ "Compiled source" }</summary>
+                        <pre>{ compiled.source }</pre>
+                    </details>
+                }
+            </div>
+        }
+    }
+}
+
+impl yew::Component for ShaderPanel {
+    type Message = Message;
+    type Properties = ShaderPanelProps;
+
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        shader_registry::listen(Some(ctx.link().callback(|()| Message::Refresh)));
+        Self {
+            programs: shader_registry::programs(),
+            selected: 0,
+            draft: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Refresh => {
+                self.programs = shader_registry::programs();
+                // The programs are forgotten, then created again after an edit
+                if self.programs.is_empty() {
+                    return true;
+                }
+                if self.selected >= self.programs.len() {
+                    self.selected = 0;
+                }
+                let original = self.program().map(|program| &program.original);
+                if self.draft.as_ref().map(|draft| &draft.original) != original {
+                    self.draft = None;
+                }
+                true
+            }
+            Message::Select(index) => {
+                self.selected = index;
+                self.draft = None;
+                true
+            }
+            Message::Edit(stage, text) => {
+                self.edit(stage, text);
+                true
+            }
+            Message::Apply => {
+                if let (true, Some(program), Some(draft)) =
+                    (self.can_apply(), self.program(), self.draft.as_ref())
+                {
+                    let edited = (draft.vertex.clone(), draft.fragment.clone());
+                    let edited = Some(edited).filter(|edited| *edited != program.original);
+                    shader_registry::set_sources(&program.original, edited);
+                    ctx.props().onapply.emit(());
+                }
+                true
+            }
+            Message::Reset => {
+                if let Some(program) = self.program() {
+                    shader_registry::set_sources(&program.original, None);
+                    self.draft = None;
+                    ctx.props().onapply.emit(());
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        let program = match self.program() {
+            Some(program) => program,
+            None => {
+                return yew::html! {
+                    <div class="wasmgl-shader-panel">
+                        <p>{ "No shader program on this page" }</p>
+                    </div>
+                }
+            }
+        };
+        let link = ctx.link();
+        let tabs = (0..self.programs.len()).map(|index| {
+            yew::html! {
+                <button
+                    onclick={link.callback(move |_| Message::Select(index))}
+                    disabled={index == self.selected}
+                >{ format!("PROGRAM {}", index + 1) }</button>
+            }
+        });
+
+        let edited = shader_registry::sources(&program.original) != program.original;
+        let no_errors = Errors::default();
+        let (vertex, fragment, errors) = match self.draft.as_ref() {
+            Some(draft) => (draft.vertex.clone(), draft.fragment.clone(), &draft.errors),
+            None => {
+                let (vertex, fragment) = shader_registry::sources(&program.original);
+                (vertex, fragment, &no_errors)
+            }
+        };
+        yew::html! {
+            <div class="wasmgl-shader-panel">
+                if self.programs.len() > 1 {
+                    <p>{ for tabs }</p>
+                }
+                { self.view_stage(ctx, ShaderStage::Vertex, vertex, &errors.vertex) }
+                { self.view_stage(ctx, ShaderStage::Fragment, fragment, &errors.fragment) }
+                if let Some(error) = errors.other.as_ref() {
+                    <p class="wasmgl-error">{ error.to_string() }</p>
+                }
+                <p>
+                    <button
+                        onclick={link.callback(|_| Message::Apply)}
+                        disabled={!self.can_apply()}
+                        title="Create the page again with the edited shaders"
+                    >{ "APPLY" }</button>
+                    <button onclick={link.callback(|_| Message::Reset)} disabled={!edited && self.draft.is_none()}>
+                        { "RESET" }
+                    </button>
+                    if !errors.is_empty() {
+                        { " The page keeps the last shaders applied" }
+                    }
+                </p>
+            </div>
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        shader_registry::listen(None);
+    }
+}
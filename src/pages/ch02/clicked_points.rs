@@ -1,8 +1,8 @@
-use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{Event, HtmlCanvasElement, MouseEvent, WebGl2RenderingContext as GL};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, MouseEvent, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{canvas_position, webgl2_context, WebGl2RenderingContextExt};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -20,25 +20,18 @@ void main() {
 
 pub enum Message {
     Click(f32, f32),
+    ContextRestored,
 }
 
 fn on_click(ev: MouseEvent) -> Message {
-    let x = ev.client_x() as f32;
-    let y = ev.client_y() as f32;
-    let rect = AsRef::<Event>::as_ref(&ev)
-        .target()
-        .unwrap()
-        .dyn_into::<web_sys::Element>()
-        .unwrap()
-        .get_bounding_client_rect();
-
-    Message::Click(x - rect.left() as f32, y - rect.top() as f32)
+    let (x, y) = canvas_position(&ev);
+    Message::Click(x, y)
 }
 
 pub struct Page {
     gl: Option<GL>,
     canvas: NodeRef,
-    a_position: i32,
+    a_position: u32,
     points: Vec<(f32, f32)>,
 }
 
@@ -47,23 +40,15 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
         // Get the storage location of a_Position
-        let a_position = gl.get_attrib_location(&program, "a_Position");
-        if a_position < 0 {
-            return Err(JsError::new("Failed to get the storage location of a_Position").into());
-        }
+        let a_position = program.attrib_location("a_Position")?;
 
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
@@ -92,10 +77,10 @@ impl Page {
 
             for (x, y) in self.points.iter() {
                 // Pass the position of a point to a_Position variable
-                gl.vertex_attrib3f(self.a_position as u32, *x, *y, 0.0);
+                gl.vertex_attrib3f(self.a_position, *x, *y, 0.0);
 
                 // Draw
-                gl.draw_arrays(GL::POINTS, 0, 1);
+                gl.checked_draw_arrays(GL::POINTS, 0, 1);
             }
         }
         false
@@ -110,13 +95,17 @@ impl yew::Component for Page {
         Self {
             gl: None,
             canvas: NodeRef::default(),
-            a_position: -1,
+            a_position: 0,
             points: Vec::new(),
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
             Message::Click(x, y) => self.on_click(x, y),
         }
     }
@@ -133,9 +122,9 @@ impl yew::Component for Page {
         }
     }
 
-    fn rendered(&mut self, _ctx: &yew::Context<Self>, first_render: bool) {
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
         }
     }
 }
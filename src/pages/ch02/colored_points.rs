@@ -1,12 +1,12 @@
-use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{
-    Event, HtmlCanvasElement, MouseEvent, WebGl2RenderingContext as GL, WebGlUniformLocation,
-};
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, MouseEvent, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{
+    canvas_position, error_message, webgl2_context, ShaderProgram, WebGl2RenderingContextExt,
+};
 
-const VSHADER_SOURCE: &str = "#
+const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
 void main() {
     gl_Position = a_Position;
@@ -24,14 +24,16 @@ void main() {
 
 pub enum Message {
     Click(f32, f32),
+    ContextRestored,
 }
 
 pub struct Page {
     gl: Option<GL>,
     canvas: NodeRef,
-    a_position: i32,
-    u_frag_color: Option<WebGlUniformLocation>,
+    a_position: u32,
+    program: Option<ShaderProgram>,
     points: Vec<(f32, f32)>,
+    error: Option<String>,
 }
 
 impl Page {
@@ -39,29 +41,15 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
         // Get the storage location of a_Position
-        let a_position = gl.get_attrib_location(&program, "a_Position");
-        if a_position < 0 {
-            return Err(JsError::new("Failed to get the storage location of a_Position").into());
-        }
-
-        // Get the storage location of u_FragColor
-        let u_frag_color = gl.get_uniform_location(&program, "u_FragColor");
-        if u_frag_color.is_none() {
-            return Err(JsError::new("Failed to get the storage location of u_FragColor").into());
-        }
+        let a_position = program.attrib_location("a_Position")?;
 
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
@@ -71,12 +59,12 @@ impl Page {
 
         self.gl = Some(gl);
         self.a_position = a_position;
-        self.u_frag_color = u_frag_color;
+        self.program = Some(program);
         Ok(())
     }
 
-    fn on_click(&mut self, x: f32, y: f32) -> bool {
-        if let Some(gl) = self.gl.as_ref() {
+    fn on_click(&mut self, x: f32, y: f32) -> Result<(), JsError> {
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
             let canvas = self.get_canvas().unwrap();
 
             let half_width = (canvas.width() as f32) / 2.0;
@@ -91,15 +79,15 @@ impl Page {
 
             for (x, y) in self.points.iter() {
                 // Pass the position of a point to a_Position variable
-                gl.vertex_attrib3f(self.a_position as u32, *x, *y, 0.0);
+                gl.vertex_attrib3f(self.a_position, *x, *y, 0.0);
                 let (r, g, b, a) = color_of_point(*x, *y);
-                gl.uniform4f(self.u_frag_color.as_ref(), r, g, b, a);
+                program.set_uniform("u_FragColor", &[r, g, b, a])?;
 
                 // Draw
-                gl.draw_arrays(GL::POINTS, 0, 1);
+                gl.checked_draw_arrays(GL::POINTS, 0, 1);
             }
         }
-        false
+        Ok(())
     }
 }
 
@@ -111,48 +99,56 @@ impl yew::Component for Page {
         Self {
             gl: None,
             canvas: NodeRef::default(),
-            a_position: -1,
-            u_frag_color: None,
+            a_position: 0,
+            program: None,
             points: Vec::new(),
+            error: None,
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Message::Click(x, y) => self.on_click(x, y),
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
+            Message::Click(x, y) => match self.on_click(x, y) {
+                Ok(()) => false,
+                Err(error) => {
+                    self.error = Some(error_message(error));
+                    true
+                }
+            },
         }
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
         let onclick = ctx.link().callback(on_click);
         yew::html! {
-            <canvas
-              ref={self.canvas.clone()}
-              {onclick}
-              width="400"
-              height="400"
-            />
+            <div>
+                <canvas
+                  ref={self.canvas.clone()}
+                  {onclick}
+                  width="400"
+                  height="400"
+                />
+                if let Some(error) = self.error.as_ref() {
+                    <p class="wasmgl-error">{ error }</p>
+                }
+            </div>
         }
     }
 
-    fn rendered(&mut self, _ctx: &yew::Context<Self>, first_render: bool) {
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
         }
     }
 }
 
 fn on_click(ev: MouseEvent) -> Message {
-    let x = ev.client_x() as f32;
-    let y = ev.client_y() as f32;
-    let rect = AsRef::<Event>::as_ref(&ev)
-        .target()
-        .unwrap()
-        .dyn_into::<web_sys::Element>()
-        .unwrap()
-        .get_bounding_client_rect();
-
-    Message::Click(x - rect.left() as f32, y - rect.top() as f32)
+    let (x, y) = canvas_position(&ev);
+    Message::Click(x, y)
 }
 
 fn color_of_point(x: f32, y: f32) -> (f32, f32, f32, f32) {
@@ -1,4 +1,4 @@
-use wasm_bindgen::{JsError, JsValue};
+use wasm_bindgen::JsError;
 use web_sys::CanvasRenderingContext2d;
 
 #[yew::function_component(Page)]
@@ -12,7 +12,7 @@ pub fn page() -> yew::Html {
 }
 
 fn render(ctx: CanvasRenderingContext2d) -> Result<(), JsError> {
-    ctx.set_fill_style(&JsValue::from_str("rgba(0, 0, 255, 1.0)"));
+    ctx.set_fill_style_str("rgba(0, 0, 255, 1.0)");
     ctx.fill_rect(120.0, 10.0, 150.0, 150.0);
     Ok(())
 }
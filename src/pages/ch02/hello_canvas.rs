@@ -1,6 +1,8 @@
 use wasm_bindgen::JsError;
 use web_sys::WebGl2RenderingContext as GL;
 
+use crate::utils::{RenderBackend, WebGl2Backend};
+
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
     let canvas = yew::use_node_ref();
@@ -12,9 +14,29 @@ pub fn page() -> yew::Html {
 }
 
 fn render(gl: GL) -> Result<(), JsError> {
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
     // 指定清空<canvas>的颜色
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
     // 清空<canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::Rasterizer;
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |_| unreachable!("nothing is drawn"));
+        rasterizer.assert_golden("ch02/hello_canvas");
+    }
+}
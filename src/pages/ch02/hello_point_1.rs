@@ -1,7 +1,7 @@
 use wasm_bindgen::JsError;
 use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -27,15 +27,39 @@ void main() {
 ";
 
 fn render(gl: GL) -> Result<(), JsError> {
-    let _program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let _program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
     // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
     // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
     // Draw a point
-    gl.draw_arrays(GL::POINTS, 0, 1);
+    backend.draw_arrays(GL::POINTS, 0, 1);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use crate::utils::software::{DrawMode, Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let point = Vertex::new(
+            na::Vector4::new(0.0, 0.0, 0.0, 1.0),
+            na::Vector4::new(1.0, 0.0, 0.0, 1.0),
+        )
+        .point_size(10.0);
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.clear([0.0, 0.0, 0.0, 1.0]);
+        rasterizer.draw_arrays(DrawMode::Points, &[point]);
+        rasterizer.assert_golden("ch02/hello_point_1");
+    }
+}
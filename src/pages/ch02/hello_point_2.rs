@@ -1,7 +1,7 @@
 use wasm_bindgen::JsError;
 use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -28,26 +28,47 @@ void main() {
 ";
 
 fn render(gl: GL) -> Result<(), JsError> {
-    let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
-
-    // Get the storage location of a_Position
-    let a_position = gl.get_attrib_location(&program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
     // Pass vertex position to attribute variable
-    gl.vertex_attrib3f(a_position as u32, 0.0, 0.0, 0.0);
+    backend.vertex_attrib(&program, "a_Position", [0.0, 0.0, 0.0, 1.0])?;
 
     // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
     // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
     // Draw
-    gl.draw_arrays(GL::POINTS, 0, 1);
+    backend.draw_arrays(GL::POINTS, 0, 1);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.attribute("a_Position"),
+                na::Vector4::new(1.0, 0.0, 0.0, 1.0),
+            )
+            .point_size(10.0)
+        });
+        // The point of hello_point_1, its position set with vertexAttrib
+        rasterizer.assert_golden("ch02/hello_point_1");
+    }
+}
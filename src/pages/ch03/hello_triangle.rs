@@ -1,9 +1,8 @@
-use js_sys::Float32Array;
-use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL, WebGlProgram};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{webgl2_context, VertexArray, VertexLayout, WebGl2RenderingContextExt};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -34,6 +33,7 @@ pub enum Mode {
 
 pub enum Message {
     ChangeMode(Mode),
+    ContextRestored,
 }
 
 pub struct Page {
@@ -52,19 +52,15 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-        init_vertex_buffers(&gl, &program)?;
+        let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES)?;
 
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
@@ -80,7 +76,7 @@ impl Page {
         gl.clear(GL::COLOR_BUFFER_BIT);
 
         // Draw the rectangle
-        gl.draw_arrays(self.mode as u32, 0, N);
+        gl.checked_draw_arrays(self.mode as u32, 0, N);
     }
 }
 
@@ -106,8 +102,12 @@ impl yew::Component for Page {
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
             Message::ChangeMode(mode) => {
                 self.mode = mode;
                 if let Some(gl) = self.gl.as_ref() {
@@ -148,33 +148,36 @@ impl yew::Component for Page {
         }
     }
 
-    fn rendered(&mut self, _ctx: &yew::Context<Self>, first_render: bool) {
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw()
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
         }
     }
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use super::*;
+    use crate::utils::software::{attribute, DrawMode, Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let vertices = attribute(VERTICES, 2, 0, 2)
+            .into_iter()
+            .map(|position| Vertex::new(position, na::Vector4::new(1.0, 0.0, 0.0, 1.0)))
+            .collect::<Vec<_>>();
+        for (mode, name) in [
+            (Mode::Triangles, "triangles"),
+            (Mode::Lines, "lines"),
+            (Mode::LineStrip, "line_strip"),
+            (Mode::LineLoop, "line_loop"),
+        ] {
+            let mut rasterizer = Rasterizer::new(400, 400);
+            rasterizer.clear([0.0, 0.0, 0.0, 1.0]);
+            rasterizer.draw_arrays(DrawMode::from_gl(mode as u32), &vertices[..N as usize]);
+            rasterizer.assert_golden(&format!("ch03/hello_triangle_{}", name));
+        }
     }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    Ok(())
 }
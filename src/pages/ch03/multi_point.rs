@@ -1,8 +1,7 @@
-use js_sys::Float32Array;
 use wasm_bindgen::JsError;
-use web_sys::{WebGl2RenderingContext as GL, WebGlProgram};
+use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -34,42 +33,45 @@ const N: i32 = 3;
 const VERTICES: &[f32] = &[0.0, 0.5, -0.5, -0.5, 0.5, -0.5];
 
 fn render(gl: GL) -> Result<(), JsError> {
-    let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
     // Write the positions of vertices to a vertex shader
-    init_vertex_buffers(&gl, &program)?;
+    let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
 
     // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
     // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
     // Draw
-    gl.draw_arrays(GL::POINTS, 0, N);
+    backend.draw_arrays(GL::POINTS, 0, N);
     Ok(())
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use super::*;
+    use crate::utils::software::{attribute, DrawMode, Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let vertices = attribute(VERTICES, 2, 0, 2)
+            .into_iter()
+            .map(|position| {
+                Vertex::new(position, na::Vector4::new(1.0, 0.0, 0.0, 1.0)).point_size(10.0)
+            })
+            .collect::<Vec<_>>();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.clear([0.0, 0.0, 0.0, 1.0]);
+        rasterizer.draw_arrays(DrawMode::Points, &vertices[..N as usize]);
+        rasterizer.assert_golden("ch03/multi_point");
     }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    Ok(())
 }
@@ -1,8 +1,7 @@
-use js_sys::Float32Array;
 use wasm_bindgen::JsError;
-use web_sys::{WebGl2RenderingContext as GL, WebGlProgram};
+use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -45,55 +44,63 @@ const ANGLE: f32 = 90.0;
 const RADIAN: f32 = std::f32::consts::PI * ANGLE / 180.0;
 
 fn render(gl: GL) -> Result<(), JsError> {
-    let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
     // Write the positions of vertices to a vertex shader
-    init_vertex_buffers(&gl, &program)?;
+    let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
 
     // Pass the data required to rotate the shape to the vertex shader
 
     let cos_b = RADIAN.cos();
     let sin_b = RADIAN.sin();
 
-    let u_cos_b = gl.get_uniform_location(&program, "u_CosB");
-    let u_sin_b = gl.get_uniform_location(&program, "u_SinB");
-    if u_cos_b.is_none() || u_sin_b.is_none() {
-        return Err(JsError::new("Failed to get the storage location of u_CosB or u_SinB").into());
-    }
-    gl.uniform1f(u_cos_b.as_ref(), cos_b);
-    gl.uniform1f(u_sin_b.as_ref(), sin_b);
+    backend.set_uniform(&program, "u_CosB", cos_b.into())?;
+    backend.set_uniform(&program, "u_SinB", sin_b.into())?;
 
     // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
     // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
     // Draw
-    gl.draw_arrays(GL::TRIANGLES, 0, N);
+    backend.draw_arrays(GL::TRIANGLES, 0, N);
     Ok(())
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            let (position, cos_b, sin_b) = (
+                inputs.attribute("a_Position"),
+                inputs.float("u_CosB"),
+                inputs.float("u_SinB"),
+            );
+            let position = na::Vector4::new(
+                position.x * cos_b - position.y * sin_b,
+                position.x * sin_b + position.y * cos_b,
+                position.z,
+                1.0,
+            );
+            Vertex::new(position, na::Vector4::new(1.0, 0.0, 0.0, 1.0))
+        });
+        // The rotation of rotated_triangle_matrix, computed in the shader
+        rasterizer.assert_golden("ch03/rotated_triangle_matrix");
     }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    Ok(())
 }
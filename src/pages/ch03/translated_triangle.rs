@@ -1,8 +1,7 @@
-use js_sys::Float32Array;
 use wasm_bindgen::JsError;
-use web_sys::{WebGl2RenderingContext as GL, WebGlProgram};
+use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -39,51 +38,49 @@ const TY: f32 = 0.5;
 const TZ: f32 = 0.0;
 
 fn render(gl: GL) -> Result<(), JsError> {
-    let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
     // Write the positions of vertices to a vertex shader
-    init_vertex_buffers(&gl, &program)?;
+    let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
 
     // Pass the translation distance to the vertex shader
-    let u_translation = gl.get_uniform_location(&program, "u_Translation");
-    if u_translation.is_none() {
-        return Err(JsError::new(
-            "Failed to get the storage location of u_Translation",
-        ));
-    }
-    gl.uniform4f(u_translation.as_ref(), TX, TY, TZ, 0.0);
+    backend.set_uniform(&program, "u_Translation", [TX, TY, TZ, 0.0].into())?;
 
     // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
     // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
     // Draw
-    gl.draw_arrays(GL::TRIANGLES, 0, N);
+    backend.draw_arrays(GL::TRIANGLES, 0, N);
     Ok(())
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.attribute("a_Position") + inputs.vec4("u_Translation"),
+                na::Vector4::new(1.0, 0.0, 0.0, 1.0),
+            )
+        });
+        rasterizer.assert_golden("ch03/translated_triangle");
     }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    Ok(())
 }
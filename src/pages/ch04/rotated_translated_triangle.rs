@@ -1,12 +1,11 @@
-use js_sys::Float32Array;
 use nalgebra as na;
-use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{
-    HtmlCanvasElement, WebGl2RenderingContext as GL, WebGlProgram, WebGlUniformLocation,
-};
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{
+    webgl2_context, ShaderProgram, VertexArray, VertexLayout, WebGl2RenderingContextExt,
+};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -42,13 +41,14 @@ pub enum Mode {
 
 pub enum Message {
     ChangeMode(Mode),
+    ContextRestored,
 }
 
 pub struct Page {
     gl: Option<GL>,
     canvas: NodeRef,
     mode: Mode,
-    u_model_matrix: Option<WebGlUniformLocation>,
+    program: Option<ShaderProgram>,
 
     onclick_rotated_translated: yew::Callback<web_sys::MouseEvent>,
     onclick_translated_rotated: yew::Callback<web_sys::MouseEvent>,
@@ -59,35 +59,26 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-        init_vertex_buffers(&gl, &program)?;
-
-        let u_model_matrix = gl.get_uniform_location(&program, "u_ModelMatrix");
-        if u_model_matrix.is_none() {
-            return Err(JsError::new("Failed to get the storage location of u_ModelMatrix").into());
-        }
+        let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES)?;
 
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
 
-        self.u_model_matrix = u_model_matrix;
-        self.rerender_triangle(&gl);
+        self.rerender_triangle(&gl, &program)?;
         self.gl = Some(gl);
+        self.program = Some(program);
         Ok(())
     }
 
-    fn rerender_triangle(&self, gl: &GL) {
+    fn rerender_triangle(&self, gl: &GL, program: &ShaderProgram) -> Result<(), JsError> {
         let rotation = na::Matrix4::new_rotation(na::Vector3::new(0.0, 0.0, RADIAN));
         let translation = na::Matrix4::new_translation(&na::Vector3::new(TX, 0.0, 0.0));
         let model_matrix = match self.mode {
@@ -95,17 +86,14 @@ impl Page {
             Mode::TranslatedRotated => translation * rotation,
         };
         // Pass the rotation matrix to the vertex shader
-        gl.uniform_matrix4fv_with_f32_array(
-            self.u_model_matrix.as_ref(),
-            false,
-            model_matrix.as_slice(),
-        );
+        program.set_uniform("u_ModelMatrix", &model_matrix)?;
 
         // Clear <canvas>
         gl.clear(GL::COLOR_BUFFER_BIT);
 
         // Draw
-        gl.draw_arrays(GL::TRIANGLES, 0, N);
+        gl.checked_draw_arrays(GL::TRIANGLES, 0, N);
+        Ok(())
     }
 }
 
@@ -123,19 +111,23 @@ impl yew::Component for Page {
             gl: None,
             canvas: NodeRef::default(),
             mode: Mode::RotatedTranslated,
-            u_model_matrix: None,
+            program: None,
 
             onclick_rotated_translated,
             onclick_translated_rotated,
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
             Message::ChangeMode(mode) => {
                 self.mode = mode;
-                if let Some(gl) = self.gl.as_ref() {
-                    self.rerender_triangle(gl);
+                if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+                    self.rerender_triangle(gl, program).unwrap_throw();
                 }
                 true
             }
@@ -164,33 +156,9 @@ impl yew::Component for Page {
         }
     }
 
-    fn rendered(&mut self, _ctx: &yew::Context<Self>, first_render: bool) {
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
         }
     }
 }
-
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    Ok(())
-}
@@ -1,13 +1,12 @@
-use gloo::render::AnimationFrame;
-use js_sys::Float32Array;
 use nalgebra as na;
-use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{
-    HtmlCanvasElement, WebGl2RenderingContext as GL, WebGlProgram, WebGlUniformLocation,
-};
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{
+    error_message, webgl2_context, AnimationLoop, Frame, ShaderProgram, VertexArray, VertexLayout,
+    WebGl2RenderingContextExt,
+};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -34,24 +33,30 @@ const ANGLE_STEP_STEP: f32 = 10.0;
 // Convert to radians
 const RADIAN_STEP: f32 = std::f32::consts::PI * ANGLE_STEP / 180.0;
 const RADIAN_STEP_STEP: f32 = std::f32::consts::PI * ANGLE_STEP_STEP / 180.0;
+// Advance the animation 60 times per second whatever the display rate
+const TIMESTEP: f64 = 1000.0 / 60.0;
 
 pub enum Message {
-    Animate(f64),
+    Animate(Frame),
     SpeedUp,
     SpeedDown,
+    TogglePause,
+    ContextRestored,
 }
 
 pub struct Page {
     gl: Option<GL>,
     canvas: NodeRef,
-    tick: Option<AnimationFrame>,
-    u_model_matrix: Option<WebGlUniformLocation>,
+    animation: Option<AnimationLoop>,
+    program: Option<ShaderProgram>,
     step: f32,
     last_radian: f32,
-    last_render: Option<f64>,
+    last_frame: Option<Frame>,
+    error: Option<String>,
 
     onclick_speed_up: yew::Callback<web_sys::MouseEvent>,
     onclick_speed_down: yew::Callback<web_sys::MouseEvent>,
+    onclick_pause: yew::Callback<web_sys::MouseEvent>,
 }
 
 impl Page {
@@ -62,73 +67,53 @@ impl Page {
     fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
         // Write the positions of vertices to a vertex shader
-        init_vertex_buffers(&gl, &program)?;
-
-        // Pass the rotation matrix to the vertex shader
-        let u_model_matrix = gl.get_uniform_location(&program, "u_ModelMatrix");
-        if u_model_matrix.is_none() {
-            return Err(JsError::new("Failed to get the storage location of u_ModelMatrix").into());
-        }
+        let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES)?;
 
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
 
-        self.u_model_matrix = u_model_matrix;
         self.gl = Some(gl);
-        self.reset_tick(link);
+        self.program = Some(program);
+        self.animation = Some(AnimationLoop::with_fixed_timestep(TIMESTEP, move |frame| {
+            link.send_message(Message::Animate(frame))
+        }));
 
         Ok(())
     }
 
-    fn reset_tick(&mut self, link: yew::html::Scope<Self>) {
-        let on_animate = move |now: f64| {
-            link.send_message(Message::Animate(now));
-        };
-
-        // A reference to the new handle must be retained for the next render to run.
-        self.tick = Some(gloo::render::request_animation_frame(on_animate));
-    }
-
-    fn animate(&mut self, link: yew::html::Scope<Self>, now: f64) {
-        if let Some(gl) = self.gl.as_ref() {
-            let elapsed = self
-                .last_render
-                .map(|last_render| now - last_render)
-                .unwrap_or(0.0);
-            self.last_render = Some(now);
-
-            let radian = self.last_radian + (self.step * elapsed as f32) / 1000.0;
+    fn animate(&mut self, frame: Frame) -> Result<(), JsError> {
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            let radian = self.last_radian + (self.step * frame.delta as f32) / 1000.0;
             self.last_radian = radian % std::f32::consts::TAU;
 
             let model_matrix =
                 na::Matrix4::new_rotation(na::Vector3::new(0.0, 0.0, self.last_radian))
                     * na::Matrix4::new_translation(&na::Vector3::new(0.35f32, 0.0, 0.0));
-            gl.uniform_matrix4fv_with_f32_array(
-                self.u_model_matrix.as_ref(),
-                false,
-                model_matrix.as_slice(),
-            );
+            // Pass the rotation matrix to the vertex shader
+            program.set_uniform("u_ModelMatrix", &model_matrix)?;
 
             // Clear <canvas>
             gl.clear(GL::COLOR_BUFFER_BIT);
 
             // Draw
-            gl.draw_arrays(GL::TRIANGLES, 0, N);
+            gl.checked_draw_arrays(GL::TRIANGLES, 0, N);
 
-            self.reset_tick(link);
-        } else {
-            self.tick = None;
+            self.last_frame = Some(frame);
         }
+        Ok(())
+    }
+
+    fn is_paused(&self) -> bool {
+        self.animation
+            .as_ref()
+            .map(AnimationLoop::is_paused)
+            .unwrap_or(false)
     }
 }
 
@@ -140,26 +125,39 @@ impl yew::Component for Page {
         let link = ctx.link();
         let onclick_speed_up = link.callback(|_| Message::SpeedUp);
         let onclick_speed_down = link.callback(|_| Message::SpeedDown);
+        let onclick_pause = link.callback(|_| Message::TogglePause);
         Self {
             gl: None,
             canvas: NodeRef::default(),
-            tick: None,
+            animation: None,
             step: RADIAN_STEP,
-            u_model_matrix: None,
+            program: None,
             last_radian: 0.0,
-            last_render: None,
+            last_frame: None,
+            error: None,
 
             onclick_speed_up,
             onclick_speed_down,
+            onclick_pause,
         }
     }
 
     fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Message::Animate(now) => {
-                self.animate(ctx.link().clone(), now);
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
                 false
             }
+            Message::Animate(frame) => match self.animate(frame) {
+                // Refresh the frame counter twice per second
+                Ok(()) => frame.count % 30 == 0,
+                Err(error) => {
+                    // Stop the animation rather than failing every frame
+                    self.animation = None;
+                    self.error = Some(error_message(error));
+                    true
+                }
+            },
             Message::SpeedUp => {
                 if self.step < std::f32::consts::TAU {
                     self.step += RADIAN_STEP_STEP;
@@ -172,10 +170,24 @@ impl yew::Component for Page {
                 }
                 false
             }
+            Message::TogglePause => {
+                if let Some(animation) = self.animation.as_ref() {
+                    if animation.is_paused() {
+                        animation.resume();
+                    } else {
+                        animation.pause();
+                    }
+                }
+                true
+            }
         }
     }
 
     fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
+        let (count, elapsed) = self
+            .last_frame
+            .map(|frame| (frame.count, frame.elapsed / 1000.0))
+            .unwrap_or((0, 0.0));
         yew::html! {
             <div>
                 <canvas
@@ -190,7 +202,14 @@ impl yew::Component for Page {
                     <button
                         onclick={self.onclick_speed_down.clone()}
                     >{ "DOWN" }</button>
+                    <button
+                        onclick={self.onclick_pause.clone()}
+                    >{ if self.is_paused() { "RESUME" } else { "PAUSE" } }</button>
                 </p>
+                <p>{ format!("frame {} / {:.1}s", count, elapsed) }</p>
+                if let Some(error) = self.error.as_ref() {
+                    <p class="wasmgl-error">{ error }</p>
+                }
             </div>
         }
     }
@@ -203,30 +222,6 @@ impl yew::Component for Page {
 
     fn destroy(&mut self, _ctx: &yew::Context<Self>) {
         // cancelAnimationFrame
-        self.tick = None;
+        self.animation = None;
     }
 }
-
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    Ok(())
-}
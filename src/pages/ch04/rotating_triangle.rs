@@ -1,13 +1,38 @@
-use gloo::render::AnimationFrame;
-use js_sys::Float32Array;
 use nalgebra as na;
-use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{
-    HtmlCanvasElement, WebGl2RenderingContext as GL, WebGlProgram, WebGlUniformLocation,
-};
-use yew::NodeRef;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{Frame, RenderBackend, VertexLayout, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    let scene = yew::use_mut_ref(|| None);
+    {
+        let scene = scene.clone();
+        crate::utils::use_webgl2_canvas_render(canvas.clone(), move |gl| {
+            let mut backend = WebGl2Backend::new(gl);
+            let scene_objects = setup(&mut backend)?;
+            *scene.borrow_mut() = Some((backend, scene_objects));
+            Ok(())
+        });
+    }
+    {
+        let mut last_radian = 0.0;
+        crate::utils::use_animation_loop(move |frame: Frame| {
+            if let Some((backend, (program, vao))) = scene.borrow_mut().as_mut() {
+                let radian = last_radian + (RADIAN_STEP * frame.delta as f32) / 1000.0;
+                last_radian = radian % std::f32::consts::TAU;
+                draw(backend, program, vao, last_radian)?;
+            }
+            Ok(())
+        });
+    }
 
-use crate::utils::WebGl2RenderingContextExt;
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -33,164 +58,108 @@ const ANGLE_STEP: f32 = 45.0;
 // Convert to radians
 const RADIAN_STEP: f32 = std::f32::consts::PI * ANGLE_STEP / 180.0;
 
-pub enum Message {
-    Animate(f64),
-}
-
-pub struct Page {
-    gl: Option<GL>,
-    canvas: NodeRef,
-    tick: Option<AnimationFrame>,
-    u_model_matrix: Option<WebGlUniformLocation>,
-    last_radian: f32,
-    last_render: Option<f64>,
-}
-
-impl Page {
-    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
-        self.canvas.cast::<HtmlCanvasElement>()
-    }
-
-    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
-        let canvas = self.get_canvas().unwrap();
-
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
-
-        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
-
-        // Write the positions of vertices to a vertex shader
-        init_vertex_buffers(&gl, &program)?;
+fn setup<B: RenderBackend>(backend: &mut B) -> Result<(B::Program, B::VertexArray), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-        // Pass the rotation matrix to the vertex shader
-        let u_model_matrix = gl.get_uniform_location(&program, "u_ModelMatrix");
-        if u_model_matrix.is_none() {
-            return Err(JsError::new("Failed to get the storage location of u_ModelMatrix").into());
-        }
+    // Write the positions of vertices to a vertex shader
+    let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+    let vao = backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
 
-        // Specify the color for clearing <canvas>
-        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    // Specify the color for clearing <canvas>
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
-        self.u_model_matrix = u_model_matrix;
-        self.gl = Some(gl);
-        self.reset_tick(link);
-
-        Ok(())
-    }
-
-    fn reset_tick(&mut self, link: yew::html::Scope<Self>) {
-        let on_animate = move |now: f64| {
-            link.send_message(Message::Animate(now));
-        };
-
-        // A reference to the new handle must be retained for the next render to run.
-        self.tick = Some(gloo::render::request_animation_frame(on_animate));
-    }
+    Ok((program, vao))
+}
 
-    fn animate(&mut self, link: yew::html::Scope<Self>, now: f64) {
-        if let Some(gl) = self.gl.as_ref() {
-            let elapsed = self
-                .last_render
-                .map(|last_render| now - last_render)
-                .unwrap_or(0.0);
-            self.last_render = Some(now);
-
-            let radian = self.last_radian + (RADIAN_STEP * elapsed as f32) / 1000.0;
-            self.last_radian = radian % std::f32::consts::TAU;
-
-            let model_matrix =
-                na::Matrix4::new_rotation(na::Vector3::new(0.0, 0.0, self.last_radian));
-            gl.uniform_matrix4fv_with_f32_array(
-                self.u_model_matrix.as_ref(),
-                false,
-                model_matrix.as_slice(),
-            );
-
-            // Clear <canvas>
-            gl.clear(GL::COLOR_BUFFER_BIT);
-
-            // Draw
-            gl.draw_arrays(GL::TRIANGLES, 0, N);
-
-            self.reset_tick(link);
-        } else {
-            self.tick = None;
-        }
-    }
+fn model_matrix(radian: f32) -> na::Matrix4<f32> {
+    na::Matrix4::new_rotation(na::Vector3::new(0.0, 0.0, radian))
 }
 
-impl yew::Component for Page {
-    type Message = Message;
-    type Properties = ();
-
-    fn create(_ctx: &yew::Context<Self>) -> Self {
-        Self {
-            gl: None,
-            canvas: NodeRef::default(),
-            tick: None,
-            u_model_matrix: None,
-            last_radian: 0.0,
-            last_render: None,
-        }
-    }
+fn draw<B: RenderBackend>(
+    backend: &mut B,
+    program: &B::Program,
+    vao: &B::VertexArray,
+    radian: f32,
+) -> Result<(), JsError> {
+    backend.use_program(program);
+    backend.bind_vertex_array(vao);
 
-    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            Message::Animate(now) => {
-                self.animate(ctx.link().clone(), now);
-                false
-            }
-        }
-    }
+    // Pass the rotation matrix to the vertex shader
+    backend.set_uniform(program, "u_ModelMatrix", model_matrix(radian).into())?;
 
-    fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
-        yew::html! {
-            <div>
-                <canvas
-                    ref={self.canvas.clone()}
-                    width="400"
-                    height="400"
-                />
-            </div>
-        }
-    }
+    // Clear <canvas>
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
-    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
-        if first_render {
-            self.setup_gl(ctx.link().clone()).unwrap_throw();
-        }
-    }
-
-    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
-        // cancelAnimationFrame
-        self.tick = None;
-    }
+    // Draw
+    backend.draw_arrays(GL::TRIANGLES, 0, N);
+    Ok(())
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backend::UniformValue;
+    use crate::utils::recording_backend::{Call, RecordingBackend};
+    use crate::utils::software::{attribute, DrawMode, Rasterizer, Vertex};
+
+    #[test]
+    fn draws_the_rotated_triangle() {
+        let mut backend = RecordingBackend::default();
+        let (program, vao) = setup(&mut backend).unwrap();
+        backend.take_calls();
+
+        draw(&mut backend, &program, &vao, std::f32::consts::FRAC_PI_2).unwrap();
+        let calls = backend.take_calls();
+        assert_eq!(
+            calls[..2],
+            [Call::UseProgram(program), Call::BindVertexArray(vao)]
+        );
+        let matrix = match &calls[2] {
+            Call::SetUniform {
+                name,
+                value: UniformValue::Mat4(matrix),
+                ..
+            } if name == "u_ModelMatrix" => *matrix,
+            call => panic!("unexpected {:?}", call),
+        };
+        // A quarter turn counterclockwise
+        #[rustfmt::skip]
+        let expected = na::Matrix4::new(
+            0.0, -1.0, 0.0, 0.0,
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+        assert!((matrix - expected).abs().max() < 1e-6);
+        assert_eq!(
+            calls[3..],
+            [
+                Call::Clear(GL::COLOR_BUFFER_BIT),
+                Call::DrawArrays {
+                    mode: GL::TRIANGLES,
+                    first: 0,
+                    count: 3
+                },
+            ]
+        );
     }
 
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    Ok(())
+    #[test]
+    fn scene() {
+        // After a second
+        let model_matrix = model_matrix(RADIAN_STEP);
+        let vertices = attribute(VERTICES, 2, 0, 2)
+            .into_iter()
+            .map(|position| {
+                Vertex::new(
+                    model_matrix * position,
+                    na::Vector4::new(1.0, 0.0, 0.0, 1.0),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.clear([0.0, 0.0, 0.0, 1.0]);
+        rasterizer.draw_arrays(DrawMode::Triangles, &vertices[..N as usize]);
+        rasterizer.assert_golden("ch04/rotating_triangle");
+    }
 }
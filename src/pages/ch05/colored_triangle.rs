@@ -1,9 +1,8 @@
-use js_sys::Float32Array;
-use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL, WebGlProgram};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{webgl2_context, VertexArray, VertexLayout, WebGl2RenderingContextExt};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -34,8 +33,6 @@ const VERTICES_COLORS: &[f32] = &[
     0.5, -0.5, 0.0, 0.0, 1.0, // the 3rd point
 ];
 
-const FSIZE: i32 = std::mem::size_of::<f32>() as i32;
-
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
@@ -45,6 +42,7 @@ pub enum Mode {
 
 pub enum Message {
     ChangeMode(Mode),
+    ContextRestored,
 }
 
 pub struct Page {
@@ -61,19 +59,17 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-        init_vertex_buffers(&gl, &program)?;
+        let layout = VertexLayout::new()
+            .attribute("a_Position", 2, GL::FLOAT, false)
+            .attribute("a_Color", 3, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES_COLORS)?;
 
         // Unbind the buffer object
         gl.bind_buffer(GL::ARRAY_BUFFER, None);
@@ -92,7 +88,7 @@ impl Page {
         gl.clear(GL::COLOR_BUFFER_BIT);
 
         // Draw
-        gl.draw_arrays(self.mode as u32, 0, N);
+        gl.checked_draw_arrays(self.mode as u32, 0, N);
     }
 }
 
@@ -114,8 +110,12 @@ impl yew::Component for Page {
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
             Message::ChangeMode(mode) => {
                 self.mode = mode;
                 if let Some(gl) = self.gl.as_ref() {
@@ -148,43 +148,32 @@ impl yew::Component for Page {
         }
     }
 
-    fn rendered(&mut self, _ctx: &yew::Context<Self>, first_render: bool) {
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
         }
     }
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices_colors = Float32Array::from(VERTICES_COLORS);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices_colors = unsafe { Float32Array::view(VERTICES_COLORS) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_colors, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, FSIZE * 5, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    let a_color = gl.get_attrib_location(program, "a_Color");
-    if a_color < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Color",
-        ));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::software::{attribute, DrawMode, Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let positions = attribute(VERTICES_COLORS, 5, 0, 2);
+        let colors = attribute(VERTICES_COLORS, 5, 2, 3);
+        let vertices = positions
+            .into_iter()
+            .zip(colors)
+            .map(|(position, color)| Vertex::new(position, color).point_size(10.0))
+            .collect::<Vec<_>>();
+        for (mode, name) in [(Mode::Points, "points"), (Mode::Triangles, "triangles")] {
+            let mut rasterizer = Rasterizer::new(400, 400);
+            rasterizer.clear([0.0, 0.0, 0.0, 1.0]);
+            rasterizer.draw_arrays(DrawMode::from_gl(mode as u32), &vertices[..N as usize]);
+            rasterizer.assert_golden(&format!("ch05/colored_triangle_{}", name));
+        }
     }
-
-    gl.vertex_attrib_pointer_with_i32(a_color as u32, 3, GL::FLOAT, false, FSIZE * 5, FSIZE * 2);
-    gl.enable_vertex_attrib_array(a_color as u32);
-
-    Ok(())
 }
@@ -1,8 +1,7 @@
-use js_sys::Float32Array;
 use wasm_bindgen::JsError;
-use web_sys::{WebGl2RenderingContext as GL, WebGlProgram};
+use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -36,69 +35,52 @@ const VERTICES: &[f32] = &[0.0, 0.5, -0.5, -0.5, 0.5, -0.5];
 const SIZES: &[f32] = &[10.0, 20.0, 30.0];
 
 fn render(gl: GL) -> Result<(), JsError> {
-    let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
 
-    init_vertex_buffers(&gl, &program)?;
-    init_size_buffers(&gl, &program)?;
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-    // Unbind the buffer object
-    gl.bind_buffer(GL::ARRAY_BUFFER, None);
+    // Positions and sizes live in separate buffers
+    let position_layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+    let size_layout = VertexLayout::new().attribute("a_PointSize", 1, GL::FLOAT, false);
+    backend.create_vertex_array(
+        &program,
+        &[(&position_layout, VERTICES), (&size_layout, SIZES)],
+        None,
+    )?;
 
     // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
     // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
     // Draw
-    gl.draw_arrays(GL::POINTS, 0, N);
-    Ok(())
-}
-
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
+    backend.draw_arrays(GL::POINTS, 0, N);
     Ok(())
 }
 
-fn init_size_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let sizes = Float32Array::from(SIZES);
-    // use view() instead of from() to avoid additional memory allocation
-    let sizes = unsafe { Float32Array::view(SIZES) };
-    let size_buffer = gl.create_buffer();
-    if size_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, size_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &sizes, GL::STATIC_DRAW);
-
-    let a_point_size = gl.get_attrib_location(program, "a_PointSize");
-    if a_point_size < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_PointSize",
-        ));
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.attribute("a_Position"),
+                na::Vector4::new(1.0, 0.0, 0.0, 1.0),
+            )
+            .point_size(inputs.attribute("a_PointSize").x)
+        });
+        rasterizer.assert_golden("ch05/multi_attribute_size");
     }
-
-    gl.vertex_attrib_pointer_with_i32(a_point_size as u32, 1, GL::FLOAT, false, 0, 0);
-    gl.enable_vertex_attrib_array(a_point_size as u32);
-
-    Ok(())
 }
@@ -1,8 +1,7 @@
-use js_sys::Float32Array;
 use wasm_bindgen::JsError;
-use web_sys::{WebGl2RenderingContext as GL, WebGlProgram};
+use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -39,64 +38,50 @@ const VERTICES_SIZES: &[f32] = &[
     0.5, -0.5, 30.0, // the 3rd point
 ];
 
-const FSIZE: i32 = std::mem::size_of::<f32>() as i32;
-
 fn render(gl: GL) -> Result<(), JsError> {
-    let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
 
-    init_vertex_buffers(&gl, &program)?;
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-    // Unbind the buffer object
-    gl.bind_buffer(GL::ARRAY_BUFFER, None);
+    let layout = VertexLayout::new()
+        .attribute("a_Position", 2, GL::FLOAT, false)
+        .attribute("a_PointSize", 1, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES_SIZES)], None)?;
 
     // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
 
     // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
+    backend.clear(GL::COLOR_BUFFER_BIT);
 
     // Draw
-    gl.draw_arrays(GL::POINTS, 0, N);
+    backend.draw_arrays(GL::POINTS, 0, N);
     Ok(())
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices_sizes = Float32Array::from(VERTICES_SIZES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices_sizes = unsafe { Float32Array::view(VERTICES_SIZES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_sizes, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
+#[cfg(test)]
+mod tests {
+    use nalgebra as na;
+
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.attribute("a_Position"),
+                na::Vector4::new(1.0, 0.0, 0.0, 1.0),
+            )
+            .point_size(inputs.attribute("a_PointSize").x)
+        });
+        // The points of multi_attribute_size, from a single buffer
+        rasterizer.assert_golden("ch05/multi_attribute_size");
     }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, FSIZE * 3, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    let a_point_size = gl.get_attrib_location(program, "a_PointSize");
-    if a_point_size < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_PointSize",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(
-        a_point_size as u32,
-        1,
-        GL::FLOAT,
-        false,
-        FSIZE * 3,
-        FSIZE * 2,
-    );
-    gl.enable_vertex_attrib_array(a_point_size as u32);
-
-    Ok(())
 }
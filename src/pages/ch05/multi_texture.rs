@@ -1,12 +1,11 @@
-use js_sys::Float32Array;
-use wasm_bindgen::{closure::Closure, JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{
-    HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as GL, WebGlProgram, WebGlTexture,
-    WebGlUniformLocation,
-};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{
+    error_message, load_image, webgl2_context, ShaderProgram, Texture, VertexArray, VertexLayout,
+    WebGl2RenderingContextExt,
+};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -34,29 +33,25 @@ void main() {
 const N: i32 = 4;
 
 // Vertex coordinates, texture coordinate
-const VERTICES_COLORS: &[f32] = &[
+const VERTICES_TEX_COORDS: &[f32] = &[
     -0.5, 0.5, 0.0, 1.0, //
     -0.5, -0.5, 0.0, 0.0, //
     0.5, 0.5, 1.0, 1.0, //
     0.5, -0.5, 1.0, 0.0, //
 ];
 
-const FSIZE: i32 = std::mem::size_of::<f32>() as i32;
-
 pub enum Message {
-    Texture0Loaded(HtmlImageElement),
-    Texture1Loaded(HtmlImageElement),
+    TexturesLoaded(HtmlImageElement, HtmlImageElement),
+    LoadFailed(String),
+    ContextRestored,
 }
 
 pub struct Page {
     gl: Option<GL>,
     canvas: NodeRef,
-    texture0: Option<WebGlTexture>,
-    texture1: Option<WebGlTexture>,
-    texture0_actived: bool,
-    texture1_actived: bool,
-    u_sampler0: Option<WebGlUniformLocation>,
-    u_sampler1: Option<WebGlUniformLocation>,
+    textures: Vec<Texture>,
+    program: Option<ShaderProgram>,
+    error: Option<String>,
 }
 
 impl Page {
@@ -64,31 +59,17 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-        init_vertex_buffers(&gl, &program)?;
-
-        let texture0 = gl.create_texture();
-        let texture1 = gl.create_texture();
-        if texture0.is_none() || texture1.is_none() {
-            return Err(JsError::new("Failed to create the texture object").into());
-        }
-
-        let u_sampler0 = gl.get_uniform_location(&program, "u_Sampler0");
-        let u_sampler1 = gl.get_uniform_location(&program, "u_Sampler0");
-        if u_sampler0.is_none() || u_sampler1.is_none() {
-            return Err(JsError::new("Failed to get the storage location of u_Sampler").into());
-        }
+        let layout = VertexLayout::new()
+            .attribute("a_Position", 2, GL::FLOAT, false)
+            .attribute("a_TexCoord", 2, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES_TEX_COORDS)?;
 
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
@@ -97,111 +78,45 @@ impl Page {
         gl.clear(GL::COLOR_BUFFER_BIT);
 
         self.gl = Some(gl);
-        self.texture0 = texture0;
-        self.texture1 = texture1;
-        self.u_sampler0 = u_sampler0;
-        self.u_sampler1 = u_sampler1;
+        self.program = Some(program);
         Ok(())
     }
 
-    fn request_texture0(&mut self, link: yew::html::Scope<Self>) {
-        self.request_texture(
-            "/resources/sky.jpg",
-            Box::new(move |event| {
-                let image = event.target().unwrap().dyn_into().unwrap();
-                link.send_message(Message::Texture0Loaded(image));
-            }),
-        );
-    }
-
-    fn request_texture1(&mut self, link: yew::html::Scope<Self>) {
-        self.request_texture(
-            "/resources/circle.gif",
-            Box::new(move |event| {
-                let image = event.target().unwrap().dyn_into().unwrap();
-                link.send_message(Message::Texture1Loaded(image));
-            }),
-        );
-    }
-
-    fn request_texture(&mut self, src: &str, callback: Box<dyn FnMut(web_sys::Event)>) {
-        let image = gloo::utils::document()
-            .create_element("img")
-            .unwrap()
-            .dyn_into::<web_sys::HtmlImageElement>()
-            .unwrap();
-        image.set_cross_origin(Some("anonymous"));
-        image.set_src(src);
-        let closure = Closure::wrap(callback);
-        image.set_onload(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
-        let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            // log::error!("failed loading texture");
-            gloo::console::error!("failed loading texture");
-        }) as Box<dyn FnMut(_)>);
-        image.set_onerror(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
-    }
-
-    fn load_texture0(&mut self, image: HtmlImageElement) -> Result<(), JsValue> {
-        self.texture0_actived = true;
-        self.load_texture(
-            self.u_sampler0.as_ref(),
-            self.texture0.as_ref(),
-            GL::TEXTURE0,
-            0,
-            image,
-        )
-    }
-
-    fn load_texture1(&mut self, image: HtmlImageElement) -> Result<(), JsValue> {
-        self.texture1_actived = true;
-        self.load_texture(
-            self.u_sampler1.as_ref(),
-            self.texture1.as_ref(),
-            GL::TEXTURE1,
-            1,
-            image,
-        )
+    fn request_textures(&mut self, link: yew::html::Scope<Self>) {
+        // Both images are requested right away and loaded in parallel
+        let image0 = load_image("/resources/sky.jpg");
+        let image1 = load_image("/resources/circle.gif");
+        link.send_future(async move {
+            match (image0.await, image1.await) {
+                (Ok(image0), Ok(image1)) => Message::TexturesLoaded(image0, image1),
+                (Err(err), _) | (_, Err(err)) => Message::LoadFailed(err.to_string()),
+            }
+        });
     }
 
-    fn load_texture(
-        &self,
-        location: Option<&WebGlUniformLocation>,
-        texture: Option<&WebGlTexture>,
-        gl_unit: u32,
-        unit: i32,
-        image: HtmlImageElement,
+    fn load_textures(
+        &mut self,
+        image0: HtmlImageElement,
+        image1: HtmlImageElement,
     ) -> Result<(), JsValue> {
-        if let Some(gl) = self.gl.as_ref() {
-            gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, 1);
-            // Make the texture unit active
-            gl.active_texture(gl_unit);
-            // Bind the texture object to the target
-            gl.bind_texture(GL::TEXTURE_2D, texture);
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            let builder = Texture::builder()
+                .filter(GL::LINEAR, GL::LINEAR)
+                .flip_y(true);
+            let texture0 = builder.clone().unit(0).build(gl, &image0)?;
+            let texture1 = builder.unit(1).build(gl, &image1)?;
 
-            // Set the texture parameters
-            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::LINEAR as i32);
-            // Set the texture image
-            gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
-                GL::TEXTURE_2D,
-                0,
-                GL::RGBA as i32,
-                GL::RGBA,
-                GL::UNSIGNED_BYTE,
-                &image,
-            )?;
+            // Set the texture units to the samplers
+            program.set_uniform("u_Sampler0", &texture0.unit())?;
+            program.set_uniform("u_Sampler1", &texture1.unit())?;
 
-            // Set the texture unit 0 to the sampler
-            gl.uniform1i(location, unit);
+            // Clear <canvas>
+            gl.clear(GL::COLOR_BUFFER_BIT);
 
-            if self.texture0_actived && self.texture1_actived {
-                // Clear <canvas>
-                gl.clear(GL::COLOR_BUFFER_BIT);
+            // Draw the rectangle
+            gl.checked_draw_arrays(GL::TRIANGLE_STRIP, 0, N);
 
-                // Draw the rectangle
-                gl.draw_arrays(GL::TRIANGLE_STRIP, 0, N);
-            }
+            self.textures = vec![texture0, texture1];
         }
         Ok(())
     }
@@ -215,83 +130,52 @@ impl yew::Component for Page {
         Self {
             gl: None,
             canvas: NodeRef::default(),
-            texture0: None,
-            texture1: None,
-            texture0_actived: false,
-            texture1_actived: false,
-            u_sampler0: None,
-            u_sampler1: None,
+            textures: Vec::new(),
+            program: None,
+            error: None,
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Message::Texture0Loaded(image) => {
-                self.load_texture0(image).unwrap();
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                self.request_textures(ctx.link().clone());
+                false
             }
-            Message::Texture1Loaded(image) => {
-                self.load_texture1(image).unwrap();
+            Message::TexturesLoaded(image0, image1) => match self.load_textures(image0, image1) {
+                Ok(()) => false,
+                Err(error) => {
+                    self.error = Some(error_message(error));
+                    true
+                }
+            },
+            Message::LoadFailed(error) => {
+                self.error = Some(error);
+                true
             }
         }
-        false
     }
 
     fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
         yew::html! {
-            <canvas
-                ref={self.canvas.clone()}
-                width="400"
-                height="400"
-            />
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                if let Some(error) = self.error.as_ref() {
+                    <p class="wasmgl-error">{ error }</p>
+                }
+            </div>
         }
     }
 
     fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
-            self.request_texture0(ctx.link().clone());
-            self.request_texture1(ctx.link().clone());
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+            self.request_textures(ctx.link().clone());
         }
     }
 }
-
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices_tex_coords = Float32Array::from(VERTICES_COLORS);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices_tex_coords = unsafe { Float32Array::view(VERTICES_COLORS) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_tex_coords, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, FSIZE * 4, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    let a_tex_coord = gl.get_attrib_location(program, "a_TexCoord");
-    if a_tex_coord < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_TexCoord",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(
-        a_tex_coord as u32,
-        2,
-        GL::FLOAT,
-        false,
-        FSIZE * 4,
-        FSIZE * 2,
-    );
-    gl.enable_vertex_attrib_array(a_tex_coord as u32);
-
-    Ok(())
-}
@@ -1,12 +1,11 @@
-use js_sys::Float32Array;
-use wasm_bindgen::{closure::Closure, JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{
-    HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as GL, WebGlProgram, WebGlTexture,
-    WebGlUniformLocation,
-};
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{
+    error_message, load_image, webgl2_context, RenderBackend, ShaderProgram, Texture, VertexLayout,
+    WebGl2Backend,
+};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -31,24 +30,68 @@ void main() {
 const N: i32 = 4;
 
 // Vertex coordinates, texture coordinate
-const VERTICES_COLORS: &[f32] = &[
+const VERTICES_TEX_COORDS: &[f32] = &[
     -0.5, 0.5, 0.0, 1.0, //
     -0.5, -0.5, 0.0, 0.0, //
     0.5, 0.5, 1.0, 1.0, //
     0.5, -0.5, 1.0, 0.0, //
 ];
 
-const FSIZE: i32 = std::mem::size_of::<f32>() as i32;
+// The texture unit of the image
+const TEXTURE_UNIT: u32 = 0;
+
+fn setup<B: RenderBackend>(backend: &mut B) -> Result<B::Program, JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    let layout = VertexLayout::new()
+        .attribute("a_Position", 2, GL::FLOAT, false)
+        .attribute("a_TexCoord", 2, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES_TEX_COORDS)], None)?;
+
+    // Specify the color for clearing <canvas>
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+
+    // Clear <canvas>
+    backend.clear(GL::COLOR_BUFFER_BIT);
+
+    Ok(program)
+}
+
+fn draw<B: RenderBackend>(
+    backend: &mut B,
+    program: &B::Program,
+    image: &B::Image,
+) -> Result<B::Texture, JsError> {
+    let options = Texture::builder()
+        .unit(TEXTURE_UNIT)
+        .format(GL::RGB)
+        .filter(GL::LINEAR, GL::LINEAR)
+        .flip_y(true);
+    let texture = backend.create_texture(&options, image)?;
+
+    // Set the texture unit 0 to the sampler
+    backend.set_uniform(program, "u_Sampler", (TEXTURE_UNIT as i32).into())?;
+
+    // Clear <canvas>
+    backend.clear(GL::COLOR_BUFFER_BIT);
+
+    // Draw the rectangle
+    backend.draw_arrays(GL::TRIANGLE_STRIP, 0, N);
+    Ok(texture)
+}
 
 pub enum Message {
     TextureLoaded(HtmlImageElement),
+    LoadFailed(String),
+    ContextRestored,
 }
 
 pub struct Page {
-    gl: Option<GL>,
+    backend: Option<WebGl2Backend>,
     canvas: NodeRef,
-    texture: Option<WebGlTexture>,
-    u_sampler: Option<WebGlUniformLocation>,
+    texture: Option<Texture>,
+    program: Option<ShaderProgram>,
+    error: Option<String>,
 }
 
 impl Page {
@@ -56,92 +99,32 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
-
-        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
-
-        init_vertex_buffers(&gl, &program)?;
-
-        let texture = gl.create_texture();
-        if texture.is_none() {
-            return Err(JsError::new("Failed to create the texture object").into());
-        }
-
-        let u_sampler = gl.get_uniform_location(&program, "u_Sampler");
-        if u_sampler.is_none() {
-            return Err(JsError::new("Failed to get the storage location of u_Sampler").into());
-        }
-
-        // Specify the color for clearing <canvas>
-        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
-        // Clear <canvas>
-        gl.clear(GL::COLOR_BUFFER_BIT);
+        let mut backend = WebGl2Backend::new(gl);
+        let program = setup(&mut backend)?;
 
-        self.gl = Some(gl);
-        self.texture = texture;
-        self.u_sampler = u_sampler;
+        self.backend = Some(backend);
+        self.program = Some(program);
         Ok(())
     }
 
     fn request_texture(&mut self, link: yew::html::Scope<Self>) {
-        let image = gloo::utils::document()
-            .create_element("img")
-            .unwrap()
-            .dyn_into::<web_sys::HtmlImageElement>()
-            .unwrap();
-        image.set_cross_origin(Some("anonymous"));
-        image.set_src("/resources/sky.jpg");
-        let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
-            let image = event.target().unwrap().dyn_into().unwrap();
-            link.send_message(Message::TextureLoaded(image));
-        }) as Box<dyn FnMut(_)>);
-        image.set_onload(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
-        let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            // log::error!("failed loading texture");
-            gloo::console::error!("failed loading texture");
-        }) as Box<dyn FnMut(_)>);
-        image.set_onerror(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
+        let image = load_image("/resources/sky.jpg");
+        link.send_future(async move {
+            match image.await {
+                Ok(image) => Message::TextureLoaded(image),
+                Err(err) => Message::LoadFailed(err.to_string()),
+            }
+        });
     }
 
-    fn load_texture(&self, image: HtmlImageElement) -> Result<(), JsValue> {
-        if let Some(gl) = self.gl.as_ref() {
-            gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, 1);
-            // Enable texture unit0
-            gl.active_texture(GL::TEXTURE0);
-            // Bind the texture object to the target
-            gl.bind_texture(GL::TEXTURE_2D, self.texture.as_ref());
-
-            // Set the texture parameters
-            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::LINEAR as i32);
-            // Set the texture image
-            gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
-                GL::TEXTURE_2D,
-                0,
-                GL::RGB as i32,
-                GL::RGB,
-                GL::UNSIGNED_BYTE,
-                &image,
-            )?;
-
-            // Set the texture unit 0 to the sampler
-            gl.uniform1i(self.u_sampler.as_ref(), 0);
-
-            // Clear <canvas>
-            gl.clear(GL::COLOR_BUFFER_BIT);
-
-            // Draw the rectangle
-            gl.draw_arrays(GL::TRIANGLE_STRIP, 0, N);
+    fn load_texture(&mut self, image: HtmlImageElement) -> Result<(), JsValue> {
+        if let (Some(backend), Some(program)) = (self.backend.as_mut(), self.program.as_ref()) {
+            self.texture = Some(draw(backend, program, &image)?);
         }
         Ok(())
     }
@@ -153,77 +136,91 @@ impl yew::Component for Page {
 
     fn create(_ctx: &yew::Context<Self>) -> Self {
         Self {
-            gl: None,
+            backend: None,
             canvas: NodeRef::default(),
             texture: None,
-            u_sampler: None,
+            program: None,
+            error: None,
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Message::TextureLoaded(image) => {
-                self.load_texture(image).unwrap();
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                self.request_texture(ctx.link().clone());
                 false
             }
+            Message::TextureLoaded(image) => match self.load_texture(image) {
+                Ok(()) => false,
+                Err(error) => {
+                    self.error = Some(error_message(error));
+                    true
+                }
+            },
+            Message::LoadFailed(error) => {
+                self.error = Some(error);
+                true
+            }
         }
     }
 
     fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
         yew::html! {
-            <canvas
-                ref={self.canvas.clone()}
-                width="400"
-                height="400"
-            />
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                if let Some(error) = self.error.as_ref() {
+                    <p class="wasmgl-error">{ error }</p>
+                }
+            </div>
         }
     }
 
     fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
             self.request_texture(ctx.link().clone());
         }
     }
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices_tex_coords = Float32Array::from(VERTICES_COLORS);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices_tex_coords = unsafe { Float32Array::view(VERTICES_COLORS) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_tex_coords, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, FSIZE * 4, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    let a_tex_coord = gl.get_attrib_location(program, "a_TexCoord");
-    if a_tex_coord < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_TexCoord",
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::backend::UniformValue;
+    use crate::utils::recording_backend::{Call, RecordingBackend};
+
+    #[test]
+    fn draws_once_the_image_is_loaded() {
+        let mut backend = RecordingBackend::default();
+        let program = setup(&mut backend).unwrap();
+        backend.take_calls();
+
+        draw(&mut backend, &program, &"sky.jpg".to_string()).unwrap();
+        let calls = backend.take_calls();
+        assert!(matches!(
+            &calls[0],
+            Call::CreateTexture { image, .. } if image == "sky.jpg"
         ));
+        assert_eq!(
+            calls[1..],
+            [
+                Call::SetUniform {
+                    program,
+                    name: "u_Sampler".to_string(),
+                    value: UniformValue::Int(0),
+                },
+                Call::Clear(GL::COLOR_BUFFER_BIT),
+                Call::DrawArrays {
+                    mode: GL::TRIANGLE_STRIP,
+                    first: 0,
+                    count: 4
+                },
+            ]
+        );
     }
-
-    gl.vertex_attrib_pointer_with_i32(
-        a_tex_coord as u32,
-        2,
-        GL::FLOAT,
-        false,
-        FSIZE * 4,
-        FSIZE * 2,
-    );
-    gl.enable_vertex_attrib_array(a_tex_coord as u32);
-
-    Ok(())
 }
@@ -1,12 +1,11 @@
-use js_sys::Float32Array;
-use wasm_bindgen::{closure::Closure, JsCast, JsError, JsValue, UnwrapThrowExt};
-use web_sys::{
-    HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as GL, WebGlProgram, WebGlTexture,
-    WebGlUniformLocation,
-};
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as GL};
 use yew::NodeRef;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{
+    error_message, load_image, webgl2_context, ShaderProgram, Texture, VertexArray, VertexLayout,
+    WebGl2RenderingContextExt,
+};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -31,24 +30,25 @@ void main() {
 const N: i32 = 4;
 
 // Vertex coordinates, texture coordinate
-const VERTICES_COLORS: &[f32] = &[
+const VERTICES_TEX_COORDS: &[f32] = &[
     -0.5, 0.5, -0.3, 1.7, //
     -0.5, -0.5, -0.3, -0.2, //
     0.5, 0.5, 1.7, 1.7, //
     0.5, -0.5, 1.7, -0.2, //
 ];
 
-const FSIZE: i32 = std::mem::size_of::<f32>() as i32;
-
 pub enum Message {
     TextureLoaded(HtmlImageElement),
+    LoadFailed(String),
+    ContextRestored,
 }
 
 pub struct Page {
     gl: Option<GL>,
     canvas: NodeRef,
-    texture: Option<WebGlTexture>,
-    u_sampler: Option<WebGlUniformLocation>,
+    texture: Option<Texture>,
+    program: Option<ShaderProgram>,
+    error: Option<String>,
 }
 
 impl Page {
@@ -56,29 +56,17 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-        init_vertex_buffers(&gl, &program)?;
-
-        let texture = gl.create_texture();
-        if texture.is_none() {
-            return Err(JsError::new("Failed to create the texture object").into());
-        }
-
-        let u_sampler = gl.get_uniform_location(&program, "u_Sampler");
-        if u_sampler.is_none() {
-            return Err(JsError::new("Failed to get the storage location of u_Sampler").into());
-        }
+        let layout = VertexLayout::new()
+            .attribute("a_Position", 2, GL::FLOAT, false)
+            .attribute("a_TexCoord", 2, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES_TEX_COORDS)?;
 
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
@@ -87,67 +75,40 @@ impl Page {
         gl.clear(GL::COLOR_BUFFER_BIT);
 
         self.gl = Some(gl);
-        self.texture = texture;
-        self.u_sampler = u_sampler;
+        self.program = Some(program);
         Ok(())
     }
 
     fn request_texture(&mut self, link: yew::html::Scope<Self>) {
-        let image = gloo::utils::document()
-            .create_element("img")
-            .unwrap()
-            .dyn_into::<web_sys::HtmlImageElement>()
-            .unwrap();
-        image.set_cross_origin(Some("anonymous"));
-        image.set_src("/resources/sky.jpg");
-        let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
-            let image = event.target().unwrap().dyn_into().unwrap();
-            link.send_message(Message::TextureLoaded(image));
-        }) as Box<dyn FnMut(_)>);
-        image.set_onload(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
-        let closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
-            // log::error!("failed loading texture");
-            gloo::console::error!("failed loading texture");
-        }) as Box<dyn FnMut(_)>);
-        image.set_onerror(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
+        let image = load_image("/resources/sky.jpg");
+        link.send_future(async move {
+            match image.await {
+                Ok(image) => Message::TextureLoaded(image),
+                Err(err) => Message::LoadFailed(err.to_string()),
+            }
+        });
     }
 
-    fn load_texture(&self, image: HtmlImageElement) -> Result<(), JsValue> {
-        if let Some(gl) = self.gl.as_ref() {
-            gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, 1);
-            // Enable texture unit0
-            gl.active_texture(GL::TEXTURE0);
-            // Bind the texture object to the target
-            gl.bind_texture(GL::TEXTURE_2D, self.texture.as_ref());
-
-            // Set the texture parameters
-            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::LINEAR as i32);
-            gl.tex_parameteri(
-                GL::TEXTURE_2D,
-                GL::TEXTURE_WRAP_T,
-                GL::MIRRORED_REPEAT as i32,
-            );
-            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
-            // Set the texture image
-            gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
-                GL::TEXTURE_2D,
-                0,
-                GL::RGB as i32,
-                GL::RGB,
-                GL::UNSIGNED_BYTE,
-                &image,
-            )?;
+    fn load_texture(&mut self, image: HtmlImageElement) -> Result<(), JsValue> {
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            let texture = Texture::builder()
+                .unit(0)
+                .format(GL::RGB)
+                .wrap(GL::CLAMP_TO_EDGE, GL::MIRRORED_REPEAT)
+                .filter(GL::LINEAR, GL::LINEAR)
+                .flip_y(true)
+                .build(gl, &image)?;
 
             // Set the texture unit 0 to the sampler
-            gl.uniform1i(self.u_sampler.as_ref(), 0);
+            program.set_uniform("u_Sampler", &texture.unit())?;
 
             // Clear <canvas>
             gl.clear(GL::COLOR_BUFFER_BIT);
 
             // Draw the rectangle
-            gl.draw_arrays(GL::TRIANGLE_STRIP, 0, N);
+            gl.checked_draw_arrays(GL::TRIANGLE_STRIP, 0, N);
+
+            self.texture = Some(texture);
         }
         Ok(())
     }
@@ -162,74 +123,51 @@ impl yew::Component for Page {
             gl: None,
             canvas: NodeRef::default(),
             texture: None,
-            u_sampler: None,
+            program: None,
+            error: None,
         }
     }
 
-    fn update(&mut self, _ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            Message::TextureLoaded(image) => {
-                self.load_texture(image).unwrap();
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                self.request_texture(ctx.link().clone());
                 false
             }
+            Message::TextureLoaded(image) => match self.load_texture(image) {
+                Ok(()) => false,
+                Err(error) => {
+                    self.error = Some(error_message(error));
+                    true
+                }
+            },
+            Message::LoadFailed(error) => {
+                self.error = Some(error);
+                true
+            }
         }
     }
 
     fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
         yew::html! {
-            <canvas
-                ref={self.canvas.clone()}
-                width="400"
-                height="400"
-            />
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                if let Some(error) = self.error.as_ref() {
+                    <p class="wasmgl-error">{ error }</p>
+                }
+            </div>
         }
     }
 
     fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
             self.request_texture(ctx.link().clone());
         }
     }
 }
-
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices_tex_coords = Float32Array::from(VERTICES_COLORS);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices_tex_coords = unsafe { Float32Array::view(VERTICES_COLORS) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices_tex_coords, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 2, GL::FLOAT, false, FSIZE * 4, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-
-    let a_tex_coord = gl.get_attrib_location(program, "a_TexCoord");
-    if a_tex_coord < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_TexCoord",
-        ));
-    }
-
-    gl.vertex_attrib_pointer_with_i32(
-        a_tex_coord as u32,
-        2,
-        GL::FLOAT,
-        false,
-        FSIZE * 4,
-        FSIZE * 2,
-    );
-    gl.enable_vertex_attrib_array(a_tex_coord as u32);
-
-    Ok(())
-}
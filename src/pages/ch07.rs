@@ -1,7 +1,21 @@
+mod colored_cube;
+mod depth_buffer;
+mod hello_cube;
 mod look_at_rotated_triangles;
 mod look_at_triangles;
 mod look_at_triangles_with_keys;
+mod ortho_view;
+mod perspective_view;
+mod perspective_view_mvp;
+mod zfighting;
 
+pub use self::colored_cube::Page as ColoredCube;
+pub use self::depth_buffer::Page as DepthBuffer;
+pub use self::hello_cube::Page as HelloCube;
 pub use self::look_at_rotated_triangles::Page as LookAtRotatedTriangles;
 pub use self::look_at_triangles::Page as LookAtTriangles;
 pub use self::look_at_triangles_with_keys::Page as LookAtTrianglesWithKeys;
+pub use self::ortho_view::Page as OrthoView;
+pub use self::perspective_view::Page as PerspectiveView;
+pub use self::perspective_view_mvp::Page as PerspectiveView_mvp;
+pub use self::zfighting::Page as Zfighting;
@@ -0,0 +1,134 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    crate::utils::use_webgl2_canvas_render(canvas.clone(), render);
+
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_MvpMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+// Create a cube
+//    v6----- v5
+//   /|      /|
+//  v1------v0|
+//  | |     | |
+//  | |v7---|-|v4
+//  |/      |/
+//  v2------v3
+#[rustfmt::skip]
+const VERTICES: &[f32] = &[
+    1.0, 1.0, 1.0,  -1.0, 1.0, 1.0,  -1.0,-1.0, 1.0,   1.0,-1.0, 1.0, // v0-v1-v2-v3 front
+    1.0, 1.0, 1.0,   1.0,-1.0, 1.0,   1.0,-1.0,-1.0,   1.0, 1.0,-1.0, // v0-v3-v4-v5 right
+    1.0, 1.0, 1.0,   1.0, 1.0,-1.0,  -1.0, 1.0,-1.0,  -1.0, 1.0, 1.0, // v0-v5-v6-v1 up
+   -1.0, 1.0, 1.0,  -1.0, 1.0,-1.0,  -1.0,-1.0,-1.0,  -1.0,-1.0, 1.0, // v1-v6-v7-v2 left
+   -1.0,-1.0,-1.0,   1.0,-1.0,-1.0,   1.0,-1.0, 1.0,  -1.0,-1.0, 1.0, // v7-v4-v3-v2 down
+    1.0,-1.0,-1.0,  -1.0,-1.0,-1.0,  -1.0, 1.0,-1.0,   1.0, 1.0,-1.0, // v4-v7-v6-v5 back
+];
+
+// One color per face
+#[rustfmt::skip]
+const COLORS: &[f32] = &[
+    0.4, 0.4, 1.0,  0.4, 0.4, 1.0,  0.4, 0.4, 1.0,  0.4, 0.4, 1.0, // v0-v1-v2-v3 front(blue)
+    0.4, 1.0, 0.4,  0.4, 1.0, 0.4,  0.4, 1.0, 0.4,  0.4, 1.0, 0.4, // v0-v3-v4-v5 right(green)
+    1.0, 0.4, 0.4,  1.0, 0.4, 0.4,  1.0, 0.4, 0.4,  1.0, 0.4, 0.4, // v0-v5-v6-v1 up(red)
+    1.0, 1.0, 0.4,  1.0, 1.0, 0.4,  1.0, 1.0, 0.4,  1.0, 1.0, 0.4, // v1-v6-v7-v2 left
+    1.0, 1.0, 1.0,  1.0, 1.0, 1.0,  1.0, 1.0, 1.0,  1.0, 1.0, 1.0, // v7-v4-v3-v2 down
+    0.4, 1.0, 1.0,  0.4, 1.0, 1.0,  0.4, 1.0, 1.0,  0.4, 1.0, 1.0, // v4-v7-v6-v5 back
+];
+
+// Indices of the vertices
+#[rustfmt::skip]
+const INDICES: &[u8] = &[
+    0, 1, 2,    0, 2, 3,    // front
+    4, 5, 6,    4, 6, 7,    // right
+    8, 9, 10,   8, 10, 11,  // up
+    12, 13, 14, 12, 14, 15, // left
+    16, 17, 18, 16, 18, 19, // down
+    20, 21, 22, 20, 22, 23, // back
+];
+
+fn render(gl: GL) -> Result<(), JsError> {
+    let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+    draw_scene(&mut WebGl2Backend::new(gl), aspect)
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B, aspect: f32) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    // Write the vertex coordinates and the colors to separate buffer objects
+    let position = VertexLayout::new().attribute("a_Position", 3, GL::FLOAT, false);
+    let color = VertexLayout::new().attribute("a_Color", 3, GL::FLOAT, false);
+    backend.create_vertex_array(
+        &program,
+        &[(&position, VERTICES), (&color, COLORS)],
+        Some(INDICES.into()),
+    )?;
+
+    // Set clear color and enable hidden surface removal
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.enable(GL::DEPTH_TEST);
+
+    // Set the eye point and the viewing volume
+    let mvp_matrix = na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0)
+        * na::Matrix4::look_at_rh(
+            &na::Point3::new(3.0, 3.0, 7.0),
+            &na::Point3::new(0.0, 0.0, 0.0),
+            &na::Vector3::new(0.0, 1.0, 0.0),
+        );
+    // Pass the model view projection matrix to u_MvpMatrix
+    backend.set_uniform(&program, "u_MvpMatrix", mvp_matrix.into())?;
+
+    // Clear color and depth buffer
+    backend.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+    // Draw the cube
+    backend.draw_elements(GL::TRIANGLES, INDICES.len() as i32, GL::UNSIGNED_BYTE);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend, 1.0).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.mat4("u_MvpMatrix") * inputs.attribute("a_Position"),
+                inputs.attribute("a_Color"),
+            )
+        });
+        rasterizer.assert_golden("ch07/colored_cube");
+    }
+}
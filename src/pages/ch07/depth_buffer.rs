@@ -0,0 +1,127 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    crate::utils::use_webgl2_canvas_render(canvas.clone(), render);
+
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_MvpMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+const N: i32 = 9;
+
+// Vertex coordinates and color
+const VERTICES: &[f32] = &[
+    0.0, 1.0, 0.0, 0.4, 0.4, 1.0, // The front blue one
+    -0.5, -1.0, 0.0, 0.4, 0.4, 1.0, //
+    0.5, -1.0, 0.0, 1.0, 0.4, 0.4, //
+    0.0, 1.0, -2.0, 1.0, 1.0, 0.4, // The middle yellow one
+    -0.5, -1.0, -2.0, 1.0, 1.0, 0.4, //
+    0.5, -1.0, -2.0, 1.0, 0.4, 0.4, //
+    0.0, 1.0, -4.0, 0.4, 1.0, 0.4, // The back green one
+    -0.5, -1.0, -4.0, 0.4, 1.0, 0.4, //
+    0.5, -1.0, -4.0, 1.0, 0.4, 0.4, //
+];
+
+fn render(gl: GL) -> Result<(), JsError> {
+    let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+    draw_scene(&mut WebGl2Backend::new(gl), aspect)
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B, aspect: f32) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    let layout = VertexLayout::new()
+        .attribute("a_Position", 3, GL::FLOAT, false)
+        .attribute("a_Color", 3, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
+
+    // Specify the color for clearing <canvas>
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+    // Enable the hidden surface removal
+    backend.enable(GL::DEPTH_TEST);
+
+    // Calculate the view projection matrix
+    let view_matrix = na::Matrix4::look_at_rh(
+        &na::Point3::new(0.0f32, 0.0, 5.0),
+        &na::Point3::new(0.0, 0.0, -100.0),
+        &na::Vector3::new(0.0, 1.0, 0.0),
+    );
+    let proj_matrix = na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0);
+    let view_proj_matrix = proj_matrix * view_matrix;
+
+    // Clear <canvas>
+    backend.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+    // The triangles are listed front to back, the depth test keeps the front ones visible
+    // Draw the triangles on the right side
+    draw(backend, &program, &view_proj_matrix, 0.75)?;
+    // Draw the triangles on the left side
+    draw(backend, &program, &view_proj_matrix, -0.75)?;
+    Ok(())
+}
+
+fn draw<B: RenderBackend>(
+    backend: &mut B,
+    program: &B::Program,
+    view_proj_matrix: &na::Matrix4<f32>,
+    x: f32,
+) -> Result<(), JsError> {
+    // Translate the triangles along the x axis
+    let model_matrix = na::Matrix4::new_translation(&na::Vector3::new(x, 0.0, 0.0));
+    // Pass the model view projection matrix to u_MvpMatrix
+    backend.set_uniform(
+        program,
+        "u_MvpMatrix",
+        (view_proj_matrix * model_matrix).into(),
+    )?;
+
+    backend.draw_arrays(GL::TRIANGLES, 0, N);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend, 1.0).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.mat4("u_MvpMatrix") * inputs.attribute("a_Position"),
+                inputs.attribute("a_Color"),
+            )
+        });
+        rasterizer.assert_golden("ch07/depth_buffer");
+    }
+}
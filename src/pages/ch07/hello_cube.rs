@@ -0,0 +1,160 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    crate::utils::use_webgl2_canvas_render(canvas.clone(), render);
+
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_MvpMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+// Create a cube
+//    v6----- v5
+//   /|      /|
+//  v1------v0|
+//  | |     | |
+//  | |v7---|-|v4
+//  |/      |/
+//  v2------v3
+const VERTICES_COLORS: &[f32] = &[
+    1.0, 1.0, 1.0, 1.0, 1.0, 1.0, // v0 White
+    -1.0, 1.0, 1.0, 1.0, 0.0, 1.0, // v1 Magenta
+    -1.0, -1.0, 1.0, 1.0, 0.0, 0.0, // v2 Red
+    1.0, -1.0, 1.0, 1.0, 1.0, 0.0, // v3 Yellow
+    1.0, -1.0, -1.0, 0.0, 1.0, 0.0, // v4 Green
+    1.0, 1.0, -1.0, 0.0, 1.0, 1.0, // v5 Cyan
+    -1.0, 1.0, -1.0, 0.0, 0.0, 1.0, // v6 Blue
+    -1.0, -1.0, -1.0, 0.0, 0.0, 0.0, // v7 Black
+];
+
+// Indices of the vertices
+#[rustfmt::skip]
+const INDICES: &[u8] = &[
+    0, 1, 2, 0, 2, 3, // front
+    0, 3, 4, 0, 4, 5, // right
+    0, 5, 6, 0, 6, 1, // up
+    1, 6, 7, 1, 7, 2, // left
+    7, 4, 3, 7, 3, 2, // down
+    4, 7, 6, 4, 6, 5, // back
+];
+
+// Set the eye point and the viewing volume
+fn mvp_matrix(aspect: f32) -> na::Matrix4<f32> {
+    na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0)
+        * na::Matrix4::look_at_rh(
+            &na::Point3::new(3.0, 3.0, 7.0),
+            &na::Point3::new(0.0, 0.0, 0.0),
+            &na::Vector3::new(0.0, 1.0, 0.0),
+        )
+}
+
+fn render(gl: GL) -> Result<(), JsError> {
+    let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+    draw_scene(&mut WebGl2Backend::new(gl), aspect)
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B, aspect: f32) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    let layout = VertexLayout::new()
+        .attribute("a_Position", 3, GL::FLOAT, false)
+        .attribute("a_Color", 3, GL::FLOAT, false);
+    backend.create_vertex_array(
+        &program,
+        &[(&layout, VERTICES_COLORS)],
+        Some(INDICES.into()),
+    )?;
+
+    // Set clear color and enable hidden surface removal
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.enable(GL::DEPTH_TEST);
+
+    // Pass the model view projection matrix to u_MvpMatrix
+    backend.set_uniform(&program, "u_MvpMatrix", mvp_matrix(aspect).into())?;
+
+    // Clear color and depth buffer
+    backend.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+    // Draw the cube
+    backend.draw_elements(GL::TRIANGLES, INDICES.len() as i32, GL::UNSIGNED_BYTE);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::{Call, RecordingBackend};
+    use crate::utils::software::{attribute, DrawMode, Rasterizer, Vertex};
+
+    #[test]
+    fn draws_the_cube_with_depth_test() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend, 1.0).unwrap();
+        let calls = backend.take_calls();
+        assert!(matches!(
+            &calls[1],
+            Call::CreateVertexArray { indices: Some(indices), .. }
+                if indices.iter().copied().eq(INDICES.iter().map(|&index| index as u32))
+        ));
+        assert_eq!(
+            calls[2..],
+            [
+                Call::ClearColor([0.0, 0.0, 0.0, 1.0]),
+                Call::Enable(GL::DEPTH_TEST),
+                Call::SetUniform {
+                    program: 0,
+                    name: "u_MvpMatrix".to_string(),
+                    value: mvp_matrix(1.0).into(),
+                },
+                Call::Clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT),
+                Call::DrawElements {
+                    mode: GL::TRIANGLES,
+                    count: 36,
+                    ty: GL::UNSIGNED_BYTE,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn scene() {
+        let mvp_matrix = mvp_matrix(1.0);
+        let positions = attribute(VERTICES_COLORS, 6, 0, 3);
+        let colors = attribute(VERTICES_COLORS, 6, 3, 3);
+        let vertices = positions
+            .into_iter()
+            .zip(colors)
+            .map(|(position, color)| Vertex::new(mvp_matrix * position, color))
+            .collect::<Vec<_>>();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.enable_depth_test();
+        rasterizer.clear([0.0, 0.0, 0.0, 1.0]);
+        rasterizer.draw_elements(DrawMode::Triangles, &vertices, INDICES);
+        rasterizer.assert_golden("ch07/hello_cube");
+    }
+}
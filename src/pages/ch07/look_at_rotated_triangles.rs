@@ -1,9 +1,8 @@
-use js_sys::Float32Array;
 use nalgebra as na;
 use wasm_bindgen::JsError;
-use web_sys::{WebGl2RenderingContext as GL, WebGlProgram};
+use web_sys::WebGl2RenderingContext as GL;
 
-use crate::utils::WebGl2RenderingContextExt;
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
 
 #[yew::function_component(Page)]
 pub fn page() -> yew::Html {
@@ -50,59 +49,17 @@ const VERTICES: &[f32] = &[
     0.5, -0.5, 0.0, 1.0, 0.4, 0.4, //
 ];
 
-const FSIZE: i32 = std::mem::size_of::<f32>() as i32;
-
 fn render(gl: GL) -> Result<(), JsError> {
-    let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
-
-    init_vertex_buffers(&gl, &program)?;
-
-    // Unbind the buffer object
-    gl.bind_buffer(GL::ARRAY_BUFFER, None);
-
-    // Specify the color for clearing <canvas>
-    gl.clear_color(0.0, 0.0, 0.0, 1.0);
-
-    // Clear <canvas>
-    gl.clear(GL::COLOR_BUFFER_BIT);
-
-    // Draw
-    gl.draw_arrays(GL::TRIANGLES, 0, N);
-    Ok(())
+    draw_scene(&mut WebGl2Backend::new(gl))
 }
 
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
-    }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-    let a_color = gl.get_attrib_location(program, "a_Color");
-    if a_color < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Color",
-        ));
-    }
-
-    let u_view_matrix = gl.get_uniform_location(program, "u_ViewMatrix");
-    let u_model_matrix = gl.get_uniform_location(program, "u_ModelMatrix");
-    if u_view_matrix.is_none() {
-        return Err(JsError::new(
-            "Failed to get the storage location of u_viewMatrix or u_ModelMatrix",
-        ));
-    }
+    let layout = VertexLayout::new()
+        .attribute("a_Position", 3, GL::FLOAT, false)
+        .attribute("a_Color", 3, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
 
     let view_matrix = na::Matrix4::look_at_rh(
         &na::Point3::new(0.20f32, 0.25, 0.25),
@@ -115,15 +72,40 @@ fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
         -10.0 * std::f32::consts::PI / 180.0,
     ));
 
-    // Assign the buffer object to a_Position and enable the assignment
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 3, GL::FLOAT, false, FSIZE * 6, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-    // Assign the buffer object to a_Color and enable the assignment
-    gl.vertex_attrib_pointer_with_i32(a_color as u32, 3, GL::FLOAT, false, FSIZE * 6, FSIZE * 3);
-    gl.enable_vertex_attrib_array(a_color as u32);
     // Pass the view projection matrix and model matrix
-    gl.uniform_matrix4fv_with_f32_array(u_view_matrix.as_ref(), false, view_matrix.as_slice());
-    gl.uniform_matrix4fv_with_f32_array(u_model_matrix.as_ref(), false, model_matrix.as_slice());
+    backend.set_uniform(&program, "u_ViewMatrix", view_matrix.into())?;
+    backend.set_uniform(&program, "u_ModelMatrix", model_matrix.into())?;
 
+    // Specify the color for clearing <canvas>
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+
+    // Clear <canvas>
+    backend.clear(GL::COLOR_BUFFER_BIT);
+
+    // Draw
+    backend.draw_arrays(GL::TRIANGLES, 0, N);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.mat4("u_ViewMatrix")
+                    * inputs.mat4("u_ModelMatrix")
+                    * inputs.attribute("a_Position"),
+                inputs.attribute("a_Color"),
+            )
+        });
+        rasterizer.assert_golden("ch07/look_at_rotated_triangles");
+    }
+}
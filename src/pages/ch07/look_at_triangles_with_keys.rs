@@ -1,12 +1,16 @@
-use js_sys::Float32Array;
+use gloo::events::EventListener;
 use nalgebra as na;
-use wasm_bindgen::{prelude::Closure, JsCast, JsError, JsValue, UnwrapThrowExt};
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
 use web_sys::{
-    HtmlCanvasElement, KeyEvent, WebGl2RenderingContext as GL, WebGlProgram, WebGlUniformLocation,
+    HtmlCanvasElement, KeyboardEvent, MouseEvent, PointerEvent, WebGl2RenderingContext as GL,
+    WheelEvent,
 };
 use yew::NodeRef;
 
-use crate::utils::{Draging, WebGl2RenderingContextExt};
+use crate::utils::{
+    listen_document, webgl2_context, Camera, CameraControl, ControlMode, Projection, ShaderProgram,
+    VertexArray, VertexLayout, WebGl2RenderingContextExt,
+};
 
 const VSHADER_SOURCE: &str = "
 attribute vec4 a_Position;
@@ -43,30 +47,24 @@ const VERTICES: &[f32] = &[
     0.5, -0.5, 0.0, 1.0, 0.4, 0.4, //
 ];
 
-const FSIZE: i32 = std::mem::size_of::<f32>() as i32;
-
 pub enum Message {
-    KeyLeft,
-    KeyRight,
-    KeyUp,
-    KeyDown,
-    MouseDown(i32, i32),
-    MouseMove(i32, i32),
-    MouseUp(i32, i32),
+    KeyDown(KeyboardEvent),
+    PointerDown(PointerEvent),
+    PointerMove(PointerEvent),
+    PointerUp(PointerEvent),
+    PointerCancel(PointerEvent),
+    Wheel(WheelEvent),
+    ContextRestored,
 }
 
 pub struct Page {
     gl: Option<GL>,
     canvas: NodeRef,
-    u_view_matrix: Option<WebGlUniformLocation>,
-    u_proj_matrix: Option<WebGlUniformLocation>,
-    near: f32,
-    far: f32,
-    eye_x: f32,
-    eye_y: f32,
-    draging: Draging,
-
-    original_onkeydown: Option<Option<js_sys::Function>>,
+    program: Option<ShaderProgram>,
+    // Moved by the arrow keys, turned around the triangles by the pointer
+    control: CameraControl,
+    // The keys pressed anywhere in the page, until the page is left
+    keydown_listener: Option<EventListener>,
 }
 
 impl Page {
@@ -74,28 +72,17 @@ impl Page {
         self.canvas.cast::<HtmlCanvasElement>()
     }
 
-    fn setup_gl(&mut self) -> Result<(), JsValue> {
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
         let canvas = self.get_canvas().unwrap();
 
-        let gl = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<GL>()
-            .unwrap();
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
 
         let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
 
-        init_vertex_buffers(&gl, &program)?;
-
-        let u_view_matrix = gl.get_uniform_location(&program, "u_ViewMatrix");
-        let u_proj_matrix = gl.get_uniform_location(&program, "u_ProjMatrix");
-        if u_view_matrix.is_none() || u_proj_matrix.is_none() {
-            return Err(JsError::new(
-                "Failed to get the storage location of u_ViewMatrix or u_ProjMatrix",
-            )
-            .into());
-        }
+        let layout = VertexLayout::new()
+            .attribute("a_Position", 3, GL::FLOAT, false)
+            .attribute("a_Color", 3, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES)?;
 
         // Unbind the buffer object
         gl.bind_buffer(GL::ARRAY_BUFFER, None);
@@ -103,40 +90,25 @@ impl Page {
         // Specify the color for clearing <canvas>
         gl.clear_color(0.0, 0.0, 0.0, 1.0);
 
-        self.u_view_matrix = u_view_matrix;
-        self.u_proj_matrix = u_proj_matrix;
-        self.rerender_triangle(&gl);
+        self.rerender_triangle(&gl, &program)?;
 
         self.gl = Some(gl);
+        self.program = Some(program);
         Ok(())
     }
 
-    fn rerender_triangle(&self, gl: &GL) {
-        let proj_matrix = na::Matrix4::new_orthographic(-1.0, 1.0, -1.0, 1.0, self.near, self.far);
-        gl.uniform_matrix4fv_with_f32_array(
-            self.u_proj_matrix.as_ref(),
-            false,
-            proj_matrix.as_slice(),
-        );
-
+    fn rerender_triangle(&self, gl: &GL, program: &ShaderProgram) -> Result<(), JsError> {
+        let camera = self.control.camera();
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        program.set_uniform("u_ProjMatrix", &camera.projection_matrix(aspect))?;
         // Set the matrix to be used for to set the camera view
-        let view_matrix = na::Matrix4::look_at_rh(
-            &na::Point3::new(self.eye_x, self.eye_y, 0.25),
-            &na::Point3::new(0.0, 0.0, 0.0),
-            &na::Vector3::new(0.0, 1.0, 0.0),
-        );
-
-        // Pass the view projection matrix
-        gl.uniform_matrix4fv_with_f32_array(
-            self.u_view_matrix.as_ref(),
-            false,
-            view_matrix.as_slice(),
-        );
+        program.set_uniform("u_ViewMatrix", &camera.view_matrix())?;
 
         // Clear <canvas>
         gl.clear(GL::COLOR_BUFFER_BIT);
         // Draw the rectangle
-        gl.draw_arrays(GL::TRIANGLES, 0, N);
+        gl.checked_draw_arrays(GL::TRIANGLES, 0, N);
+        Ok(())
     }
 }
 
@@ -145,106 +117,83 @@ impl yew::Component for Page {
     type Properties = ();
 
     fn create(_ctx: &yew::Context<Self>) -> Self {
+        let camera = Camera::new(
+            na::Point3::new(0.0, 0.0, 0.25),
+            na::Point3::new(0.0, 0.0, 0.0),
+            Projection::Orthographic {
+                height: 2.0,
+                near: 0.0,
+                far: 2.0,
+            },
+        );
         Self {
             gl: None,
             canvas: NodeRef::default(),
-            u_view_matrix: None,
-            u_proj_matrix: None,
-            near: 0.0,
-            far: 2.0,
-            eye_x: 0.0,
-            eye_y: 0.0,
-            draging: Draging::default(),
-            original_onkeydown: None,
+            program: None,
+            control: CameraControl::new(camera, ControlMode::Orbit),
+            keydown_listener: None,
         }
     }
 
     fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            Message::KeyLeft => {
-                if let Some(gl) = self.gl.as_ref() {
-                    if !self.draging.is_tracking() {
-                        self.eye_x -= 0.01;
-                        self.rerender_triangle(gl);
-                    }
-                }
-            }
-            Message::KeyRight => {
-                if let Some(gl) = self.gl.as_ref() {
-                    if !self.draging.is_tracking() {
-                        self.eye_x += 0.01;
-                        self.rerender_triangle(gl);
-                    }
-                }
+        let changed = match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
             }
-            Message::KeyUp => {
-                if let Some(gl) = self.gl.as_ref() {
-                    if !self.draging.is_tracking() {
-                        self.eye_y += 0.01;
-                        self.rerender_triangle(gl);
-                    }
-                }
+            Message::KeyDown(event) => {
+                // Move the eye point as in the book, rather than turning
+                // around the triangles
+                let offset = match event.key().as_str() {
+                    "ArrowRight" => na::Vector3::new(0.01, 0.0, 0.0),
+                    "ArrowLeft" => na::Vector3::new(-0.01, 0.0, 0.0),
+                    "ArrowUp" => na::Vector3::new(0.0, 0.01, 0.0),
+                    "ArrowDown" => na::Vector3::new(0.0, -0.01, 0.0),
+                    _ => return false,
+                };
+                event.prevent_default();
+                self.control.move_eye(offset);
+                true
             }
-            Message::KeyDown => {
-                if let Some(gl) = self.gl.as_ref() {
-                    if !self.draging.is_tracking() {
-                        self.eye_y -= 0.01;
-                        self.rerender_triangle(gl);
-                    }
-                }
+            // The pointer is captured by the canvas until it goes up
+            Message::PointerDown(event) => {
+                self.control.onpointerdown(&event);
+                false
             }
-            Message::MouseDown(x, y) => {
-                if self.draging.onmousedown(x, y) {
-                    let document = gloo::utils::document();
-
-                    let link = ctx.link().clone();
-                    let closure = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
-                        link.send_message(Message::MouseMove(event.client_x(), event.client_y()));
-                    }) as Box<dyn FnMut(_)>);
-                    document.set_onmousemove(Some(closure.as_ref().unchecked_ref()));
-                    closure.forget();
-
-                    let link = ctx.link().clone();
-                    let closure = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
-                        link.send_message(Message::MouseUp(event.client_x(), event.client_y()));
-                    }) as Box<dyn FnMut(_)>);
-                    document.set_onmouseup(Some(closure.as_ref().unchecked_ref()));
-                    closure.forget();
-                }
+            Message::PointerMove(event) => self.control.onpointermove(&event),
+            Message::PointerUp(event) => self.control.onpointerup(&event),
+            Message::PointerCancel(event) => {
+                self.control.onpointercancel(&event);
+                false
             }
-            Message::MouseMove(x, y) => {
-                if let Some((diff_x, diff_y)) = self.draging.onmousemove(x, y) {
-                    if let Some(gl) = self.gl.as_ref() {
-                        let old_eye_x = self.eye_x;
-                        let old_eye_y = self.eye_y;
-                        self.eye_x += diff_x as f32 / gl.drawing_buffer_width() as f32;
-                        self.eye_y -= diff_y as f32 / gl.drawing_buffer_height() as f32;
-                        self.rerender_triangle(gl);
-                        self.eye_x = old_eye_x;
-                        self.eye_y = old_eye_y;
-                    }
-                }
-            }
-            Message::MouseUp(x, y) => {
-                if let Some((diff_x, diff_y)) = self.draging.onmouseup(x, y) {
-                    if let Some(gl) = self.gl.as_ref() {
-                        self.eye_x += diff_x as f32 / gl.drawing_buffer_width() as f32;
-                        self.eye_y -= diff_y as f32 / gl.drawing_buffer_height() as f32;
-                    }
-                }
+            Message::Wheel(event) => self.control.onwheel(&event),
+        };
+        if changed {
+            if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+                self.rerender_triangle(gl, program).unwrap_throw();
             }
         }
         false
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
-        let onmousedown = ctx.link().callback(|event: web_sys::MouseEvent| {
-            Message::MouseDown(event.client_x(), event.client_y())
-        });
+        let onpointerdown = ctx.link().callback(Message::PointerDown);
+        let onpointermove = ctx.link().callback(Message::PointerMove);
+        let onpointerup = ctx.link().callback(Message::PointerUp);
+        let onpointercancel = ctx.link().callback(Message::PointerCancel);
+        let onwheel = ctx.link().callback(Message::Wheel);
+        // The right button pans the view
+        let oncontextmenu = |event: MouseEvent| event.prevent_default();
         yew::html! {
             <canvas
                 ref={self.canvas.clone()}
-                {onmousedown}
+                style="touch-action: none"
+                {onpointerdown}
+                {onpointermove}
+                {onpointerup}
+                {onpointercancel}
+                {onwheel}
+                {oncontextmenu}
                 width="400"
                 height="400"
             />
@@ -253,73 +202,19 @@ impl yew::Component for Page {
 
     fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
         if first_render {
-            self.setup_gl().unwrap_throw();
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
 
             let link = ctx.link().clone();
-            let closure = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
-                // https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/keyCode
-                match event.key_code() {
-                    KeyEvent::DOM_VK_LEFT => {
-                        link.send_message(Message::KeyLeft);
-                    }
-                    KeyEvent::DOM_VK_UP => {
-                        link.send_message(Message::KeyUp);
-                    }
-                    KeyEvent::DOM_VK_RIGHT => {
-                        link.send_message(Message::KeyRight);
-                    }
-                    KeyEvent::DOM_VK_DOWN => {
-                        link.send_message(Message::KeyDown);
-                    }
-                    _ => {}
-                }
-            }) as Box<dyn FnMut(_)>);
-            let document = gloo::utils::document();
-            self.original_onkeydown = Some(document.onkeydown());
-            document.set_onkeydown(Some(closure.as_ref().unchecked_ref()));
-            closure.forget();
+            self.keydown_listener =
+                Some(listen_document("keydown", move |event: &KeyboardEvent| {
+                    link.send_message(Message::KeyDown(event.clone()));
+                }));
         }
     }
 
     fn destroy(&mut self, _ctx: &yew::Context<Self>) {
-        if let Some(original_onkeydown) = self.original_onkeydown.take() {
-            gloo::utils::document().set_onkeydown(original_onkeydown.as_ref());
-        }
-        self.draging.stop_tracking();
-    }
-}
-
-fn init_vertex_buffers(gl: &GL, program: &WebGlProgram) -> Result<(), JsError> {
-    // let vertices = Float32Array::from(VERTICES);
-    // use view() instead of from() to avoid additional memory allocation
-    let vertices = unsafe { Float32Array::view(VERTICES) };
-    let vertex_buffer = gl.create_buffer();
-    if vertex_buffer.is_none() {
-        return Err(JsError::new("Failed to create the buffer object"));
+        // removeEventListener
+        self.keydown_listener = None;
+        self.control.stop_tracking();
     }
-    gl.bind_buffer(GL::ARRAY_BUFFER, vertex_buffer.as_ref());
-    gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vertices, GL::STATIC_DRAW);
-
-    let a_position = gl.get_attrib_location(program, "a_Position");
-    if a_position < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Position",
-        ));
-    }
-
-    let a_color = gl.get_attrib_location(program, "a_Color");
-    if a_color < 0 {
-        return Err(JsError::new(
-            "Failed to get the storage location of a_Color",
-        ));
-    }
-
-    // Assign the buffer object to a_Position and enable the assignment
-    gl.vertex_attrib_pointer_with_i32(a_position as u32, 3, GL::FLOAT, false, FSIZE * 6, 0);
-    gl.enable_vertex_attrib_array(a_position as u32);
-    // Assign the buffer object to a_Color and enable the assignment
-    gl.vertex_attrib_pointer_with_i32(a_color as u32, 3, GL::FLOAT, false, FSIZE * 6, FSIZE * 3);
-    gl.enable_vertex_attrib_array(a_color as u32);
-
-    Ok(())
 }
@@ -0,0 +1,181 @@
+use gloo::events::EventListener;
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, KeyEvent, KeyboardEvent, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    listen_document, webgl2_context, ShaderProgram, VertexArray, VertexLayout,
+    WebGl2RenderingContextExt,
+};
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_ProjMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_ProjMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+const N: i32 = 9;
+
+// Vertex coordinates and color
+const VERTICES: &[f32] = &[
+    0.0, 0.6, -0.4, 0.4, 1.0, 0.4, // The back green one
+    -0.5, -0.4, -0.4, 0.4, 1.0, 0.4, //
+    0.5, -0.4, -0.4, 1.0, 0.4, 0.4, //
+    0.5, 0.4, -0.2, 1.0, 0.4, 0.4, // The middle yellow one
+    -0.5, 0.4, -0.2, 1.0, 1.0, 0.4, //
+    0.0, -0.6, -0.2, 1.0, 1.0, 0.4, //
+    0.0, 0.5, 0.0, 0.4, 0.4, 1.0, // The front blue one
+    -0.5, -0.5, 0.0, 0.4, 0.4, 1.0, //
+    0.5, -0.5, 0.0, 1.0, 0.4, 0.4, //
+];
+
+pub enum Message {
+    KeyDown(KeyboardEvent),
+    ContextRestored,
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    // The distances to the near and far clipping plane
+    near: f32,
+    far: f32,
+    // The keys pressed anywhere in the page, until the page is left
+    keydown_listener: Option<EventListener>,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+        let layout = VertexLayout::new()
+            .attribute("a_Position", 3, GL::FLOAT, false)
+            .attribute("a_Color", 3, GL::FLOAT, false);
+        VertexArray::new(&gl)?.buffer(&program, &layout, VERTICES)?;
+
+        // Specify the color for clearing <canvas>
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+
+        self.rerender_triangle(&gl, &program)?;
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        Ok(())
+    }
+
+    fn rerender_triangle(&self, gl: &GL, program: &ShaderProgram) -> Result<(), JsError> {
+        // Set the viewing volume
+        let proj_matrix = na::Matrix4::new_orthographic(-1.0, 1.0, -1.0, 1.0, self.near, self.far);
+        program.set_uniform("u_ProjMatrix", &proj_matrix)?;
+
+        // Clear <canvas>
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        // Draw the triangles
+        gl.checked_draw_arrays(GL::TRIANGLES, 0, N);
+        Ok(())
+    }
+
+    fn update_planes(&mut self, near: f32, far: f32) {
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            // The viewing volume would be empty
+            if (far - near).abs() < f32::EPSILON {
+                return;
+            }
+            self.near = near;
+            self.far = far;
+            self.rerender_triangle(gl, program).unwrap_throw();
+        }
+    }
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            near: 0.0,
+            far: 0.5,
+            keydown_listener: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+            }
+            Message::KeyDown(event) => {
+                // https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/keyCode
+                let (near, far) = match event.key_code() {
+                    KeyEvent::DOM_VK_LEFT => (-0.01, 0.0),
+                    KeyEvent::DOM_VK_UP => (0.0, 0.01),
+                    KeyEvent::DOM_VK_RIGHT => (0.01, 0.0),
+                    KeyEvent::DOM_VK_DOWN => (0.0, -0.01),
+                    _ => return false,
+                };
+                // The arrow keys do not scroll the page
+                event.prevent_default();
+                self.update_planes(self.near + near, self.far + far);
+            }
+        }
+        true
+    }
+
+    fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                <p>{ format!("near: {:.2}, far: {:.2}", self.near, self.far) }</p>
+                <p>{ "Use the left/right arrow keys to change near and the up/down arrow keys to change far." }</p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+
+            let link = ctx.link().clone();
+            self.keydown_listener =
+                Some(listen_document("keydown", move |event: &KeyboardEvent| {
+                    link.send_message(Message::KeyDown(event.clone()));
+                }));
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // removeEventListener
+        self.keydown_listener = None;
+    }
+}
@@ -0,0 +1,119 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    crate::utils::use_webgl2_canvas_render(canvas.clone(), render);
+
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_ViewMatrix;
+uniform mat4 u_ProjMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_ProjMatrix * u_ViewMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+const N: i32 = 18;
+
+// Vertex coordinates and color
+const VERTICES: &[f32] = &[
+    // Three triangles on the right side
+    0.75, 1.0, -4.0, 0.4, 1.0, 0.4, // The back green one
+    0.25, -1.0, -4.0, 0.4, 1.0, 0.4, //
+    1.25, -1.0, -4.0, 1.0, 0.4, 0.4, //
+    0.75, 1.0, -2.0, 1.0, 1.0, 0.4, // The middle yellow one
+    0.25, -1.0, -2.0, 1.0, 1.0, 0.4, //
+    1.25, -1.0, -2.0, 1.0, 0.4, 0.4, //
+    0.75, 1.0, 0.0, 0.4, 0.4, 1.0, // The front blue one
+    0.25, -1.0, 0.0, 0.4, 0.4, 1.0, //
+    1.25, -1.0, 0.0, 1.0, 0.4, 0.4, //
+    // Three triangles on the left side
+    -0.75, 1.0, -4.0, 0.4, 1.0, 0.4, // The back green one
+    -1.25, -1.0, -4.0, 0.4, 1.0, 0.4, //
+    -0.25, -1.0, -4.0, 1.0, 0.4, 0.4, //
+    -0.75, 1.0, -2.0, 1.0, 1.0, 0.4, // The middle yellow one
+    -1.25, -1.0, -2.0, 1.0, 1.0, 0.4, //
+    -0.25, -1.0, -2.0, 1.0, 0.4, 0.4, //
+    -0.75, 1.0, 0.0, 0.4, 0.4, 1.0, // The front blue one
+    -1.25, -1.0, 0.0, 0.4, 0.4, 1.0, //
+    -0.25, -1.0, 0.0, 1.0, 0.4, 0.4, //
+];
+
+fn render(gl: GL) -> Result<(), JsError> {
+    let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+    draw_scene(&mut WebGl2Backend::new(gl), aspect)
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B, aspect: f32) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    let layout = VertexLayout::new()
+        .attribute("a_Position", 3, GL::FLOAT, false)
+        .attribute("a_Color", 3, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
+
+    // Calculate the view matrix and the projection matrix
+    let view_matrix = na::Matrix4::look_at_rh(
+        &na::Point3::new(0.0f32, 0.0, 5.0),
+        &na::Point3::new(0.0, 0.0, -100.0),
+        &na::Vector3::new(0.0, 1.0, 0.0),
+    );
+    let proj_matrix = na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0);
+    // Pass the view and projection matrix to u_ViewMatrix, u_ProjMatrix
+    backend.set_uniform(&program, "u_ViewMatrix", view_matrix.into())?;
+    backend.set_uniform(&program, "u_ProjMatrix", proj_matrix.into())?;
+
+    // Specify the color for clearing <canvas>
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+
+    // Clear <canvas>
+    backend.clear(GL::COLOR_BUFFER_BIT);
+
+    // Draw the triangles
+    backend.draw_arrays(GL::TRIANGLES, 0, N);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend, 1.0).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            Vertex::new(
+                inputs.mat4("u_ProjMatrix")
+                    * inputs.mat4("u_ViewMatrix")
+                    * inputs.attribute("a_Position"),
+                inputs.attribute("a_Color"),
+            )
+        });
+        rasterizer.assert_golden("ch07/perspective_view");
+    }
+}
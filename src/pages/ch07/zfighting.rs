@@ -0,0 +1,86 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{RenderBackend, VertexLayout, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    crate::utils::use_webgl2_canvas_render(canvas.clone(), render);
+
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_ViewProjMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_ViewProjMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+// Vertex coordinates and color, both triangles lie in the same plane
+const VERTICES: &[f32] = &[
+    0.0, 2.5, -5.0, 0.4, 1.0, 0.4, // The green triangle
+    -2.5, -2.5, -5.0, 0.4, 1.0, 0.4, //
+    2.5, -2.5, -5.0, 1.0, 0.4, 0.4, //
+    0.0, 3.0, -5.0, 1.0, 0.4, 0.4, // The yellow triangle
+    -3.0, -3.0, -5.0, 1.0, 1.0, 0.4, //
+    3.0, -3.0, -5.0, 1.0, 1.0, 0.4, //
+];
+
+fn render(gl: GL) -> Result<(), JsError> {
+    let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+    draw_scene(&mut WebGl2Backend::new(gl), aspect)
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B, aspect: f32) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    let layout = VertexLayout::new()
+        .attribute("a_Position", 3, GL::FLOAT, false)
+        .attribute("a_Color", 3, GL::FLOAT, false);
+    backend.create_vertex_array(&program, &[(&layout, VERTICES)], None)?;
+
+    // Specify the color for clearing <canvas>
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+    // Enable the hidden surface removal
+    backend.enable(GL::DEPTH_TEST);
+
+    // Set the eye point, look-at point, and up vector.
+    let view_proj_matrix = na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0)
+        * na::Matrix4::look_at_rh(
+            &na::Point3::new(3.06, 2.5, 10.0),
+            &na::Point3::new(0.0, 0.0, -2.0),
+            &na::Vector3::new(0.0, 1.0, 0.0),
+        );
+    // Pass the view projection matrix to u_ViewProjMatrix
+    backend.set_uniform(&program, "u_ViewProjMatrix", view_proj_matrix.into())?;
+
+    // Clear color and depth buffer
+    backend.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+    // Enable the polygon offset function
+    backend.enable(GL::POLYGON_OFFSET_FILL);
+    // Draw the green triangle
+    backend.draw_arrays(GL::TRIANGLES, 0, 3);
+    // Set the polygon offset
+    backend.polygon_offset(1.0, 1.0);
+    // Draw the yellow triangle
+    backend.draw_arrays(GL::TRIANGLES, 3, 3);
+    Ok(())
+}
@@ -0,0 +1,19 @@
+mod cube;
+mod lighted_cube;
+mod lighted_cube_ambient;
+mod lighted_translated_rotated_cube;
+mod point_lighted_cube;
+mod point_lighted_cube_per_fragment;
+mod point_lighted_sphere;
+mod point_lighted_sphere_per_fragment;
+mod primitives;
+mod sphere;
+
+pub use self::lighted_cube::Page as LightedCube;
+pub use self::lighted_cube_ambient::Page as LightedCube_ambient;
+pub use self::lighted_translated_rotated_cube::Page as LightedTranslatedRotatedCube;
+pub use self::point_lighted_cube::Page as PointLightedCube;
+pub use self::point_lighted_cube_per_fragment::Page as PointLightedCube_perFragment;
+pub use self::point_lighted_sphere::Page as PointLightedSphere;
+pub use self::point_lighted_sphere_per_fragment::Page as PointLightedSphere_perFragment;
+pub use self::primitives::Page as Primitives;
@@ -0,0 +1,27 @@
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{Mesh, RenderBackend, VertexLayout};
+
+/// Write the positions, normals and colors of a red cube to the attributes
+/// `a_Position`, `a_Normal` and `a_Color` and return the number of indices,
+/// which are 32-bit
+pub fn init_vertex_buffers<B: RenderBackend>(
+    backend: &mut B,
+    program: &B::Program,
+) -> Result<i32, JsError> {
+    let cube = Mesh::cube(2.0);
+    // The cube is red
+    let colors = [1.0f32, 0.0, 0.0].repeat(cube.vertex_count());
+    let color = VertexLayout::new().attribute("a_Color", 3, GL::FLOAT, false);
+
+    let mesh_buffers = cube.buffers();
+    let mut buffers = mesh_buffers
+        .iter()
+        .map(|(layout, data)| (layout, *data))
+        .collect::<Vec<_>>();
+    buffers.push((&color, &colors));
+    backend.create_vertex_array(program, &buffers, Some(cube.indices[..].into()))?;
+
+    Ok(cube.indices.len() as i32)
+}
@@ -0,0 +1,135 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{RenderBackend, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    crate::utils::use_webgl2_canvas_render(canvas.clone(), render);
+
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+attribute vec4 a_Normal;        // Normal
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_NormalMatrix;   // Transformation matrix of the normal
+uniform vec3 u_LightColor;     // Light color
+uniform vec3 u_LightDirection; // Light direction (in the world coordinate, normalized)
+uniform vec3 u_AmbientLight;   // Color of an ambient light
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    // Recalculate the normal based on the model matrix and make its length 1.
+    vec3 normal = normalize(vec3(u_NormalMatrix * a_Normal));
+    // Dot product of the light direction and the orientation of a surface (the normal)
+    float nDotL = max(dot(u_LightDirection, normal), 0.0);
+    // Calculate the color due to diffuse reflection
+    vec3 diffuse = u_LightColor * a_Color.rgb * nDotL;
+    // Calculate the color due to ambient reflection
+    vec3 ambient = u_AmbientLight * a_Color.rgb;
+    // Add the surface colors due to diffuse reflection and ambient reflection
+    v_Color = vec4(diffuse + ambient, a_Color.a);
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+fn render(gl: GL) -> Result<(), JsError> {
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    // Set the vertex coordinates, the color and the normal
+    let n = super::cube::init_vertex_buffers(backend, &program)?;
+
+    // Set the clear color and enable the depth test
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.enable(GL::DEPTH_TEST);
+
+    // Set the light color (white)
+    backend.set_uniform(&program, "u_LightColor", [1.0f32, 1.0, 1.0].into())?;
+    // Set the light direction (in the world coordinate)
+    let light_direction = na::Vector3::new(0.5f32, 3.0, 4.0).normalize();
+    backend.set_uniform(&program, "u_LightDirection", light_direction.into())?;
+    // Set the ambient light
+    backend.set_uniform(&program, "u_AmbientLight", [0.2f32, 0.2, 0.2].into())?;
+
+    // Calculate the model matrix
+    // Translate to Y direction and rotate around Z-axis
+    let model_matrix = na::Matrix4::new_translation(&na::Vector3::new(0.0, 0.5, 0.0))
+        * na::Matrix4::new_rotation(na::Vector3::new(0.0, 0.0, 90f32.to_radians()));
+
+    // Calculate the view projection matrix
+    let mvp_matrix = na::Matrix4::new_perspective(1.0, 30f32.to_radians(), 1.0, 100.0)
+        * na::Matrix4::look_at_rh(
+            &na::Point3::new(3.0, 3.0, 7.0),
+            &na::Point3::new(0.0, 0.0, 0.0),
+            &na::Vector3::new(0.0, 1.0, 0.0),
+        )
+        * model_matrix;
+    // Pass the model view projection matrix to the variable u_MvpMatrix
+    backend.set_uniform(&program, "u_MvpMatrix", mvp_matrix.into())?;
+
+    // Calculate the matrix to transform the normal based on the model matrix
+    let normal_matrix = model_matrix
+        .try_inverse()
+        .ok_or_else(|| JsError::new("The model matrix is not invertible"))?
+        .transpose();
+    // Pass the transformation matrix for normals to u_NormalMatrix
+    backend.set_uniform(&program, "u_NormalMatrix", normal_matrix.into())?;
+
+    // Clear color and depth buffer
+    backend.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+    // Draw the cube
+    backend.draw_elements(GL::TRIANGLES, n, GL::UNSIGNED_INT);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+    use crate::utils::software::{Rasterizer, Vertex};
+
+    #[test]
+    fn uniforms_are_declared() {
+        draw_scene(&mut RecordingBackend::default()).unwrap();
+    }
+
+    #[test]
+    fn scene() {
+        let mut backend = RecordingBackend::default();
+        draw_scene(&mut backend).unwrap();
+        let mut rasterizer = Rasterizer::new(400, 400);
+        rasterizer.replay(&backend.take_calls(), |inputs| {
+            let color = inputs.attribute("a_Color");
+            let normal = (inputs.mat4("u_NormalMatrix") * inputs.attribute("a_Normal"))
+                .xyz()
+                .normalize();
+            let n_dot_l = inputs.vec3("u_LightDirection").dot(&normal).max(0.0);
+            let diffuse = inputs.vec3("u_LightColor").component_mul(&color.xyz()) * n_dot_l
+                + inputs.vec3("u_AmbientLight").component_mul(&color.xyz());
+            Vertex::new(
+                inputs.mat4("u_MvpMatrix") * inputs.attribute("a_Position"),
+                diffuse.push(color.w),
+            )
+        });
+        rasterizer.assert_golden("ch08/lighted_translated_rotated_cube");
+    }
+}
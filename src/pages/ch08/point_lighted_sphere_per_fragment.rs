@@ -0,0 +1,121 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{RenderBackend, WebGl2Backend};
+
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    let canvas = yew::use_node_ref();
+    crate::utils::use_webgl2_canvas_render(canvas.clone(), render);
+
+    yew::html! {
+        <canvas ref={canvas} width="400" height="400" />
+    }
+}
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+attribute vec4 a_Normal;
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_ModelMatrix;    // Model matrix
+uniform mat4 u_NormalMatrix;   // Transformation matrix of the normal
+varying vec4 v_Color;
+varying vec3 v_Normal;
+varying vec3 v_Position;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    // Calculate the vertex position in the world coordinate
+    v_Position = vec3(u_ModelMatrix * a_Position);
+    v_Normal = normalize(vec3(u_NormalMatrix * a_Normal));
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+uniform vec3 u_LightColor;     // Light color
+uniform vec3 u_LightPosition;  // Position of the light source
+uniform vec3 u_AmbientLight;   // Ambient light color
+varying vec3 v_Normal;
+varying vec3 v_Position;
+varying vec4 v_Color;
+void main() {
+    // Normalize the normal because it is interpolated and not 1.0 in length any more
+    vec3 normal = normalize(v_Normal);
+    // Calculate the light direction and make its length 1.
+    vec3 lightDirection = normalize(u_LightPosition - v_Position);
+    // The dot product of the light direction and the orientation of a surface (the normal)
+    float nDotL = max(dot(lightDirection, normal), 0.0);
+    // Calculate the final color from diffuse reflection and ambient reflection
+    vec3 diffuse = u_LightColor * v_Color.rgb * nDotL;
+    vec3 ambient = u_AmbientLight * v_Color.rgb;
+    gl_FragColor = vec4(diffuse + ambient, v_Color.a);
+}
+";
+
+fn render(gl: GL) -> Result<(), JsError> {
+    draw_scene(&mut WebGl2Backend::new(gl))
+}
+
+fn draw_scene<B: RenderBackend>(backend: &mut B) -> Result<(), JsError> {
+    let program = backend.create_program(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+    // Set the vertex coordinates, the color and the normal
+    let n = super::sphere::init_vertex_buffers(backend, &program)?;
+
+    // Set the clear color and enable the depth test
+    backend.clear_color(0.0, 0.0, 0.0, 1.0);
+    backend.enable(GL::DEPTH_TEST);
+
+    // Set the light color (white)
+    backend.set_uniform(&program, "u_LightColor", [1.0f32, 1.0, 1.0].into())?;
+    // Set the light position (in the world coordinate)
+    backend.set_uniform(&program, "u_LightPosition", [5.0f32, 8.0, 7.0].into())?;
+    // Set the ambient light
+    backend.set_uniform(&program, "u_AmbientLight", [0.2f32, 0.2, 0.2].into())?;
+
+    // Calculate the model matrix
+    // Rotate around the y-axis
+    let model_matrix = na::Matrix4::new_rotation(na::Vector3::new(0.0, 90f32.to_radians(), 0.0));
+    // Pass the model matrix to u_ModelMatrix
+    backend.set_uniform(&program, "u_ModelMatrix", model_matrix.into())?;
+
+    // Calculate the model view projection matrix
+    let mvp_matrix = na::Matrix4::new_perspective(1.0, 30f32.to_radians(), 1.0, 100.0)
+        * na::Matrix4::look_at_rh(
+            &na::Point3::new(0.0, 0.0, 6.0),
+            &na::Point3::new(0.0, 0.0, 0.0),
+            &na::Vector3::new(0.0, 1.0, 0.0),
+        )
+        * model_matrix;
+    // Pass the model view projection matrix to u_MvpMatrix
+    backend.set_uniform(&program, "u_MvpMatrix", mvp_matrix.into())?;
+
+    // Calculate the matrix to transform the normal based on the model matrix
+    let normal_matrix = model_matrix
+        .try_inverse()
+        .ok_or_else(|| JsError::new("The model matrix is not invertible"))?
+        .transpose();
+    // Pass the transformation matrix for normals to u_NormalMatrix
+    backend.set_uniform(&program, "u_NormalMatrix", normal_matrix.into())?;
+
+    // Clear color and depth buffer
+    backend.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+    // Draw the sphere
+    backend.draw_elements(GL::TRIANGLES, n, GL::UNSIGNED_INT);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::recording_backend::RecordingBackend;
+
+    #[test]
+    fn uniforms_are_declared() {
+        draw_scene(&mut RecordingBackend::default()).unwrap();
+    }
+}
@@ -0,0 +1,365 @@
+use gloo::events::EventListener;
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{
+    HtmlCanvasElement, KeyboardEvent, MouseEvent, PointerEvent, WebGl2RenderingContext as GL,
+    WheelEvent,
+};
+use yew::NodeRef;
+
+use crate::utils::{
+    listen_document, webgl2_context, AnimationLoop, Camera, CameraControl, ControlMode, Frame,
+    Mesh, Projection, ShaderProgram, VertexArray, WebGl2RenderingContextExt,
+};
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Normal;
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_ModelMatrix;    // Model matrix
+uniform mat4 u_NormalMatrix;   // Transformation matrix of the normal
+varying vec3 v_Normal;
+varying vec3 v_Position;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    // Calculate the vertex position in the world coordinate
+    v_Position = vec3(u_ModelMatrix * a_Position);
+    v_Normal = normalize(vec3(u_NormalMatrix * a_Normal));
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+uniform vec3 u_LightColor;     // Light color
+uniform vec3 u_LightPosition;  // Position of the light source
+uniform vec3 u_AmbientLight;   // Ambient light color
+varying vec3 v_Normal;
+varying vec3 v_Position;
+#include \"common/lighting.glsl\"
+void main() {
+    const vec3 color = vec3(1.0, 0.6, 0.2);
+    vec3 normal = normalize(v_Normal);
+    vec3 light = pointLight(color, normal, v_Position, u_LightPosition, u_LightColor, u_AmbientLight);
+    gl_FragColor = vec4(light, 1.0);
+}
+";
+
+// Rotation angle (degrees/second)
+const ANGLE_STEP: f32 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Cube,
+    Sphere,
+    Cylinder,
+    Cone,
+    Torus,
+    Plane,
+}
+
+impl Shape {
+    const ALL: [Shape; 6] = [
+        Shape::Cube,
+        Shape::Sphere,
+        Shape::Cylinder,
+        Shape::Cone,
+        Shape::Torus,
+        Shape::Plane,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Shape::Cube => "CUBE",
+            Shape::Sphere => "SPHERE",
+            Shape::Cylinder => "CYLINDER",
+            Shape::Cone => "CONE",
+            Shape::Torus => "TORUS",
+            Shape::Plane => "PLANE",
+        }
+    }
+
+    fn mesh(self) -> Mesh {
+        match self {
+            Shape::Cube => Mesh::cube(1.5),
+            Shape::Sphere => Mesh::sphere(1.0, 24, 12),
+            Shape::Cylinder => Mesh::cylinder(0.8, 1.6, 24),
+            Shape::Cone => Mesh::cone(0.9, 1.6, 24),
+            Shape::Torus => Mesh::torus(0.8, 0.3, 32, 12),
+            Shape::Plane => Mesh::plane(2.0, 2.0, 4, 4),
+        }
+    }
+}
+
+pub enum Message {
+    ChangeShape(Shape),
+    ToggleFlat,
+    ToggleFly,
+    PointerDown(PointerEvent),
+    PointerMove(PointerEvent),
+    PointerUp(PointerEvent),
+    PointerCancel(PointerEvent),
+    Wheel(WheelEvent),
+    KeyDown(KeyboardEvent),
+    KeyUp(KeyboardEvent),
+    Blur,
+    Animate(Frame),
+    ContextRestored,
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    vao: Option<VertexArray>,
+    animation: Option<AnimationLoop>,
+    shape: Shape,
+    // Whether every triangle has its own normal
+    flat: bool,
+    // Vertex and triangle counts of the uploaded mesh
+    stats: (usize, usize),
+    // Orbit or fly around the shape
+    control: CameraControl,
+    // The rotation angle of the shape (degrees)
+    angle: f32,
+    // The keys pressed anywhere in the page and the window losing the focus,
+    // until the page is left
+    key_listeners: Vec<EventListener>,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+        program.set_uniform("u_LightColor", &[1.0f32, 1.0, 1.0])?;
+        program.set_uniform("u_LightPosition", &[3.0f32, 4.0, 5.0])?;
+        program.set_uniform("u_AmbientLight", &[0.2f32, 0.2, 0.2])?;
+
+        // Set the clear color and enable the depth test
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        self.animation = Some(AnimationLoop::new(move |frame| {
+            link.send_message(Message::Animate(frame))
+        }));
+        Ok(())
+    }
+
+    fn load_mesh(&mut self) -> Result<(), JsError> {
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            let mut mesh = self.shape.mesh();
+            if self.flat {
+                mesh = mesh.with_flat_normals();
+            }
+            self.vao = Some(mesh.vertex_array(gl, program)?);
+            self.stats = (mesh.vertex_count(), mesh.triangle_count());
+        }
+        Ok(())
+    }
+
+    fn draw(&self, gl: &GL, program: &ShaderProgram, vao: &VertexArray) -> Result<(), JsError> {
+        // Tilt the shape toward the viewer and turn it around the y axis
+        let model_matrix = na::Matrix4::new_rotation(na::Vector3::x() * 20f32.to_radians())
+            * na::Matrix4::new_rotation(na::Vector3::y() * self.angle.to_radians());
+        let normal_matrix = model_matrix
+            .try_inverse()
+            .ok_or_else(|| JsError::new("The model matrix is not invertible"))?
+            .transpose();
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        let mvp_matrix = self.control.camera().view_proj_matrix(aspect) * model_matrix;
+        program.set_uniform("u_ModelMatrix", &model_matrix)?;
+        program.set_uniform("u_NormalMatrix", &normal_matrix)?;
+        program.set_uniform("u_MvpMatrix", &mvp_matrix)?;
+
+        // Clear color and depth buffer
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        vao.bind();
+        gl.checked_draw_elements(GL::TRIANGLES, self.stats.1 as i32 * 3, GL::UNSIGNED_INT, 0);
+        Ok(())
+    }
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            vao: None,
+            animation: None,
+            shape: Shape::Sphere,
+            flat: false,
+            stats: (0, 0),
+            control: CameraControl::new(
+                Camera::new(
+                    na::Point3::new(0.0, 2.0, 7.0),
+                    na::Point3::origin(),
+                    Projection::Perspective {
+                        fovy: 30f32.to_radians(),
+                        near: 0.1,
+                        far: 100.0,
+                    },
+                ),
+                ControlMode::Orbit,
+            ),
+            angle: 0.0,
+            key_listeners: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                // Upload the first shape and show its counts
+                ctx.link().send_message(Message::ChangeShape(self.shape));
+                false
+            }
+            Message::ChangeShape(shape) => {
+                self.shape = shape;
+                self.load_mesh().unwrap_throw();
+                true
+            }
+            Message::ToggleFlat => {
+                self.flat = !self.flat;
+                self.load_mesh().unwrap_throw();
+                true
+            }
+            Message::ToggleFly => {
+                self.control.set_mode(match self.control.mode() {
+                    ControlMode::Orbit => ControlMode::Fly,
+                    ControlMode::Fly => ControlMode::Orbit,
+                });
+                true
+            }
+            // The camera is drawn with the next frame
+            Message::PointerDown(event) => {
+                self.control.onpointerdown(&event);
+                false
+            }
+            Message::PointerMove(event) => {
+                self.control.onpointermove(&event);
+                false
+            }
+            Message::PointerUp(event) => {
+                self.control.onpointerup(&event);
+                false
+            }
+            Message::PointerCancel(event) => {
+                self.control.onpointercancel(&event);
+                false
+            }
+            Message::Wheel(event) => {
+                self.control.onwheel(&event);
+                false
+            }
+            Message::KeyDown(event) => {
+                self.control.onkeydown(&event);
+                false
+            }
+            Message::KeyUp(event) => {
+                self.control.onkeyup(&event);
+                false
+            }
+            Message::Blur => {
+                self.control.stop_tracking();
+                false
+            }
+            Message::Animate(frame) => {
+                self.control.update(frame.delta);
+                self.angle = (self.angle + ANGLE_STEP * frame.delta as f32 / 1000.0) % 360.0;
+                if let (Some(gl), Some(program), Some(vao)) =
+                    (self.gl.as_ref(), self.program.as_ref(), self.vao.as_ref())
+                {
+                    self.draw(gl, program, vao).unwrap_throw();
+                }
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        let link = ctx.link();
+        let buttons = Shape::ALL.iter().map(|&shape| {
+            yew::html! {
+                <button
+                    onclick={link.callback(move |_| Message::ChangeShape(shape))}
+                    disabled={shape == self.shape}
+                >{ shape.name() }</button>
+            }
+        });
+        let (vertices, triangles) = self.stats;
+        yew::html! {
+            <div>
+                // Dragged by fingers too
+                <canvas
+                    ref={self.canvas.clone()}
+                    style="touch-action: none"
+                    onpointerdown={link.callback(Message::PointerDown)}
+                    onpointermove={link.callback(Message::PointerMove)}
+                    onpointerup={link.callback(Message::PointerUp)}
+                    onpointercancel={link.callback(Message::PointerCancel)}
+                    onwheel={link.callback(Message::Wheel)}
+                    oncontextmenu={|event: MouseEvent| event.prevent_default()}
+                    width="400"
+                    height="400"
+                />
+                <p>{ for buttons }</p>
+                <p>
+                    <button onclick={link.callback(|_| Message::ToggleFlat)}>
+                        { if self.flat { "SMOOTH" } else { "FLAT" } }
+                    </button>
+                    <button onclick={link.callback(|_| Message::ToggleFly)}>
+                        { match self.control.mode() {
+                            ControlMode::Orbit => "FLY",
+                            ControlMode::Fly => "ORBIT",
+                        } }
+                    </button>
+                    { format!(" {} vertices, {} triangles", vertices, triangles) }
+                </p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+            // Upload the first shape and show its counts
+            ctx.link().send_message(Message::ChangeShape(self.shape));
+
+            let (down, up, blur) = (ctx.link().clone(), ctx.link().clone(), ctx.link().clone());
+            self.key_listeners = vec![
+                listen_document("keydown", move |event: &KeyboardEvent| {
+                    down.send_message(Message::KeyDown(event.clone()));
+                }),
+                listen_document("keyup", move |event: &KeyboardEvent| {
+                    up.send_message(Message::KeyUp(event.clone()));
+                }),
+                // The keys held when switching to another window are released
+                EventListener::new(&gloo::utils::window(), "blur", move |_| {
+                    blur.send_message(Message::Blur);
+                }),
+            ];
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // removeEventListener
+        self.key_listeners.clear();
+        // cancelAnimationFrame
+        self.animation = None;
+        self.control.stop_tracking();
+    }
+}
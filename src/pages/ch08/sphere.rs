@@ -0,0 +1,30 @@
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{Mesh, RenderBackend, VertexLayout};
+
+// Number of slices and stacks of the sphere
+const SPHERE_DIV: u32 = 13;
+
+/// Write the positions, normals and colors of a white sphere to the attributes
+/// `a_Position`, `a_Normal` and `a_Color` and return the number of indices,
+/// which are 32-bit
+pub fn init_vertex_buffers<B: RenderBackend>(
+    backend: &mut B,
+    program: &B::Program,
+) -> Result<i32, JsError> {
+    let sphere = Mesh::sphere(1.0, SPHERE_DIV, SPHERE_DIV);
+    // The sphere is white
+    let colors = [1.0f32, 1.0, 1.0].repeat(sphere.vertex_count());
+    let color = VertexLayout::new().attribute("a_Color", 3, GL::FLOAT, false);
+
+    let mesh_buffers = sphere.buffers();
+    let mut buffers = mesh_buffers
+        .iter()
+        .map(|(layout, data)| (layout, *data))
+        .collect::<Vec<_>>();
+    buffers.push((&color, &colors));
+    backend.create_vertex_array(program, &buffers, Some(sphere.indices[..].into()))?;
+
+    Ok(sphere.indices.len() as i32)
+}
@@ -0,0 +1,8 @@
+mod cuboid;
+mod joint_model;
+mod multi_joint_model;
+mod multi_joint_model_segment;
+
+pub use self::joint_model::Page as JointModel;
+pub use self::multi_joint_model::Page as MultiJointModel;
+pub use self::multi_joint_model_segment::Page as MultiJointModel_segment;
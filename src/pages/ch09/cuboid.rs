@@ -0,0 +1,50 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use crate::utils::{Mesh, ShaderProgram, VertexArray, WebGl2RenderingContextExt};
+
+/// The number of indices of a cuboid, 2 triangles for each of its 6 faces
+pub const N: i32 = 36;
+
+/// Write a `width` x `height` x `depth` cuboid standing on the origin to the
+/// attributes `a_Position` and `a_Normal`
+pub fn init_vertex_buffers(
+    gl: &GL,
+    program: &ShaderProgram,
+    width: f32,
+    height: f32,
+    depth: f32,
+) -> Result<VertexArray, JsError> {
+    let mut cuboid = Mesh::cube(1.0);
+    // Scale the unit cube and raise it onto the origin, the normals of its
+    // faces staying the same
+    for position in cuboid.positions.chunks_exact_mut(3) {
+        position[0] *= width;
+        position[1] = (position[1] + 0.5) * height;
+        position[2] *= depth;
+    }
+    cuboid.vertex_array(gl, program)
+}
+
+/// Draw the cuboid bound to the current vertex array object, `model_matrix`
+/// placing it in the world
+pub fn draw(
+    gl: &GL,
+    program: &ShaderProgram,
+    view_proj_matrix: &na::Matrix4<f32>,
+    model_matrix: &na::Matrix4<f32>,
+) -> Result<(), JsError> {
+    // Calculate the model view project matrix and pass it to u_MvpMatrix
+    program.set_uniform("u_MvpMatrix", &(view_proj_matrix * model_matrix))?;
+    // Calculate the normal transformation matrix and pass it to u_NormalMatrix
+    let normal_matrix = model_matrix
+        .try_inverse()
+        .ok_or_else(|| JsError::new("The model matrix is not invertible"))?
+        .transpose();
+    program.set_uniform("u_NormalMatrix", &normal_matrix)?;
+
+    // Draw
+    gl.checked_draw_elements(GL::TRIANGLES, N, GL::UNSIGNED_INT, 0);
+    Ok(())
+}
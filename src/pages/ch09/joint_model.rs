@@ -0,0 +1,197 @@
+use gloo::events::EventListener;
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, KeyEvent, KeyboardEvent, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    listen_document, webgl2_context, MatrixStack, Node, ShaderProgram, WebGl2RenderingContextExt,
+};
+
+use super::cuboid;
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Normal;
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_NormalMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    // Shading calculation to make the arm look three-dimensional
+    vec3 lightDirection = normalize(vec3(0.0, 0.5, 0.7)); // Light direction
+    vec4 color = vec4(1.0, 0.4, 0.0, 1.0);
+    vec3 normal = normalize((u_NormalMatrix * a_Normal).xyz);
+    float nDotL = max(dot(normal, lightDirection), 0.0);
+    v_Color = vec4(color.rgb * nDotL + vec3(0.1), color.a);
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+// The rotation angle per key press (degrees)
+const ANGLE_STEP: f32 = 3.0;
+
+pub enum Message {
+    RotateArm1(f32),
+    RotateJoint1(f32),
+    ContextRestored,
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    view_proj_matrix: na::Matrix4<f32>,
+    // The rotation angle of arm1 (degrees)
+    arm1_angle: f32,
+    // The rotation angle of joint1 (degrees)
+    joint1_angle: f32,
+
+    // The keys pressed anywhere in the page, until the page is left
+    keydown_listener: Option<EventListener>,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+        // Both arms are drawn by scaling a single cuboid
+        cuboid::init_vertex_buffers(&gl, &program, 1.0, 1.0, 1.0)?;
+
+        // Set the clear color and enable the depth test
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+
+        // Calculate the view projection matrix
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        self.view_proj_matrix =
+            na::Matrix4::new_perspective(aspect, 50f32.to_radians(), 1.0, 100.0)
+                * na::Matrix4::look_at_rh(
+                    &na::Point3::new(20.0, 10.0, 30.0),
+                    &na::Point3::new(0.0, 0.0, 0.0),
+                    &na::Vector3::new(0.0, 1.0, 0.0),
+                );
+
+        self.rerender(&gl, &program)?;
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        Ok(())
+    }
+
+    fn rerender(&self, gl: &GL, program: &ShaderProgram) -> Result<(), JsError> {
+        let arm1_length = 10.0;
+        // Arm1 stands on the base and turns around the y-axis,
+        // arm2 is attached on top of it and turns around the z-axis
+        let scene = Node::new(
+            na::Matrix4::new_translation(&na::Vector3::new(0.0, -12.0, 0.0))
+                * na::Matrix4::new_rotation(na::Vector3::y() * self.arm1_angle.to_radians()),
+        )
+        .with_drawable(na::Vector3::new(3.0, arm1_length, 3.0))
+        .with_child(
+            Node::new(
+                na::Matrix4::new_translation(&na::Vector3::new(0.0, arm1_length, 0.0))
+                    * na::Matrix4::new_rotation(na::Vector3::z() * self.joint1_angle.to_radians()),
+            )
+            .with_drawable(na::Vector3::new(4.0, 10.0, 4.0)),
+        );
+
+        // Clear color and depth buffer
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        scene.traverse(&mut MatrixStack::default(), &mut |size, model_matrix| {
+            let model_matrix = model_matrix * na::Matrix4::new_nonuniform_scaling(size);
+            cuboid::draw(gl, program, &self.view_proj_matrix, &model_matrix)
+        })
+    }
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            view_proj_matrix: na::Matrix4::identity(),
+            arm1_angle: -90.0,
+            joint1_angle: 0.0,
+            keydown_listener: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+            }
+            Message::RotateArm1(step) => {
+                self.arm1_angle = (self.arm1_angle + step) % 360.0;
+            }
+            Message::RotateJoint1(step) => {
+                self.joint1_angle = (self.joint1_angle + step).clamp(-135.0, 135.0);
+            }
+        }
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            self.rerender(gl, program).unwrap_throw();
+        }
+        false
+    }
+
+    fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                <p>{ "Right/Left arrow keys: arm1 rotation (y-axis), Up/Down arrow keys: joint1 rotation (z-axis)" }</p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+
+            let link = ctx.link().clone();
+            self.keydown_listener =
+                Some(listen_document("keydown", move |event: &KeyboardEvent| {
+                    // https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/keyCode
+                    let message = match event.key_code() {
+                        KeyEvent::DOM_VK_UP => Message::RotateJoint1(ANGLE_STEP),
+                        KeyEvent::DOM_VK_DOWN => Message::RotateJoint1(-ANGLE_STEP),
+                        KeyEvent::DOM_VK_RIGHT => Message::RotateArm1(ANGLE_STEP),
+                        KeyEvent::DOM_VK_LEFT => Message::RotateArm1(-ANGLE_STEP),
+                        _ => return,
+                    };
+                    // The arrow keys do not scroll the page
+                    event.prevent_default();
+                    link.send_message(message);
+                }));
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // removeEventListener
+        self.keydown_listener = None;
+    }
+}
@@ -0,0 +1,276 @@
+use gloo::events::EventListener;
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, KeyEvent, KeyboardEvent, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    listen_document, webgl2_context, MatrixStack, Node, ShaderProgram, VertexArray,
+    WebGl2RenderingContextExt,
+};
+
+use super::cuboid;
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Normal;
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_NormalMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    // Shading calculation to make the arm look three-dimensional
+    vec3 lightDirection = normalize(vec3(0.0, 0.5, 0.7)); // Light direction
+    vec4 color = vec4(1.0, 0.4, 0.0, 1.0);
+    vec3 normal = normalize((u_NormalMatrix * a_Normal).xyz);
+    float nDotL = max(dot(normal, lightDirection), 0.0);
+    v_Color = vec4(color.rgb * nDotL + vec3(0.1), color.a);
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+// The rotation angle per key press (degrees)
+const ANGLE_STEP: f32 = 3.0;
+
+pub enum Message {
+    RotateArm1(f32),
+    RotateJoint1(f32),
+    RotateJoint2(f32),
+    RotateJoint3(f32),
+    ContextRestored,
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    // The base, arm1, arm2, the palm and the fingers
+    segments: Vec<VertexArray>,
+    view_proj_matrix: na::Matrix4<f32>,
+    // The rotation angle of arm1 (degrees)
+    arm1_angle: f32,
+    // The rotation angle of joint1 (degrees)
+    joint1_angle: f32,
+    // The rotation angle of joint2 (degrees)
+    joint2_angle: f32,
+    // The rotation angle of joint3 (degrees)
+    joint3_angle: f32,
+
+    // The keys pressed anywhere in the page, until the page is left
+    keydown_listener: Option<EventListener>,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+        // Each part has its own vertex buffers of the right size
+        self.segments = vec![
+            cuboid::init_vertex_buffers(&gl, &program, 10.0, BASE_HEIGHT, 10.0)?,
+            cuboid::init_vertex_buffers(&gl, &program, 3.0, ARM1_LENGTH, 3.0)?,
+            cuboid::init_vertex_buffers(&gl, &program, 4.0, ARM2_LENGTH, 4.0)?,
+            cuboid::init_vertex_buffers(&gl, &program, 2.0, PALM_LENGTH, 6.0)?,
+            cuboid::init_vertex_buffers(&gl, &program, 1.0, 2.0, 1.0)?,
+        ];
+
+        // Set the clear color and enable the depth test
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+
+        // Calculate the view projection matrix
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        self.view_proj_matrix =
+            na::Matrix4::new_perspective(aspect, 50f32.to_radians(), 1.0, 100.0)
+                * na::Matrix4::look_at_rh(
+                    &na::Point3::new(20.0, 10.0, 30.0),
+                    &na::Point3::new(0.0, 0.0, 0.0),
+                    &na::Vector3::new(0.0, 1.0, 0.0),
+                );
+
+        self.rerender(&gl, &program)?;
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        Ok(())
+    }
+
+    fn rerender(&self, gl: &GL, program: &ShaderProgram) -> Result<(), JsError> {
+        let scene = robot_arm(
+            self.arm1_angle,
+            self.joint1_angle,
+            self.joint2_angle,
+            self.joint3_angle,
+            [
+                &self.segments[0],
+                &self.segments[1],
+                &self.segments[2],
+                &self.segments[3],
+                &self.segments[4],
+                &self.segments[4],
+            ],
+        );
+
+        // Clear color and depth buffer
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        scene.traverse(&mut MatrixStack::default(), &mut |segment, model_matrix| {
+            segment.bind();
+            cuboid::draw(gl, program, &self.view_proj_matrix, model_matrix)
+        })
+    }
+}
+
+const BASE_HEIGHT: f32 = 2.0;
+const ARM1_LENGTH: f32 = 10.0;
+const ARM2_LENGTH: f32 = 10.0;
+const PALM_LENGTH: f32 = 2.0;
+
+fn rotation(axis: na::Vector3<f32>, angle: f32) -> na::Matrix4<f32> {
+    na::Matrix4::new_rotation(axis * angle.to_radians())
+}
+
+fn translation(x: f32, y: f32, z: f32) -> na::Matrix4<f32> {
+    na::Matrix4::new_translation(&na::Vector3::new(x, y, z))
+}
+
+/// The robot arm: a base, arm1, arm2, a palm and two fingers, each part attached
+/// to the top of the previous one
+fn robot_arm<D>(
+    arm1_angle: f32,
+    joint1_angle: f32,
+    joint2_angle: f32,
+    joint3_angle: f32,
+    parts: [D; 6],
+) -> Node<D> {
+    let [base, arm1, arm2, palm, finger1, finger2] = parts;
+
+    // The fingers are attached to the tip of the palm and close around the x-axis
+    let finger1 = Node::new(translation(0.0, 0.0, 2.0) * rotation(na::Vector3::x(), joint3_angle))
+        .with_drawable(finger1);
+    let finger2 =
+        Node::new(translation(0.0, 0.0, -2.0) * rotation(na::Vector3::x(), -joint3_angle))
+            .with_drawable(finger2);
+    let fingers = Node::new(translation(0.0, PALM_LENGTH, 0.0))
+        .with_child(finger1)
+        .with_child(finger2);
+
+    let palm =
+        Node::new(translation(0.0, ARM2_LENGTH, 0.0) * rotation(na::Vector3::y(), joint2_angle))
+            .with_drawable(palm)
+            .with_child(fingers);
+    let arm2 =
+        Node::new(translation(0.0, ARM1_LENGTH, 0.0) * rotation(na::Vector3::z(), joint1_angle))
+            .with_drawable(arm2)
+            .with_child(palm);
+    let arm1 =
+        Node::new(translation(0.0, BASE_HEIGHT, 0.0) * rotation(na::Vector3::y(), arm1_angle))
+            .with_drawable(arm1)
+            .with_child(arm2);
+    Node::new(translation(0.0, -12.0, 0.0))
+        .with_drawable(base)
+        .with_child(arm1)
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            segments: Vec::new(),
+            view_proj_matrix: na::Matrix4::identity(),
+            arm1_angle: -90.0,
+            joint1_angle: 0.0,
+            joint2_angle: 0.0,
+            joint3_angle: 0.0,
+            keydown_listener: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+            }
+            Message::RotateArm1(step) => {
+                self.arm1_angle = (self.arm1_angle + step) % 360.0;
+            }
+            Message::RotateJoint1(step) => {
+                self.joint1_angle = (self.joint1_angle + step).clamp(-135.0, 135.0);
+            }
+            Message::RotateJoint2(step) => {
+                self.joint2_angle = (self.joint2_angle + step) % 360.0;
+            }
+            Message::RotateJoint3(step) => {
+                self.joint3_angle = (self.joint3_angle + step).clamp(-60.0, 60.0);
+            }
+        }
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            self.rerender(gl, program).unwrap_throw();
+        }
+        false
+    }
+
+    fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                <p>{ "Right/Left arrow keys: arm1 rotation (y-axis), Up/Down arrow keys: joint1 rotation (z-axis)" }</p>
+                <p>{ "Z/X keys: joint2 rotation (y-axis), C/V keys: joint3 rotation (x-axis)" }</p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+
+            let link = ctx.link().clone();
+            self.keydown_listener =
+                Some(listen_document("keydown", move |event: &KeyboardEvent| {
+                    // https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/keyCode
+                    let message = match event.key_code() {
+                        KeyEvent::DOM_VK_UP => Message::RotateJoint1(ANGLE_STEP),
+                        KeyEvent::DOM_VK_DOWN => Message::RotateJoint1(-ANGLE_STEP),
+                        KeyEvent::DOM_VK_RIGHT => Message::RotateArm1(ANGLE_STEP),
+                        KeyEvent::DOM_VK_LEFT => Message::RotateArm1(-ANGLE_STEP),
+                        KeyEvent::DOM_VK_Z => Message::RotateJoint2(ANGLE_STEP),
+                        KeyEvent::DOM_VK_X => Message::RotateJoint2(-ANGLE_STEP),
+                        KeyEvent::DOM_VK_V => Message::RotateJoint3(ANGLE_STEP),
+                        KeyEvent::DOM_VK_C => Message::RotateJoint3(-ANGLE_STEP),
+                        _ => return,
+                    };
+                    // The arrow keys do not scroll the page
+                    event.prevent_default();
+                    link.send_message(message);
+                }));
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // removeEventListener
+        self.keydown_listener = None;
+    }
+}
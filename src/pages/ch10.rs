@@ -0,0 +1,16 @@
+mod cube;
+mod framebuffer_object;
+mod gltf_viewer;
+mod obj_viewer;
+mod pick_face;
+mod pick_object;
+mod shadow;
+mod shadow_highp;
+
+pub use self::framebuffer_object::Page as FramebufferObject;
+pub use self::gltf_viewer::Page as GltfViewer;
+pub use self::obj_viewer::Page as OBJViewer;
+pub use self::pick_face::Page as PickFace;
+pub use self::pick_object::Page as PickObject;
+pub use self::shadow::Page as Shadow;
+pub use self::shadow_highp::Page as Shadow_highp;
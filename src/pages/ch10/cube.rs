@@ -0,0 +1,32 @@
+use crate::utils::Mesh;
+
+// Number of indices of the cube, 2 triangles per face
+pub const INDEX_COUNT: i32 = 36;
+
+/// The cube of side 2 of the book, 4 vertices per face in the order of `COLORS`
+/// and `FACES`
+pub fn mesh() -> Mesh {
+    Mesh::cube(2.0)
+}
+
+// Colors of the faces: front, right, up, left, down and back
+#[rustfmt::skip]
+pub const COLORS: &[f32] = &[
+    0.32, 0.18, 0.56,  0.32, 0.18, 0.56,  0.32, 0.18, 0.56,  0.32, 0.18, 0.56, // v0-v1-v2-v3 front
+    0.5, 0.41, 0.69,   0.5, 0.41, 0.69,   0.5, 0.41, 0.69,   0.5, 0.41, 0.69,  // v0-v3-v4-v5 right
+    0.78, 0.69, 0.84,  0.78, 0.69, 0.84,  0.78, 0.69, 0.84,  0.78, 0.69, 0.84, // v0-v5-v6-v1 up
+    0.0, 0.32, 0.61,   0.0, 0.32, 0.61,   0.0, 0.32, 0.61,   0.0, 0.32, 0.61,  // v1-v6-v7-v2 left
+    0.27, 0.58, 0.82,  0.27, 0.58, 0.82,  0.27, 0.58, 0.82,  0.27, 0.58, 0.82, // v7-v4-v3-v2 down
+    0.73, 0.82, 0.93,  0.73, 0.82, 0.93,  0.73, 0.82, 0.93,  0.73, 0.82, 0.93, // v4-v7-v6-v5 back
+];
+
+// Faces to which the vertices belong
+#[rustfmt::skip]
+pub const FACES: &[u8] = &[
+    1, 1, 1, 1, // v0-v1-v2-v3 front
+    2, 2, 2, 2, // v0-v3-v4-v5 right
+    3, 3, 3, 3, // v0-v5-v6-v1 up
+    4, 4, 4, 4, // v1-v6-v7-v2 left
+    5, 5, 5, 5, // v7-v4-v3-v2 down
+    6, 6, 6, 6, // v4-v7-v6-v5 back
+];
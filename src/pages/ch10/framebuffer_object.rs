@@ -0,0 +1,226 @@
+use nalgebra as na;
+use wasm_bindgen::{JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    webgl2_context, AnimationLoop, Frame, RenderTarget, ShaderProgram, VertexArray, VertexLayout,
+    WebGl2RenderingContextExt,
+};
+
+use super::cube;
+
+// Shaders of the scene drawn into the framebuffer object
+const OFFSCREEN_VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_MvpMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const OFFSCREEN_FSHADER_SOURCE: &str = "
+precision mediump float;
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = v_Color;
+}
+";
+
+// Shaders of the cube textured with the framebuffer object
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec2 a_TexCoord;
+uniform mat4 u_MvpMatrix;
+varying vec2 v_TexCoord;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_TexCoord = a_TexCoord;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+uniform sampler2D u_Sampler;
+varying vec2 v_TexCoord;
+void main() {
+    gl_FragColor = texture2D(u_Sampler, v_TexCoord);
+}
+";
+
+// Size of the offscreen drawing
+const OFFSCREEN_WIDTH: i32 = 256;
+const OFFSCREEN_HEIGHT: i32 = 256;
+
+// Rotation angle (degrees/second)
+const ANGLE_STEP: f32 = 30.0;
+
+pub enum Message {
+    Animate(Frame),
+    ContextRestored,
+}
+
+struct Scene {
+    offscreen_program: ShaderProgram,
+    offscreen_cube: VertexArray,
+    program: ShaderProgram,
+    cube: VertexArray,
+    target: RenderTarget,
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    scene: Option<Scene>,
+    animation: Option<AnimationLoop>,
+    // The rotation angle of the cubes (degrees)
+    angle: f32,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let offscreen_program =
+            gl.init_shaders(OFFSCREEN_VSHADER_SOURCE, OFFSCREEN_FSHADER_SOURCE)?;
+        let offscreen_cube = cube::mesh().vertex_array(&gl, &offscreen_program)?.buffer(
+            &offscreen_program,
+            &VertexLayout::new().attribute("a_Color", 3, GL::FLOAT, false),
+            cube::COLORS,
+        )?;
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+        let cube = cube::mesh().vertex_array(&gl, &program)?;
+        // The texture of the framebuffer object is bound to the texture unit 0
+        program.set_uniform("u_Sampler", &0)?;
+
+        // Prepare the framebuffer object to draw into
+        let target = RenderTarget::new(&gl, OFFSCREEN_WIDTH, OFFSCREEN_HEIGHT, true)?;
+
+        // Enable the depth test
+        gl.enable(GL::DEPTH_TEST);
+
+        self.gl = Some(gl);
+        self.scene = Some(Scene {
+            offscreen_program,
+            offscreen_cube,
+            program,
+            cube,
+            target,
+        });
+        self.animation = Some(AnimationLoop::new(move |frame| {
+            link.send_message(Message::Animate(frame))
+        }));
+        Ok(())
+    }
+
+    fn draw(&self, gl: &GL, scene: &Scene) -> Result<(), JsValue> {
+        let radian = self.angle.to_radians();
+
+        // Draw the colored cube into the framebuffer object
+        scene.target.bind()?;
+        let aspect = scene.target.width() as f32 / scene.target.height() as f32;
+        let model_matrix = na::Matrix4::new_rotation(na::Vector3::new(radian, radian, 0.0));
+        let mvp_matrix = view_proj_matrix(aspect, 7.0) * model_matrix;
+        scene.offscreen_program.use_program();
+        scene
+            .offscreen_program
+            .set_uniform("u_MvpMatrix", &mvp_matrix)?;
+        scene.offscreen_cube.bind();
+        // Clear the framebuffer object with a light color
+        gl.clear_color(0.2, 0.2, 0.4, 1.0);
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+        draw_cube(gl);
+        scene.target.unbind();
+
+        // Draw the cube textured with the drawing above into the canvas
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        let model_matrix = na::Matrix4::new_rotation(na::Vector3::y() * radian / 2.0);
+        let mvp_matrix = view_proj_matrix(aspect, 6.0) * model_matrix;
+        scene.program.use_program();
+        scene.program.set_uniform("u_MvpMatrix", &mvp_matrix)?;
+        scene.cube.bind();
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, Some(scene.target.texture()));
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+        draw_cube(gl);
+        // Do not leave the texture bound while drawing into it
+        gl.bind_texture(GL::TEXTURE_2D, None);
+
+        Ok(())
+    }
+}
+
+fn view_proj_matrix(aspect: f32, distance: f32) -> na::Matrix4<f32> {
+    na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0)
+        * na::Matrix4::look_at_rh(
+            &na::Point3::new(0.0, 2.0, distance),
+            &na::Point3::new(0.0, 0.0, 0.0),
+            &na::Vector3::new(0.0, 1.0, 0.0),
+        )
+}
+
+fn draw_cube(gl: &GL) {
+    gl.checked_draw_elements(GL::TRIANGLES, cube::INDEX_COUNT, GL::UNSIGNED_INT, 0);
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            scene: None,
+            animation: None,
+            angle: 0.0,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+            }
+            Message::Animate(frame) => {
+                self.angle = (self.angle + ANGLE_STEP * frame.delta as f32 / 1000.0) % 720.0;
+                if let (Some(gl), Some(scene)) = (self.gl.as_ref(), self.scene.as_ref()) {
+                    self.draw(gl, scene).unwrap_throw();
+                }
+            }
+        }
+        false
+    }
+
+    fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
+        yew::html! {
+            <canvas
+                ref={self.canvas.clone()}
+                width="400"
+                height="400"
+            />
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // cancelAnimationFrame
+        self.animation = None;
+    }
+}
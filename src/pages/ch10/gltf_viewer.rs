@@ -0,0 +1,445 @@
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::gltf::{BufferSource, Document, Image, Model};
+use crate::utils::{
+    error_message, fetch_bytes, load_image, load_image_bytes, webgl2_context, AnimationLoop, Frame,
+    LoadImageError, MatrixStack, ShaderProgram, Texture, VertexArray, VertexLayout,
+    WebGl2RenderingContextExt,
+};
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Normal;
+attribute vec2 a_TexCoord;
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_NormalMatrix;
+varying vec3 v_Normal;
+varying vec2 v_TexCoord;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_Normal = vec3(u_NormalMatrix * a_Normal);
+    v_TexCoord = a_TexCoord;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+uniform vec3 u_LightDirection; // Direction to the light, normalized
+uniform vec4 u_BaseColorFactor;
+uniform sampler2D u_BaseColorTexture;
+uniform bool u_UseTexture;
+varying vec3 v_Normal;
+varying vec2 v_TexCoord;
+void main() {
+    vec4 color = u_BaseColorFactor;
+    if (u_UseTexture) {
+        color *= texture2D(u_BaseColorTexture, v_TexCoord);
+    }
+    float nDotL = max(dot(u_LightDirection, normalize(v_Normal)), 0.0);
+    gl_FragColor = vec4(color.rgb * (0.3 + 0.7 * nDotL), color.a);
+}
+";
+
+// The same scene, with its buffer and image in sibling files or in the
+// binary chunk of a single file
+const ASSETS: [&str; 2] = ["/resources/boxes.gltf", "/resources/boxes.glb"];
+
+// Rotation angle (degrees/second)
+const ANGLE_STEP: f32 = 20.0;
+
+pub enum Message {
+    Select(usize),
+    Loaded(usize, Box<Model>),
+    ImageLoaded(usize, usize, HtmlImageElement),
+    LoadFailed(usize, String),
+    Animate(Frame),
+    ContextRestored,
+}
+
+/// Vertex array object and draw parameters of a glTF primitive
+struct MeshPrimitive {
+    vao: VertexArray,
+    mode: u32,
+    count: i32,
+    indexed: bool,
+    material: Option<usize>,
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    animation: Option<AnimationLoop>,
+    // Index of the shown asset in ASSETS
+    selected: usize,
+    model: Option<Model>,
+    meshes: Vec<Vec<MeshPrimitive>>,
+    // The textures of the model, created as their images arrive
+    textures: Vec<Option<Texture>>,
+    error: Option<String>,
+    // The rotation angle of the scene (degrees)
+    angle: f32,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+        program.set_uniform(
+            "u_LightDirection",
+            &na::Vector3::new(0.5f32, 3.0, 4.0).normalize(),
+        )?;
+        program.set_uniform("u_BaseColorTexture", &0i32)?;
+
+        // Set the clear color, enable the depth test and blending
+        gl.clear_color(0.2, 0.2, 0.2, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        Ok(())
+    }
+
+    fn request_model(&self, link: yew::html::Scope<Self>) {
+        let selected = self.selected;
+        link.send_future(async move {
+            match load_model(ASSETS[selected]).await {
+                Ok(model) => Message::Loaded(selected, Box::new(model)),
+                Err(err) => Message::LoadFailed(selected, err),
+            }
+        });
+    }
+
+    fn request_images(&self, model: &Model, link: yew::html::Scope<Self>) {
+        let url = ASSETS[self.selected];
+        let base = &url[..url.rfind('/').map_or(0, |i| i + 1)];
+        for (index, image) in model.images.iter().enumerate() {
+            let selected = self.selected;
+            let loaded = move |result: Result<HtmlImageElement, LoadImageError>| match result {
+                Ok(image) => Message::ImageLoaded(selected, index, image),
+                Err(err) => Message::LoadFailed(selected, err.to_string()),
+            };
+            // The futures are created here, they do not borrow the model
+            match image {
+                Image::Uri(uri) if uri.starts_with("data:") => {
+                    let image = load_image(uri);
+                    link.send_future(async move { loaded(image.await) });
+                }
+                Image::Uri(uri) => {
+                    let image = load_image(&format!("{}{}", base, uri));
+                    link.send_future(async move { loaded(image.await) });
+                }
+                Image::Bytes { data, mime_type } => {
+                    let image = load_image_bytes(data, mime_type);
+                    link.send_future(async move { loaded(image.await) });
+                }
+            }
+        }
+    }
+
+    fn load_meshes(&mut self, model: &Model) -> Result<(), JsError> {
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            let position = VertexLayout::new().attribute("a_Position", 3, GL::FLOAT, false);
+            let normal = VertexLayout::new().attribute("a_Normal", 3, GL::FLOAT, false);
+            let tex_coord = VertexLayout::new().attribute("a_TexCoord", 2, GL::FLOAT, false);
+
+            let mut meshes = Vec::with_capacity(model.meshes.len());
+            for primitives in &model.meshes {
+                let mut mesh = Vec::with_capacity(primitives.len());
+                for primitive in primitives {
+                    let mut vao = VertexArray::new(gl)?
+                        .buffer(program, &position, &primitive.positions)?
+                        .buffer(program, &normal, &primitive.normals)?
+                        .buffer(program, &tex_coord, &primitive.tex_coords)?;
+                    let count = match primitive.indices.as_ref() {
+                        Some(indices) => {
+                            vao = vao.indices(indices)?;
+                            indices.len()
+                        }
+                        None => primitive.positions.len() / 3,
+                    };
+                    mesh.push(MeshPrimitive {
+                        vao,
+                        mode: primitive.mode,
+                        count: count as i32,
+                        indexed: primitive.indices.is_some(),
+                        material: primitive.material,
+                    });
+                }
+                meshes.push(mesh);
+            }
+            self.meshes = meshes;
+        }
+        Ok(())
+    }
+
+    /// Create the textures sampling the image `index` of the model
+    fn load_textures(&mut self, index: usize, image: &HtmlImageElement) -> Result<(), JsValue> {
+        if let (Some(gl), Some(model)) = (self.gl.as_ref(), self.model.as_ref()) {
+            for (texture, desc) in self.textures.iter_mut().zip(&model.textures) {
+                if desc.image == index {
+                    // glTF texture coordinates start at the top-left corner,
+                    // like the image, so it is not flipped
+                    *texture = Some(
+                        Texture::builder()
+                            .wrap(desc.wrap_s, desc.wrap_t)
+                            .filter(desc.min_filter, desc.mag_filter)
+                            .build(gl, image)?,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The view projection matrix of the first camera of the scene, or of a
+    /// camera looking at the whole scene when it has none
+    fn view_proj(&self, model: &Model, aspect: f32) -> na::Matrix4<f32> {
+        let mut camera = None;
+        let _ =
+            model
+                .scene
+                .traverse::<(), _>(&mut MatrixStack::default(), &mut |content, matrix| {
+                    if let (None, Some(index)) = (camera, content.camera) {
+                        camera = Some((model.cameras[index], *matrix));
+                    }
+                    Ok(())
+                });
+        if let Some((camera, matrix)) = camera {
+            if let Some(view) = matrix.try_inverse() {
+                return camera.projection(aspect) * view;
+            }
+        }
+
+        let (min, max) = model
+            .bounds()
+            .unwrap_or((na::Point3::origin(), na::Point3::origin()));
+        let center = na::center(&min, &max);
+        let radius = (na::distance(&min, &max) / 2.0).max(0.1);
+        let eye = center + na::Vector3::new(0.0, radius, radius * 2.5);
+        na::Matrix4::new_perspective(aspect, 45f32.to_radians(), radius * 0.1, radius * 10.0)
+            * na::Matrix4::look_at_rh(&eye, &center, &na::Vector3::y())
+    }
+
+    fn draw(&self, gl: &GL, program: &ShaderProgram, model: &Model) -> Result<(), JsError> {
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        let view_proj = self.view_proj(model, aspect);
+
+        // Turn the scene around the vertical axis through its center
+        let center = model
+            .bounds()
+            .map_or(na::Point3::origin(), |(min, max)| na::center(&min, &max));
+        let rotation = na::Matrix4::new_translation(&center.coords)
+            * na::Matrix4::from_axis_angle(&na::Vector3::y_axis(), self.angle.to_radians())
+            * na::Matrix4::new_translation(&-center.coords);
+
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+        model.scene.traverse(
+            &mut MatrixStack::new(rotation),
+            &mut |content, model_matrix| {
+                let primitives = content.mesh.and_then(|mesh| self.meshes.get(mesh));
+                for primitive in primitives.into_iter().flatten() {
+                    let normal_matrix = model_matrix
+                        .try_inverse()
+                        .ok_or_else(|| JsError::new("The model matrix is not invertible"))?
+                        .transpose();
+                    program.set_uniform("u_NormalMatrix", &normal_matrix)?;
+                    program.set_uniform("u_MvpMatrix", &(view_proj * model_matrix))?;
+
+                    let material = primitive
+                        .material
+                        .and_then(|material| model.materials.get(material))
+                        .cloned()
+                        .unwrap_or_default();
+                    // Until its image arrives, a texture is left out
+                    let texture = material
+                        .base_color_texture
+                        .and_then(|texture| self.textures.get(texture))
+                        .and_then(Option::as_ref);
+                    program.set_uniform("u_BaseColorFactor", &material.base_color_factor)?;
+                    program.set_uniform("u_UseTexture", &texture.is_some())?;
+                    if let Some(texture) = texture {
+                        texture.bind();
+                    }
+
+                    primitive.vao.bind();
+                    if primitive.indexed {
+                        gl.checked_draw_elements(
+                            primitive.mode,
+                            primitive.count,
+                            GL::UNSIGNED_INT,
+                            0,
+                        );
+                    } else {
+                        gl.checked_draw_arrays(primitive.mode, 0, primitive.count);
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Fetch and decode the `.gltf` or `.glb` file at `url` and the buffers it
+/// references
+async fn load_model(url: &str) -> Result<Model, String> {
+    let bytes = fetch_bytes(url).await.map_err(|err| err.to_string())?;
+    let (document, bin) = Document::parse(&bytes).map_err(|err| format!("{}, {}", url, err))?;
+
+    let base = &url[..url.rfind('/').map_or(0, |i| i + 1)];
+    let mut buffers = Vec::with_capacity(document.buffers.len());
+    for index in 0..document.buffers.len() {
+        let source = document
+            .buffer_source(index)
+            .map_err(|err| format!("{}, {}", url, err))?;
+        let buffer = match source {
+            BufferSource::Glb => bin
+                .clone()
+                .ok_or_else(|| format!("{}, buffer {} has no binary chunk", url, index))?,
+            BufferSource::Data(data) => data,
+            BufferSource::Uri(uri) => fetch_bytes(&format!("{}{}", base, uri))
+                .await
+                .map_err(|err| err.to_string())?,
+        };
+        buffers.push(buffer);
+    }
+    Model::new(&document, &buffers).map_err(|err| format!("{}, {}", url, err))
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            animation: None,
+            selected: 0,
+            model: None,
+            meshes: Vec::new(),
+            textures: Vec::new(),
+            error: None,
+            angle: 0.0,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                self.request_model(ctx.link().clone());
+                false
+            }
+            Message::Select(selected) => {
+                if selected == self.selected {
+                    return false;
+                }
+                self.selected = selected;
+                self.model = None;
+                self.meshes.clear();
+                self.textures.clear();
+                self.error = None;
+                self.request_model(ctx.link().clone());
+                true
+            }
+            // Results of an asset that is no longer selected are dropped
+            Message::Loaded(selected, _)
+            | Message::ImageLoaded(selected, _, _)
+            | Message::LoadFailed(selected, _)
+                if selected != self.selected =>
+            {
+                false
+            }
+            Message::Loaded(_, model) => {
+                if let Err(error) = self.load_meshes(&model) {
+                    self.error = Some(error_message(error));
+                    return true;
+                }
+                self.request_images(&model, ctx.link().clone());
+                self.textures = vec![None; model.textures.len()];
+                self.model = Some(*model);
+                if self.animation.is_none() {
+                    let link = ctx.link().clone();
+                    self.animation = Some(AnimationLoop::new(move |frame| {
+                        link.send_message(Message::Animate(frame))
+                    }));
+                }
+                true
+            }
+            Message::ImageLoaded(_, index, image) => {
+                if let Err(error) = self.load_textures(index, &image) {
+                    self.error = Some(error_message(error));
+                    return true;
+                }
+                false
+            }
+            Message::LoadFailed(_, error) => {
+                self.error = Some(error);
+                true
+            }
+            Message::Animate(frame) => {
+                self.angle = (self.angle + ANGLE_STEP * frame.delta as f32 / 1000.0) % 360.0;
+                if let (Some(gl), Some(program), Some(model)) =
+                    (self.gl.as_ref(), self.program.as_ref(), self.model.as_ref())
+                {
+                    self.draw(gl, program, model).unwrap_throw();
+                }
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        let buttons = ASSETS.iter().enumerate().map(|(index, url)| {
+            let name = &url[url.rfind('/').map_or(0, |i| i + 1)..];
+            yew::html! {
+                <button
+                    onclick={ctx.link().callback(move |_| Message::Select(index))}
+                    disabled={index == self.selected}
+                >
+                    { name }
+                </button>
+            }
+        });
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                <p>{ for buttons }</p>
+                if let Some(error) = self.error.as_ref() {
+                    <p class="wasmgl-error">{ error }</p>
+                }
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+            self.request_model(ctx.link().clone());
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // cancelAnimationFrame
+        self.animation = None;
+    }
+}
@@ -0,0 +1,286 @@
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    fetch_text, webgl2_context, AnimationLoop, Frame, Material, ObjModel, ShaderProgram,
+    VertexArray, WebGl2RenderingContextExt,
+};
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Normal;
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_ModelMatrix;
+uniform mat4 u_NormalMatrix;
+varying vec3 v_Normal;
+varying vec3 v_Position;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_Position = vec3(u_ModelMatrix * a_Position);
+    v_Normal = vec3(u_NormalMatrix * a_Normal);
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+uniform vec3 u_LightDirection; // Direction to the light, normalized
+uniform vec3 u_EyePosition;
+uniform vec3 u_Ambient;        // Material colors
+uniform vec3 u_Diffuse;
+uniform vec3 u_Specular;
+uniform float u_Shininess;
+uniform float u_Opacity;
+varying vec3 v_Normal;
+varying vec3 v_Position;
+void main() {
+    vec3 normal = normalize(v_Normal);
+    float nDotL = max(dot(u_LightDirection, normal), 0.0);
+    // Blinn-Phong highlight
+    vec3 halfway = normalize(u_LightDirection + normalize(u_EyePosition - v_Position));
+    float specular = nDotL > 0.0 ? pow(max(dot(normal, halfway), 0.0), u_Shininess) : 0.0;
+    vec3 color = u_Ambient + u_Diffuse * nDotL + u_Specular * specular;
+    gl_FragColor = vec4(color, u_Opacity);
+}
+";
+
+// The model to show, its MTL files are looked up next to it
+const MODEL_URL: &str = "/resources/cube.obj";
+
+const EYE_POSITION: [f32; 3] = [0.0, 4.0, 8.0];
+
+// Rotation angle (degrees/second)
+const ANGLE_STEP: f32 = 30.0;
+
+pub enum Message {
+    Loaded(ObjModel, Vec<Material>),
+    LoadFailed(String),
+    Animate(Frame),
+    ContextRestored,
+}
+
+/// Draw calls of the model, one per group
+struct Mesh {
+    vao: VertexArray,
+    groups: Vec<(usize, usize, Material)>,
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    mesh: Option<Mesh>,
+    animation: Option<AnimationLoop>,
+    // Vertex and triangle counts shown below the canvas
+    stats: Option<(usize, usize)>,
+    error: Option<String>,
+    // The rotation angle of the model (degrees)
+    angle: f32,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+        let [x, y, z] = EYE_POSITION;
+        program.set_uniform("u_EyePosition", &[x, y, z])?;
+        program.set_uniform(
+            "u_LightDirection",
+            &na::Vector3::new(-0.35f32, 0.35, 0.87).normalize(),
+        )?;
+
+        // Set the clear color, enable the depth test and blending
+        gl.clear_color(0.2, 0.2, 0.2, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        Ok(())
+    }
+
+    fn request_model(&self, link: yew::html::Scope<Self>) {
+        link.send_future(async {
+            match load_model(MODEL_URL).await {
+                Ok((model, materials)) => Message::Loaded(model, materials),
+                Err(err) => Message::LoadFailed(err),
+            }
+        });
+    }
+
+    fn load_mesh(
+        &mut self,
+        model: &ObjModel,
+        materials: &[Material],
+        link: yew::html::Scope<Self>,
+    ) -> Result<(), JsError> {
+        if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+            let vao = VertexArray::new(gl)?
+                .buffer(program, &ObjModel::layout(), &model.vertices)?
+                .indices(&model.indices)?;
+            let groups = model
+                .groups
+                .iter()
+                .map(|group| {
+                    // Groups without a known material are drawn grey
+                    let material = group
+                        .material
+                        .as_ref()
+                        .and_then(|name| materials.iter().find(|m| &m.name == name))
+                        .cloned()
+                        .unwrap_or_default();
+                    (group.first, group.count, material)
+                })
+                .collect();
+
+            self.mesh = Some(Mesh { vao, groups });
+            self.stats = Some((model.vertices.len() / 8, model.indices.len() / 3));
+            self.animation = Some(AnimationLoop::new(move |frame| {
+                link.send_message(Message::Animate(frame))
+            }));
+        }
+        Ok(())
+    }
+
+    fn draw(&self, gl: &GL, program: &ShaderProgram, mesh: &Mesh) -> Result<(), JsError> {
+        let radian = self.angle.to_radians();
+        let model_matrix = na::Matrix4::new_rotation(na::Vector3::new(radian, radian, 0.0));
+        let normal_matrix = model_matrix
+            .try_inverse()
+            .ok_or_else(|| JsError::new("The model matrix is not invertible"))?
+            .transpose();
+
+        let [x, y, z] = EYE_POSITION;
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        let view_proj = na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0)
+            * na::Matrix4::look_at_rh(
+                &na::Point3::new(x, y, z),
+                &na::Point3::origin(),
+                &na::Vector3::y(),
+            );
+
+        program.set_uniform("u_ModelMatrix", &model_matrix)?;
+        program.set_uniform("u_NormalMatrix", &normal_matrix)?;
+        program.set_uniform("u_MvpMatrix", &(view_proj * model_matrix))?;
+
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+        mesh.vao.bind();
+        for (first, count, material) in &mesh.groups {
+            program.set_uniform("u_Ambient", &material.ambient)?;
+            program.set_uniform("u_Diffuse", &material.diffuse)?;
+            program.set_uniform("u_Specular", &material.specular)?;
+            program.set_uniform("u_Shininess", &material.shininess)?;
+            program.set_uniform("u_Opacity", &material.opacity)?;
+            // The offset is in bytes, indices are 32-bit
+            gl.checked_draw_elements(
+                GL::TRIANGLES,
+                *count as i32,
+                GL::UNSIGNED_INT,
+                (*first * 4) as i32,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Fetch and parse the OBJ file at `url` and the MTL files it names
+async fn load_model(url: &str) -> Result<(ObjModel, Vec<Material>), String> {
+    let source = fetch_text(url).await.map_err(|err| err.to_string())?;
+    let model = ObjModel::parse(&source).map_err(|err| format!("{}, {}", url, err))?;
+
+    let base = &url[..url.rfind('/').map_or(0, |i| i + 1)];
+    let mut materials = Vec::new();
+    for library in &model.material_libraries {
+        let url = format!("{}{}", base, library);
+        let source = fetch_text(&url).await.map_err(|err| err.to_string())?;
+        materials
+            .extend(Material::parse_library(&source).map_err(|err| format!("{}, {}", url, err))?);
+    }
+    Ok((model, materials))
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            mesh: None,
+            animation: None,
+            stats: None,
+            error: None,
+            angle: 0.0,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                self.request_model(ctx.link().clone());
+                false
+            }
+            Message::Loaded(model, materials) => {
+                self.load_mesh(&model, &materials, ctx.link().clone())
+                    .unwrap_throw();
+                true
+            }
+            Message::LoadFailed(error) => {
+                self.error = Some(error);
+                true
+            }
+            Message::Animate(frame) => {
+                self.angle = (self.angle + ANGLE_STEP * frame.delta as f32 / 1000.0) % 360.0;
+                if let (Some(gl), Some(program), Some(mesh)) =
+                    (self.gl.as_ref(), self.program.as_ref(), self.mesh.as_ref())
+                {
+                    self.draw(gl, program, mesh).unwrap_throw();
+                }
+                false
+            }
+        }
+    }
+
+    fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                if let Some((vertices, triangles)) = self.stats {
+                    <p>{ format!("{}: {} vertices, {} triangles", MODEL_URL, vertices, triangles) }</p>
+                }
+                if let Some(error) = self.error.as_ref() {
+                    <p class="wasmgl-error">{ error }</p>
+                }
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+            self.request_model(ctx.link().clone());
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // cancelAnimationFrame
+        self.animation = None;
+    }
+}
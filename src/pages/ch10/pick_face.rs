@@ -0,0 +1,218 @@
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, MouseEvent, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    canvas_position, webgl2_context, AnimationLoop, Frame, Picker, ShaderProgram, VertexLayout,
+    WebGl2RenderingContextExt,
+};
+
+use super::cube;
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+attribute float a_Face;   // Surface number (Cannot use int for attribute variable)
+uniform mat4 u_MvpMatrix;
+uniform float u_PickedFace; // Surface number of the selected face
+varying vec4 v_Color;
+varying float v_Face;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    // The selected face is drawn in white
+    v_Color = a_Face == u_PickedFace ? vec4(1.0) : a_Color;
+    v_Face = a_Face;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+#include \"picking/id_color.glsl\"
+uniform bool u_Picking; // Draw the ID color for picking
+varying vec4 v_Color;
+varying float v_Face;
+void main() {
+    // The face numbers are the IDs
+    gl_FragColor = u_Picking ? idColor(v_Face) : v_Color;
+}
+";
+
+// Rotation angle (degrees/second)
+const ANGLE_STEP: f32 = 20.0;
+
+pub enum Message {
+    Animate(Frame),
+    Click(f32, f32),
+    ContextRestored,
+}
+
+fn on_click(event: MouseEvent) -> Message {
+    let (x, y) = canvas_position(&event);
+    Message::Click(x, y)
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    picker: Option<Picker>,
+    animation: Option<AnimationLoop>,
+    view_proj_matrix: na::Matrix4<f32>,
+    // The rotation angle of the cube (degrees)
+    angle: f32,
+    picked_face: Option<u32>,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+        cube::mesh()
+            .vertex_array(&gl, &program)?
+            .buffer(
+                &program,
+                &VertexLayout::new().attribute("a_Color", 3, GL::FLOAT, false),
+                cube::COLORS,
+            )?
+            .buffer(
+                &program,
+                &VertexLayout::new().attribute("a_Face", 1, GL::UNSIGNED_BYTE, false),
+                cube::FACES,
+            )?;
+
+        let picker = Picker::new(&gl)?;
+
+        // Set the clear color and enable the depth test
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+
+        // Calculate the view projection matrix
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        self.view_proj_matrix =
+            na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0)
+                * na::Matrix4::look_at_rh(
+                    &na::Point3::new(0.0, 0.0, 7.0),
+                    &na::Point3::new(0.0, 0.0, 0.0),
+                    &na::Vector3::new(0.0, 1.0, 0.0),
+                );
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        self.picker = Some(picker);
+        self.animation = Some(AnimationLoop::new(move |frame| {
+            link.send_message(Message::Animate(frame))
+        }));
+        Ok(())
+    }
+
+    fn draw(&self, gl: &GL, program: &ShaderProgram, picking: bool) -> Result<(), JsError> {
+        // Rotate the cube around the x, y and z axes
+        let radian = self.angle.to_radians();
+        let model_matrix = na::Matrix4::new_rotation(na::Vector3::x() * radian)
+            * na::Matrix4::new_rotation(na::Vector3::y() * radian)
+            * na::Matrix4::new_rotation(na::Vector3::z() * radian);
+        program.set_uniform("u_MvpMatrix", &(self.view_proj_matrix * model_matrix))?;
+        program.set_uniform("u_Picking", &picking)?;
+        // No face has the number 0
+        let picked_face = self.picked_face.map(|face| face as f32).unwrap_or(0.0);
+        program.set_uniform("u_PickedFace", &picked_face)?;
+
+        // Clear color and depth buffer
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        // Draw the cube
+        gl.checked_draw_elements(GL::TRIANGLES, cube::INDEX_COUNT, GL::UNSIGNED_INT, 0);
+        Ok(())
+    }
+
+    fn pick(&self, x: f32, y: f32) -> Result<Option<u32>, JsValue> {
+        match (
+            self.gl.as_ref(),
+            self.program.as_ref(),
+            self.picker.as_ref(),
+        ) {
+            (Some(gl), Some(program), Some(picker)) => {
+                picker.pick(x, y, || self.draw(gl, program, true))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            picker: None,
+            animation: None,
+            view_proj_matrix: na::Matrix4::identity(),
+            angle: 0.0,
+            picked_face: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
+            Message::Animate(frame) => {
+                self.angle = (self.angle + ANGLE_STEP * frame.delta as f32 / 1000.0) % 360.0;
+                if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+                    self.draw(gl, program, false).unwrap_throw();
+                }
+                false
+            }
+            Message::Click(x, y) => {
+                self.picked_face = self.pick(x, y).unwrap_throw();
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        let onclick = ctx.link().callback(on_click);
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    {onclick}
+                    width="400"
+                    height="400"
+                />
+                <p>{
+                    match self.picked_face {
+                        Some(face) => format!("Face {} was selected.", face),
+                        None => "Click on a face of the cube.".to_string(),
+                    }
+                }</p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // cancelAnimationFrame
+        self.animation = None;
+    }
+}
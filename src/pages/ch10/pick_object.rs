@@ -0,0 +1,202 @@
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, MouseEvent, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    canvas_position, webgl2_context, AnimationLoop, Frame, Picker, ShaderProgram, VertexLayout,
+    WebGl2RenderingContextExt,
+};
+
+use super::cube;
+
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_MvpMatrix;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+#include \"picking/id_color.glsl\"
+uniform bool u_Picking; // Draw the ID color for picking
+uniform float u_Id;     // ID of the object
+varying vec4 v_Color;
+void main() {
+    gl_FragColor = u_Picking ? idColor(u_Id) : v_Color;
+}
+";
+
+// Rotation angle (degrees/second)
+const ANGLE_STEP: f32 = 20.0;
+
+// The ID of the cube, the only object of the scene
+const CUBE_ID: u32 = 0;
+
+pub enum Message {
+    Animate(Frame),
+    Click(f32, f32),
+    ContextRestored,
+}
+
+fn on_click(event: MouseEvent) -> Message {
+    let (x, y) = canvas_position(&event);
+    Message::Click(x, y)
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    program: Option<ShaderProgram>,
+    picker: Option<Picker>,
+    animation: Option<AnimationLoop>,
+    view_proj_matrix: na::Matrix4<f32>,
+    // The rotation angle of the cube (degrees)
+    angle: f32,
+    selected: bool,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        let program = gl.init_shaders(VSHADER_SOURCE, FSHADER_SOURCE)?;
+
+        cube::mesh().vertex_array(&gl, &program)?.buffer(
+            &program,
+            &VertexLayout::new().attribute("a_Color", 3, GL::FLOAT, false),
+            cube::COLORS,
+        )?;
+
+        let picker = Picker::new(&gl)?;
+
+        // Set the clear color and enable the depth test
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+
+        // Calculate the view projection matrix
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        self.view_proj_matrix =
+            na::Matrix4::new_perspective(aspect, 30f32.to_radians(), 1.0, 100.0)
+                * na::Matrix4::look_at_rh(
+                    &na::Point3::new(0.0, 0.0, 7.0),
+                    &na::Point3::new(0.0, 0.0, 0.0),
+                    &na::Vector3::new(0.0, 1.0, 0.0),
+                );
+
+        self.gl = Some(gl);
+        self.program = Some(program);
+        self.picker = Some(picker);
+        self.animation = Some(AnimationLoop::new(move |frame| {
+            link.send_message(Message::Animate(frame))
+        }));
+        Ok(())
+    }
+
+    fn draw(&self, gl: &GL, program: &ShaderProgram, picking: bool) -> Result<(), JsError> {
+        // Rotate the cube around the x, y and z axes
+        let radian = self.angle.to_radians();
+        let model_matrix = na::Matrix4::new_rotation(na::Vector3::x() * radian)
+            * na::Matrix4::new_rotation(na::Vector3::y() * radian)
+            * na::Matrix4::new_rotation(na::Vector3::z() * radian);
+        program.set_uniform("u_MvpMatrix", &(self.view_proj_matrix * model_matrix))?;
+        program.set_uniform("u_Picking", &picking)?;
+        program.set_uniform("u_Id", &(CUBE_ID as f32))?;
+
+        // Clear color and depth buffer
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        // Draw the cube
+        gl.checked_draw_elements(GL::TRIANGLES, cube::INDEX_COUNT, GL::UNSIGNED_INT, 0);
+        Ok(())
+    }
+
+    fn pick(&self, x: f32, y: f32) -> Result<bool, JsValue> {
+        match (
+            self.gl.as_ref(),
+            self.program.as_ref(),
+            self.picker.as_ref(),
+        ) {
+            (Some(gl), Some(program), Some(picker)) => {
+                let id = picker.pick(x, y, || self.draw(gl, program, true))?;
+                Ok(id == Some(CUBE_ID))
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            program: None,
+            picker: None,
+            animation: None,
+            view_proj_matrix: na::Matrix4::identity(),
+            angle: 0.0,
+            selected: false,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
+            Message::Animate(frame) => {
+                self.angle = (self.angle + ANGLE_STEP * frame.delta as f32 / 1000.0) % 360.0;
+                if let (Some(gl), Some(program)) = (self.gl.as_ref(), self.program.as_ref()) {
+                    self.draw(gl, program, false).unwrap_throw();
+                }
+                false
+            }
+            Message::Click(x, y) => {
+                self.selected = self.pick(x, y).unwrap_throw();
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
+        let onclick = ctx.link().callback(on_click);
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    {onclick}
+                    width="400"
+                    height="400"
+                />
+                <p>{ if self.selected { "The cube was selected!" } else { "Click on the cube." } }</p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // cancelAnimationFrame
+        self.animation = None;
+    }
+}
@@ -0,0 +1,329 @@
+use nalgebra as na;
+use wasm_bindgen::{JsError, JsValue, UnwrapThrowExt};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL};
+use yew::NodeRef;
+
+use crate::utils::{
+    webgl2_context, AnimationLoop, Frame, ShaderProgram, ShadowFormat, ShadowMap, VertexArray,
+    VertexLayout, WebGl2RenderingContextExt,
+};
+
+// Shaders generating the shadow map
+const SHADOW_VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+uniform mat4 u_MvpMatrix;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+}
+";
+
+// Shaders of the regular drawing, `shadowDepth()` depending on the format of
+// the shadow map
+const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+attribute vec4 a_Color;
+uniform mat4 u_MvpMatrix;
+uniform mat4 u_MvpMatrixFromLight;
+varying highp vec4 v_PositionFromLight;
+varying vec4 v_Color;
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+    v_PositionFromLight = u_MvpMatrixFromLight * a_Position;
+    v_Color = a_Color;
+}
+";
+
+const FSHADER_SOURCE: &str = "
+precision mediump float;
+#include \"shadow/depth.glsl\"
+uniform highp sampler2D u_ShadowMap;
+varying highp vec4 v_PositionFromLight;
+varying vec4 v_Color;
+void main() {
+    highp vec3 shadowCoord = (v_PositionFromLight.xyz / v_PositionFromLight.w) / 2.0 + 0.5;
+    highp float depth = shadowDepth(u_ShadowMap, shadowCoord.xy);
+    float visibility = (shadowCoord.z > depth + 0.0015) ? 0.7 : 1.0;
+    gl_FragColor = vec4(v_Color.rgb * visibility, v_Color.a);
+}
+";
+
+// Size of the shadow map
+const SHADOW_MAP_SIZE: i32 = 2048;
+
+// Position of the light source
+const LIGHT_POSITION: [f32; 3] = [0.0, 7.0, 2.0];
+
+// Rotation angle (degrees/second)
+const ANGLE_STEP: f32 = 40.0;
+
+const TRIANGLE_POSITIONS: &[f32] = &[-0.8, 3.5, 0.0, 0.8, 3.5, 0.0, 0.0, 3.5, 1.8];
+const TRIANGLE_COLORS: &[f32] = &[1.0, 0.5, 0.0, 1.0, 0.5, 0.0, 1.0, 0.0, 0.0];
+const TRIANGLE_INDICES: &[u8] = &[0, 1, 2];
+
+const PLANE_POSITIONS: &[f32] = &[
+    3.0, -1.7, 2.5, -3.0, -1.7, 2.5, -3.0, -1.7, -2.5, 3.0, -1.7, -2.5,
+];
+const PLANE_COLORS: &[f32] = &[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+const PLANE_INDICES: &[u8] = &[0, 1, 2, 0, 2, 3];
+
+#[derive(Debug, Clone, PartialEq, yew::Properties)]
+pub struct Props {
+    /// How the shadow map is stored when the page opens
+    #[prop_or(ShadowFormat::DepthTexture)]
+    pub format: ShadowFormat,
+}
+
+pub enum Message {
+    Animate(Frame),
+    ToggleFormat,
+    ContextRestored,
+}
+
+/// A mesh with a vertex array object for each pass
+struct Mesh {
+    shadow_vao: VertexArray,
+    vao: VertexArray,
+    count: i32,
+}
+
+impl Mesh {
+    fn new(
+        gl: &GL,
+        shadow_program: &ShaderProgram,
+        program: &ShaderProgram,
+        positions: &[f32],
+        colors: &[f32],
+        indices: &[u8],
+    ) -> Result<Self, JsError> {
+        let position_layout = VertexLayout::new().attribute("a_Position", 3, GL::FLOAT, false);
+        let shadow_vao = VertexArray::new(gl)?
+            .buffer(shadow_program, &position_layout, positions)?
+            .indices(indices)?;
+        let vao = VertexArray::new(gl)?
+            .buffer(program, &position_layout, positions)?
+            .buffer(
+                program,
+                &VertexLayout::new().attribute("a_Color", 3, GL::FLOAT, false),
+                colors,
+            )?
+            .indices(indices)?;
+        Ok(Self {
+            shadow_vao,
+            vao,
+            count: indices.len() as i32,
+        })
+    }
+
+    fn draw(&self, gl: &GL) {
+        gl.checked_draw_elements(GL::TRIANGLES, self.count, GL::UNSIGNED_BYTE, 0);
+    }
+}
+
+struct Scene {
+    shadow_program: ShaderProgram,
+    program: ShaderProgram,
+    triangle: Mesh,
+    plane: Mesh,
+    shadow_map: ShadowMap,
+}
+
+impl Scene {
+    fn new(gl: &GL, format: ShadowFormat) -> Result<Self, JsValue> {
+        let shadow_program = gl.init_shaders(SHADOW_VSHADER_SOURCE, format.fragment_shader())?;
+        let program =
+            gl.init_shaders_with(&format.preprocessor(), VSHADER_SOURCE, FSHADER_SOURCE)?;
+        // The shadow map is bound to the texture unit 0
+        program.set_uniform("u_ShadowMap", &0)?;
+
+        let triangle = Mesh::new(
+            gl,
+            &shadow_program,
+            &program,
+            TRIANGLE_POSITIONS,
+            TRIANGLE_COLORS,
+            TRIANGLE_INDICES,
+        )?;
+        let plane = Mesh::new(
+            gl,
+            &shadow_program,
+            &program,
+            PLANE_POSITIONS,
+            PLANE_COLORS,
+            PLANE_INDICES,
+        )?;
+
+        let mut shadow_map = ShadowMap::new(gl, SHADOW_MAP_SIZE, format)?;
+        let [x, y, z] = LIGHT_POSITION;
+        shadow_map.look_at(
+            &na::Point3::new(x, y, z),
+            &na::Point3::origin(),
+            70f32.to_radians(),
+            1.0,
+            100.0,
+        );
+
+        Ok(Self {
+            shadow_program,
+            program,
+            triangle,
+            plane,
+            shadow_map,
+        })
+    }
+
+    fn draw(&self, gl: &GL, angle: f32) -> Result<(), JsValue> {
+        let triangle_model = na::Matrix4::new_rotation(na::Vector3::y() * angle.to_radians());
+        let plane_model = na::Matrix4::from_axis_angle(
+            &na::Unit::new_normalize(na::Vector3::new(0.0, 1.0, 1.0)),
+            -45f32.to_radians(),
+        );
+        let models = [(&self.triangle, triangle_model), (&self.plane, plane_model)];
+
+        // Draw the depth of the scene as seen from the light
+        self.shadow_map.render(|light_view_proj| {
+            self.shadow_program.use_program();
+            for (mesh, model) in &models {
+                self.shadow_program
+                    .set_uniform("u_MvpMatrix", &(light_view_proj * model))?;
+                mesh.shadow_vao.bind();
+                mesh.draw(gl);
+            }
+            Ok(())
+        })?;
+
+        // Draw the scene from the eye, comparing with the shadow map
+        let aspect = gl.drawing_buffer_width() as f32 / gl.drawing_buffer_height() as f32;
+        let view_proj = na::Matrix4::new_perspective(aspect, 45f32.to_radians(), 1.0, 100.0)
+            * na::Matrix4::look_at_rh(
+                &na::Point3::new(0.0, 7.0, 9.0),
+                &na::Point3::origin(),
+                &na::Vector3::y(),
+            );
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+        self.program.use_program();
+        self.shadow_map.bind_texture(0);
+        for (mesh, model) in &models {
+            self.program
+                .set_uniform("u_MvpMatrix", &(view_proj * model))?;
+            self.program.set_uniform(
+                "u_MvpMatrixFromLight",
+                &(self.shadow_map.light_view_proj() * model),
+            )?;
+            mesh.vao.bind();
+            mesh.draw(gl);
+        }
+        // Do not leave the shadow map bound while drawing into it
+        gl.bind_texture(GL::TEXTURE_2D, None);
+
+        Ok(())
+    }
+}
+
+pub struct Page {
+    gl: Option<GL>,
+    canvas: NodeRef,
+    format: ShadowFormat,
+    scene: Option<Scene>,
+    animation: Option<AnimationLoop>,
+    onclick_format: yew::Callback<web_sys::MouseEvent>,
+    // The rotation angle of the triangle (degrees)
+    angle: f32,
+}
+
+impl Page {
+    fn get_canvas(&self) -> Option<HtmlCanvasElement> {
+        self.canvas.cast::<HtmlCanvasElement>()
+    }
+
+    fn setup_gl(&mut self, link: yew::html::Scope<Self>) -> Result<(), JsValue> {
+        let canvas = self.get_canvas().unwrap();
+
+        let gl = webgl2_context(&canvas, link.callback(|_| Message::ContextRestored));
+
+        self.scene = Some(Scene::new(&gl, self.format)?);
+
+        // Set the clear color and enable the depth test
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.enable(GL::DEPTH_TEST);
+
+        self.gl = Some(gl);
+        self.animation = Some(AnimationLoop::new(move |frame| {
+            link.send_message(Message::Animate(frame))
+        }));
+        Ok(())
+    }
+}
+
+impl yew::Component for Page {
+    type Message = Message;
+    type Properties = Props;
+
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        Self {
+            gl: None,
+            canvas: NodeRef::default(),
+            format: ctx.props().format,
+            scene: None,
+            animation: None,
+            onclick_format: ctx.link().callback(|_| Message::ToggleFormat),
+            angle: 0.0,
+        }
+    }
+
+    fn update(&mut self, ctx: &yew::Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::ContextRestored => {
+                self.setup_gl(ctx.link().clone()).unwrap_throw();
+                false
+            }
+            Message::Animate(frame) => {
+                self.angle = (self.angle + ANGLE_STEP * frame.delta as f32 / 1000.0) % 360.0;
+                if let (Some(gl), Some(scene)) = (self.gl.as_ref(), self.scene.as_ref()) {
+                    scene.draw(gl, self.angle).unwrap_throw();
+                }
+                false
+            }
+            Message::ToggleFormat => {
+                self.format = match self.format {
+                    ShadowFormat::DepthTexture => ShadowFormat::PackedRgba,
+                    ShadowFormat::PackedRgba => ShadowFormat::DepthTexture,
+                };
+                if let Some(gl) = self.gl.as_ref() {
+                    self.scene = Some(Scene::new(gl, self.format).unwrap_throw());
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self, _ctx: &yew::Context<Self>) -> yew::Html {
+        let format = match self.format {
+            ShadowFormat::DepthTexture => "depth texture",
+            ShadowFormat::PackedRgba => "RGBA-packed depth",
+        };
+        yew::html! {
+            <div>
+                <canvas
+                    ref={self.canvas.clone()}
+                    width="400"
+                    height="400"
+                />
+                <p>
+                    { format!("Shadow map: {} ", format) }
+                    <button onclick={self.onclick_format.clone()}>{ "SWITCH" }</button>
+                </p>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &yew::Context<Self>, first_render: bool) {
+        if first_render {
+            self.setup_gl(ctx.link().clone()).unwrap_throw();
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &yew::Context<Self>) {
+        // cancelAnimationFrame
+        self.animation = None;
+    }
+}
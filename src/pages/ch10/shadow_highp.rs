@@ -0,0 +1,10 @@
+use crate::utils::ShadowFormat;
+
+/// The Shadow example with the book's high precision shadow map, the depth
+/// packed into an RGBA texture
+#[yew::function_component(Page)]
+pub fn page() -> yew::Html {
+    yew::html! {
+        <super::shadow::Page format={ShadowFormat::PackedRgba} />
+    }
+}
@@ -3,6 +3,9 @@ mod ch03;
 mod ch04;
 mod ch05;
 mod ch07;
+mod ch08;
+mod ch09;
+mod ch10;
 mod page;
 
 pub use self::page::{render_page, Page};
@@ -63,6 +63,56 @@ pub enum Page {
     LookAtRotatedTriangles,
     #[at("/ch07/look_at_triangles_with_keys")]
     LookAtTrianglesWithKeys,
+    #[at("/ch07/ortho_view")]
+    OrthoView,
+    #[at("/ch07/perspective_view")]
+    PerspectiveView,
+    #[at("/ch07/perspective_view_mvp")]
+    PerspectiveView_mvp,
+    #[at("/ch07/depth_buffer")]
+    DepthBuffer,
+    #[at("/ch07/zfighting")]
+    Zfighting,
+    #[at("/ch07/hello_cube")]
+    HelloCube,
+    #[at("/ch07/colored_cube")]
+    ColoredCube,
+    #[at("/ch08/lighted_cube")]
+    LightedCube,
+    #[at("/ch08/lighted_cube_ambient")]
+    LightedCube_ambient,
+    #[at("/ch08/lighted_translated_rotated_cube")]
+    LightedTranslatedRotatedCube,
+    #[at("/ch08/point_lighted_cube")]
+    PointLightedCube,
+    #[at("/ch08/point_lighted_cube_per_fragment")]
+    PointLightedCube_perFragment,
+    #[at("/ch08/point_lighted_sphere")]
+    PointLightedSphere,
+    #[at("/ch08/point_lighted_sphere_per_fragment")]
+    PointLightedSphere_perFragment,
+    #[at("/ch08/primitives")]
+    Primitives,
+    #[at("/ch09/joint_model")]
+    JointModel,
+    #[at("/ch09/multi_joint_model")]
+    MultiJointModel,
+    #[at("/ch09/multi_joint_model_segment")]
+    MultiJointModel_segment,
+    #[at("/ch10/pick_object")]
+    PickObject,
+    #[at("/ch10/pick_face")]
+    PickFace,
+    #[at("/ch10/framebuffer_object")]
+    FramebufferObject,
+    #[at("/ch10/shadow")]
+    Shadow,
+    #[at("/ch10/shadow_highp")]
+    Shadow_highp,
+    #[at("/ch10/obj_viewer")]
+    OBJViewer,
+    #[at("/ch10/gltf_viewer")]
+    GltfViewer,
 }
 
 pub fn render_page(page: Page) -> yew::Html {
@@ -105,5 +155,39 @@ pub fn render_page(page: Page) -> yew::Html {
         LookAtTriangles => yew::html! { <super::ch07::LookAtTriangles /> },
         LookAtRotatedTriangles => yew::html! { <super::ch07::LookAtRotatedTriangles /> },
         LookAtTrianglesWithKeys => yew::html! { <super::ch07::LookAtTrianglesWithKeys /> },
+        OrthoView => yew::html! { <super::ch07::OrthoView /> },
+        PerspectiveView => yew::html! { <super::ch07::PerspectiveView /> },
+        PerspectiveView_mvp => yew::html! { <super::ch07::PerspectiveView_mvp /> },
+        DepthBuffer => yew::html! { <super::ch07::DepthBuffer /> },
+        Zfighting => yew::html! { <super::ch07::Zfighting /> },
+        HelloCube => yew::html! { <super::ch07::HelloCube /> },
+        ColoredCube => yew::html! { <super::ch07::ColoredCube /> },
+
+        LightedCube => yew::html! { <super::ch08::LightedCube /> },
+        LightedCube_ambient => yew::html! { <super::ch08::LightedCube_ambient /> },
+        LightedTranslatedRotatedCube => {
+            yew::html! { <super::ch08::LightedTranslatedRotatedCube /> }
+        }
+        PointLightedCube => yew::html! { <super::ch08::PointLightedCube /> },
+        PointLightedCube_perFragment => {
+            yew::html! { <super::ch08::PointLightedCube_perFragment /> }
+        }
+        PointLightedSphere => yew::html! { <super::ch08::PointLightedSphere /> },
+        PointLightedSphere_perFragment => {
+            yew::html! { <super::ch08::PointLightedSphere_perFragment /> }
+        }
+        Primitives => yew::html! { <super::ch08::Primitives /> },
+
+        JointModel => yew::html! { <super::ch09::JointModel /> },
+        MultiJointModel => yew::html! { <super::ch09::MultiJointModel /> },
+        MultiJointModel_segment => yew::html! { <super::ch09::MultiJointModel_segment /> },
+
+        PickObject => yew::html! { <super::ch10::PickObject /> },
+        PickFace => yew::html! { <super::ch10::PickFace /> },
+        FramebufferObject => yew::html! { <super::ch10::FramebufferObject /> },
+        Shadow => yew::html! { <super::ch10::Shadow /> },
+        Shadow_highp => yew::html! { <super::ch10::Shadow_highp /> },
+        OBJViewer => yew::html! { <super::ch10::OBJViewer /> },
+        GltfViewer => yew::html! { <super::ch10::GltfViewer /> },
     }
 }
@@ -0,0 +1,8 @@
+// Diffuse and ambient reflection of a point light by a surface of `color`,
+// at `position` with the unit `normal` in world coordinates
+vec3 pointLight(vec3 color, vec3 normal, vec3 position,
+                vec3 lightPosition, vec3 lightColor, vec3 ambientLight) {
+    vec3 lightDirection = normalize(lightPosition - position);
+    float nDotL = max(dot(lightDirection, normal), 0.0);
+    return lightColor * color * nDotL + ambientLight * color;
+}
@@ -0,0 +1,6 @@
+// The color an object is drawn with in the picking pass, matching what
+// Picker::pick decodes
+highp vec4 idColor(highp float id) {
+    highp float n = id + 1.0;
+    return vec4(mod(n, 256.0), mod(floor(n / 256.0), 256.0), floor(n / 65536.0), 255.0) / 255.0;
+}
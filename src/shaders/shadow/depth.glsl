@@ -0,0 +1,11 @@
+// The depth stored in the shadow map at `texCoord`, between 0.0 and 1.0.
+// SHADOW_PACKED_RGBA is defined when it is packed into the 4 bytes of an
+// RGBA texture, the sampler being declared highp.
+highp float shadowDepth(highp sampler2D shadowMap, highp vec2 texCoord) {
+#ifdef SHADOW_PACKED_RGBA
+    const highp vec4 bitShift = vec4(1.0, 1.0 / 256.0, 1.0 / (256.0 * 256.0), 1.0 / (256.0 * 256.0 * 256.0));
+    return dot(texture2D(shadowMap, texCoord), bitShift);
+#else
+    return texture2D(shadowMap, texCoord).r;
+#endif
+}
@@ -46,6 +46,37 @@ pub fn sidemenu() -> yew::Html {
                 <MenuItem><Link<Page> to={Page::LookAtTriangles}>{ "LookAtTriangles" }</Link<Page>></MenuItem>
                 <MenuItem><Link<Page> to={Page::LookAtRotatedTriangles}>{ "LookAtRotatedTriangles" }</Link<Page>></MenuItem>
                 <MenuItem><Link<Page> to={Page::LookAtTrianglesWithKeys}>{ "LookAtTrianglesWithKeys" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::OrthoView}>{ "OrthoView" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::PerspectiveView}>{ "PerspectiveView" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::PerspectiveView_mvp}>{ "PerspectiveView_mvp" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::DepthBuffer}>{ "DepthBuffer" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::Zfighting}>{ "Zfighting" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::HelloCube}>{ "HelloCube" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::ColoredCube}>{ "ColoredCube" }</Link<Page>></MenuItem>
+            </Submenu>
+            <Submenu title="ch08">
+                <MenuItem><Link<Page> to={Page::LightedCube}>{ "LightedCube" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::LightedCube_ambient}>{ "LightedCube_ambient" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::LightedTranslatedRotatedCube}>{ "LightedTranslatedRotatedCube" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::PointLightedCube}>{ "PointLightedCube" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::PointLightedCube_perFragment}>{ "PointLightedCube_perFragment" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::PointLightedSphere}>{ "PointLightedSphere" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::PointLightedSphere_perFragment}>{ "PointLightedSphere_perFragment" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::Primitives}>{ "Primitives" }</Link<Page>></MenuItem>
+            </Submenu>
+            <Submenu title="ch09">
+                <MenuItem><Link<Page> to={Page::JointModel}>{ "JointModel" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::MultiJointModel}>{ "MultiJointModel" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::MultiJointModel_segment}>{ "MultiJointModel_segment" }</Link<Page>></MenuItem>
+            </Submenu>
+            <Submenu title="ch10">
+                <MenuItem><Link<Page> to={Page::PickObject}>{ "PickObject" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::PickFace}>{ "PickFace" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::FramebufferObject}>{ "FramebufferObject" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::Shadow}>{ "Shadow" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::Shadow_highp}>{ "Shadow_highp" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::OBJViewer}>{ "OBJViewer" }</Link<Page>></MenuItem>
+                <MenuItem><Link<Page> to={Page::GltfViewer}>{ "GltfViewer" }</Link<Page>></MenuItem>
             </Submenu>
         </Menu>
         </div>
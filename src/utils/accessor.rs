@@ -0,0 +1,508 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+// Component types of accessors
+pub const BYTE: u32 = 5120;
+pub const UNSIGNED_BYTE: u32 = 5121;
+pub const SHORT: u32 = 5122;
+pub const UNSIGNED_SHORT: u32 = 5123;
+pub const UNSIGNED_INT: u32 = 5125;
+pub const FLOAT: u32 = 5126;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessorError(String);
+
+impl fmt::Display for AccessorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for AccessorError {}
+
+fn error<T>(message: impl Into<String>) -> Result<T, AccessorError> {
+    Err(AccessorError(message.into()))
+}
+
+/// A glTF `bufferView`: a slice of a buffer, with the distance between elements
+/// when they are interleaved
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferView {
+    pub buffer: usize,
+    #[serde(default)]
+    pub byte_offset: usize,
+    pub byte_length: usize,
+    pub byte_stride: Option<usize>,
+}
+
+/// A glTF `accessor`: how to read typed elements out of a buffer view
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Accessor {
+    /// Without a buffer view, all elements are zeros until replaced by `sparse`
+    pub buffer_view: Option<usize>,
+    #[serde(default)]
+    pub byte_offset: usize,
+    pub component_type: u32,
+    #[serde(default)]
+    pub normalized: bool,
+    pub count: usize,
+    /// `SCALAR`, `VEC2`, ...
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub sparse: Option<Sparse>,
+}
+
+/// Elements of an accessor replaced by other values
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Sparse {
+    pub count: usize,
+    pub indices: SparseIndices,
+    pub values: SparseValues,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparseIndices {
+    pub buffer_view: usize,
+    #[serde(default)]
+    pub byte_offset: usize,
+    pub component_type: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparseValues {
+    pub buffer_view: usize,
+    #[serde(default)]
+    pub byte_offset: usize,
+}
+
+/// Number of components of an element of type `ty`, and the number of columns
+/// they are split into
+fn element_shape(ty: &str) -> Result<(usize, usize), AccessorError> {
+    match ty {
+        "SCALAR" => Ok((1, 1)),
+        "VEC2" => Ok((2, 1)),
+        "VEC3" => Ok((3, 1)),
+        "VEC4" => Ok((4, 1)),
+        "MAT2" => Ok((4, 2)),
+        "MAT3" => Ok((9, 3)),
+        "MAT4" => Ok((16, 4)),
+        _ => error(format!("unknown accessor type {}", ty)),
+    }
+}
+
+fn component_size(component_type: u32) -> Result<usize, AccessorError> {
+    match component_type {
+        BYTE | UNSIGNED_BYTE => Ok(1),
+        SHORT | UNSIGNED_SHORT => Ok(2),
+        UNSIGNED_INT | FLOAT => Ok(4),
+        _ => error(format!("unknown component type {}", component_type)),
+    }
+}
+
+/// Read a component of type `component_type` at the start of `bytes`, converting
+/// integers to float as `normalized` says
+fn read_component(bytes: &[u8], component_type: u32, normalized: bool) -> f32 {
+    match component_type {
+        BYTE => {
+            let value = bytes[0] as i8 as f32;
+            if normalized {
+                (value / 127.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        UNSIGNED_BYTE => {
+            let value = bytes[0] as f32;
+            if normalized {
+                value / 255.0
+            } else {
+                value
+            }
+        }
+        SHORT => {
+            let value = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized {
+                (value / 32767.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        UNSIGNED_SHORT => {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized {
+                value / 65535.0
+            } else {
+                value
+            }
+        }
+        UNSIGNED_INT => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+        _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    }
+}
+
+fn read_index(bytes: &[u8], component_type: u32) -> Result<u32, AccessorError> {
+    match component_type {
+        UNSIGNED_BYTE => Ok(bytes[0] as u32),
+        UNSIGNED_SHORT => Ok(u16::from_le_bytes([bytes[0], bytes[1]]) as u32),
+        UNSIGNED_INT => Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        _ => error(format!("invalid index component type {}", component_type)),
+    }
+}
+
+/// The bytes of the buffer view `index`
+fn view_bytes<'a>(
+    views: &[BufferView],
+    buffers: &'a [Vec<u8>],
+    index: usize,
+) -> Result<(&'a [u8], Option<usize>), AccessorError> {
+    let view = match views.get(index) {
+        Some(view) => view,
+        None => return error(format!("buffer view {} does not exist", index)),
+    };
+    let buffer = match buffers.get(view.buffer) {
+        Some(buffer) => buffer,
+        None => return error(format!("buffer {} does not exist", view.buffer)),
+    };
+    let end = view.byte_offset.checked_add(view.byte_length);
+    match end.and_then(|end| buffer.get(view.byte_offset..end)) {
+        Some(bytes) => Ok((bytes, view.byte_stride)),
+        None => error(format!(
+            "buffer view {} is out of the bounds of buffer {}",
+            index, view.buffer
+        )),
+    }
+}
+
+/// Read the elements of `accessor` as floats, `count` times the number of
+/// components of its type
+pub fn read_floats(
+    accessor: &Accessor,
+    views: &[BufferView],
+    buffers: &[Vec<u8>],
+) -> Result<Vec<f32>, AccessorError> {
+    if accessor.normalized && matches!(accessor.component_type, FLOAT | UNSIGNED_INT) {
+        return error("only byte and short accessors can be normalized");
+    }
+    read_components(accessor, views, buffers, |bytes| {
+        Ok(read_component(
+            bytes,
+            accessor.component_type,
+            accessor.normalized,
+        ))
+    })
+}
+
+/// Read the elements of a `SCALAR` accessor of unsigned integers, such as the
+/// vertex indices of a primitive
+pub fn read_indices(
+    accessor: &Accessor,
+    views: &[BufferView],
+    buffers: &[Vec<u8>],
+) -> Result<Vec<u32>, AccessorError> {
+    if accessor.ty != "SCALAR" {
+        return error(format!("indices must be SCALAR, not {}", accessor.ty));
+    }
+    read_components(accessor, views, buffers, |bytes| {
+        read_index(bytes, accessor.component_type)
+    })
+}
+
+/// Read all the components of `accessor` with `read`, applying the strides, the
+/// matrix column alignment and the sparse substitution
+fn read_components<T, F>(
+    accessor: &Accessor,
+    views: &[BufferView],
+    buffers: &[Vec<u8>],
+    read: F,
+) -> Result<Vec<T>, AccessorError>
+where
+    T: Copy + Default,
+    F: Fn(&[u8]) -> Result<T, AccessorError>,
+{
+    let (components, columns) = element_shape(&accessor.ty)?;
+    let size = component_size(accessor.component_type)?;
+    // Matrix columns start on 4-byte boundaries
+    let rows = components / columns;
+    let column_stride = if columns > 1 {
+        (rows * size).div_ceil(4) * 4
+    } else {
+        rows * size
+    };
+    let element_size = column_stride * columns;
+    let component_offset = |c: usize| (c / rows) * column_stride + (c % rows) * size;
+
+    // The bounds checked before allocating, the sizes coming from the file
+    let view = match accessor.buffer_view {
+        Some(view) => {
+            let (bytes, stride) = view_bytes(views, buffers, view)?;
+            let stride = stride.unwrap_or(element_size);
+            if accessor.count > 0 {
+                let end = (accessor.count - 1)
+                    .checked_mul(stride)
+                    .and_then(|end| end.checked_add(accessor.byte_offset))
+                    .and_then(|end| end.checked_add(element_size));
+                match end {
+                    Some(end) if end <= bytes.len() => {}
+                    Some(end) => {
+                        return error(format!(
+                            "accessor needs {} bytes but buffer view {} has {}",
+                            end,
+                            view,
+                            bytes.len()
+                        ))
+                    }
+                    None => {
+                        return error(format!("accessor count {} is too large", accessor.count))
+                    }
+                }
+            }
+            Some((bytes, stride))
+        }
+        None => None,
+    };
+    let sparse = match accessor.sparse.as_ref() {
+        Some(sparse) => {
+            let index_size = component_size(sparse.indices.component_type)?;
+            let (index_bytes, _) = view_bytes(views, buffers, sparse.indices.buffer_view)?;
+            let (value_bytes, _) = view_bytes(views, buffers, sparse.values.buffer_view)?;
+            let fits = |offset: usize, size: usize, bytes: &[u8]| {
+                sparse
+                    .count
+                    .checked_mul(size)
+                    .and_then(|end| end.checked_add(offset))
+                    .is_some_and(|end| end <= bytes.len())
+            };
+            if !fits(sparse.indices.byte_offset, index_size, index_bytes)
+                || !fits(sparse.values.byte_offset, element_size, value_bytes)
+            {
+                return error("sparse accessor is out of the bounds of its buffer views");
+            }
+            Some((sparse, index_size, index_bytes, value_bytes))
+        }
+        None => None,
+    };
+    let length = match accessor.count.checked_mul(components) {
+        Some(length) => length,
+        None => return error(format!("accessor count {} is too large", accessor.count)),
+    };
+
+    let mut values = vec![T::default(); length];
+    if let Some((bytes, stride)) = view {
+        for (i, element) in values.chunks_mut(components).enumerate() {
+            let start = accessor.byte_offset + i * stride;
+            for (c, value) in element.iter_mut().enumerate() {
+                *value = read(&bytes[start + component_offset(c)..])?;
+            }
+        }
+    }
+
+    if let Some((sparse, index_size, index_bytes, value_bytes)) = sparse {
+        for i in 0..sparse.count {
+            let index = read_index(
+                &index_bytes[sparse.indices.byte_offset + i * index_size..],
+                sparse.indices.component_type,
+            )? as usize;
+            if index >= accessor.count {
+                return error(format!(
+                    "sparse index {} is out of range ({} elements)",
+                    index, accessor.count
+                ));
+            }
+            let start = sparse.values.byte_offset + i * element_size;
+            for c in 0..components {
+                values[index * components + c] = read(&value_bytes[start + component_offset(c)..])?;
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accessor(component_type: u32, ty: &str, count: usize) -> Accessor {
+        Accessor {
+            buffer_view: Some(0),
+            component_type,
+            count,
+            ty: ty.to_string(),
+            ..Accessor::default()
+        }
+    }
+
+    fn view(byte_length: usize) -> BufferView {
+        BufferView {
+            byte_length,
+            ..BufferView::default()
+        }
+    }
+
+    fn floats_bytes(values: &[f32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn tightly_packed_floats() {
+        let buffer = floats_bytes(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let values = read_floats(
+            &accessor(FLOAT, "VEC3", 2),
+            &[view(buffer.len())],
+            &[buffer],
+        )
+        .unwrap();
+        assert_eq!(values, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn byte_stride_and_offsets() {
+        // Interleaved position (vec2) and a float to skip, after 4 bytes of padding
+        let mut buffer = vec![0xff; 4];
+        buffer.extend(floats_bytes(&[1.0, 2.0, 9.0, 3.0, 4.0, 9.0]));
+        let views = [BufferView {
+            byte_offset: 4,
+            byte_length: 24,
+            byte_stride: Some(12),
+            ..BufferView::default()
+        }];
+        let values = read_floats(&accessor(FLOAT, "VEC2", 2), &views, &[buffer]).unwrap();
+        assert_eq!(values, [1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn normalized_integers() {
+        let buffer = vec![255, 0, 51, 0];
+        let mut unsigned = accessor(UNSIGNED_BYTE, "VEC2", 2);
+        unsigned.normalized = true;
+        let values = read_floats(&unsigned, &[view(4)], &[buffer]).unwrap();
+        assert_eq!(values, [1.0, 0.0, 0.2, 0.0]);
+
+        let buffer: Vec<u8> = [i16::MIN, 32767, 0, -32767]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let mut signed = accessor(SHORT, "SCALAR", 4);
+        signed.normalized = true;
+        let values = read_floats(&signed, &[view(8)], std::slice::from_ref(&buffer)).unwrap();
+        assert_eq!(values, [-1.0, 1.0, 0.0, -1.0]);
+
+        // Without normalization the integer values are kept
+        let values = read_floats(&accessor(SHORT, "SCALAR", 4), &[view(8)], &[buffer]).unwrap();
+        assert_eq!(values, [-32768.0, 32767.0, 0.0, -32767.0]);
+    }
+
+    #[test]
+    fn matrix_columns_are_aligned() {
+        // A MAT2 of bytes: each 2-byte column is padded to 4 bytes
+        let buffer = vec![1, 2, 0, 0, 3, 4, 0, 0];
+        let values = read_floats(&accessor(BYTE, "MAT2", 1), &[view(8)], &[buffer]).unwrap();
+        assert_eq!(values, [1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn indices_of_each_type() {
+        let buffer = vec![1, 0, 2, 0, 3, 0];
+        let indices = read_indices(
+            &accessor(UNSIGNED_SHORT, "SCALAR", 3),
+            &[view(6)],
+            &[buffer],
+        )
+        .unwrap();
+        assert_eq!(indices, [1, 2, 3]);
+
+        let buffer = 70000u32.to_le_bytes().to_vec();
+        let indices =
+            read_indices(&accessor(UNSIGNED_INT, "SCALAR", 1), &[view(4)], &[buffer]).unwrap();
+        assert_eq!(indices, [70000]);
+
+        let error = read_indices(&accessor(FLOAT, "SCALAR", 1), &[view(4)], &[vec![0; 4]]);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn sparse_values_replace_elements() {
+        // 3 zero scalars without a buffer view, the second one replaced by 5.0
+        let mut buffer = vec![1, 0];
+        buffer.extend(floats_bytes(&[5.0]));
+        let views = [
+            view(2),
+            BufferView {
+                byte_offset: 2,
+                byte_length: 4,
+                ..BufferView::default()
+            },
+        ];
+        let sparse = Accessor {
+            buffer_view: None,
+            sparse: Some(Sparse {
+                count: 1,
+                indices: SparseIndices {
+                    buffer_view: 0,
+                    byte_offset: 0,
+                    component_type: UNSIGNED_SHORT,
+                },
+                values: SparseValues {
+                    buffer_view: 1,
+                    byte_offset: 0,
+                },
+            }),
+            ..accessor(FLOAT, "SCALAR", 3)
+        };
+        let values = read_floats(&sparse, &views, &[buffer]).unwrap();
+        assert_eq!(values, [0.0, 5.0, 0.0]);
+    }
+
+    #[test]
+    fn out_of_bounds_reads_fail() {
+        let buffer = floats_bytes(&[1.0, 2.0]);
+        let error = read_floats(
+            &accessor(FLOAT, "VEC3", 1),
+            &[view(8)],
+            std::slice::from_ref(&buffer),
+        );
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "accessor needs 12 bytes but buffer view 0 has 8"
+        );
+        let error = read_floats(
+            &accessor(FLOAT, "SCALAR", 1),
+            &[view(16)],
+            std::slice::from_ref(&buffer),
+        );
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "buffer view 0 is out of the bounds of buffer 0"
+        );
+        let error = read_floats(
+            &accessor(FLOAT, "VEC3", usize::MAX),
+            &[view(8)],
+            std::slice::from_ref(&buffer),
+        );
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            format!("accessor count {} is too large", usize::MAX)
+        );
+        let mut offset_view = view(8);
+        offset_view.byte_offset = usize::MAX;
+        let error = read_floats(
+            &accessor(FLOAT, "SCALAR", 1),
+            &[offset_view],
+            std::slice::from_ref(&buffer),
+        );
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "buffer view 0 is out of the bounds of buffer 0"
+        );
+        let error = read_floats(&accessor(FLOAT, "VEC5", 1), &[view(8)], &[buffer]);
+        assert_eq!(error.unwrap_err().to_string(), "unknown accessor type VEC5");
+    }
+}
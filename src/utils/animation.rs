@@ -0,0 +1,211 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use gloo::render::AnimationFrame;
+use wasm_bindgen::JsError;
+
+use super::{context_loss, error_message};
+
+/// Upper bound of the time simulated in a single frame, so that coming back to a
+/// background tab does not replay minutes of fixed steps
+const MAX_FRAME_TIME: f64 = 250.0;
+
+/// Timing information passed to the per-frame closure, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// Time since the previous frame, or the step length for a fixed timestep
+    pub delta: f64,
+    /// Running time since the loop started, pauses excluded
+    pub elapsed: f64,
+    /// Number of frames delivered so far, starting at 0
+    pub count: u64,
+}
+
+/// The per-frame closure, the loop stopping when it fails
+type Callback = Box<dyn FnMut(Frame) -> Result<(), JsError>>;
+
+struct State {
+    callback: Option<Callback>,
+    tick: Option<AnimationFrame>,
+    fixed_timestep: Option<f64>,
+    paused: bool,
+    last_render: Option<f64>,
+    accumulator: f64,
+    elapsed: f64,
+    count: u64,
+}
+
+/// Drives a closure from `requestAnimationFrame` until dropped, or until it
+/// fails
+pub struct AnimationLoop {
+    state: Rc<RefCell<State>>,
+}
+
+impl AnimationLoop {
+    /// Call `callback` once per display frame with the measured frame time
+    pub fn new<F: FnMut(Frame) + 'static>(mut callback: F) -> Self {
+        Self::try_new(move |frame| {
+            callback(frame);
+            Ok(())
+        })
+    }
+
+    /// Like `new`, the loop stopping at the first error `callback` returns,
+    /// which is logged
+    pub fn try_new<F>(callback: F) -> Self
+    where
+        F: FnMut(Frame) -> Result<(), JsError> + 'static,
+    {
+        Self::start(None, Box::new(callback))
+    }
+
+    /// Call `callback` with a constant `delta` of `step` milliseconds, as many
+    /// times per display frame as needed to catch up with real time
+    pub fn with_fixed_timestep<F: FnMut(Frame) + 'static>(step: f64, mut callback: F) -> Self {
+        let callback = move |frame| {
+            callback(frame);
+            Ok(())
+        };
+        Self::start(Some(step), Box::new(callback))
+    }
+
+    fn start(fixed_timestep: Option<f64>, callback: Callback) -> Self {
+        let state = Rc::new(RefCell::new(State {
+            callback: Some(callback),
+            tick: None,
+            fixed_timestep,
+            paused: false,
+            last_render: None,
+            accumulator: 0.0,
+            elapsed: 0.0,
+            count: 0,
+        }));
+        request_tick(&state);
+        Self { state }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.borrow().paused
+    }
+
+    pub fn pause(&self) {
+        let mut state = self.state.borrow_mut();
+        state.paused = true;
+        // cancelAnimationFrame
+        state.tick = None;
+    }
+
+    pub fn resume(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            if !state.paused {
+                return;
+            }
+            state.paused = false;
+            // Do not count the time spent paused
+            state.last_render = None;
+        }
+        request_tick(&self.state);
+    }
+}
+
+impl Drop for AnimationLoop {
+    fn drop(&mut self) {
+        // cancelAnimationFrame
+        self.state.borrow_mut().tick = None;
+    }
+}
+
+fn request_tick(state: &Rc<RefCell<State>>) {
+    let weak = Rc::downgrade(state);
+    // A reference to the new handle must be retained for the next render to run.
+    state.borrow_mut().tick = Some(gloo::render::request_animation_frame(move |now| {
+        on_tick(weak, now)
+    }));
+}
+
+fn on_tick(weak: Weak<RefCell<State>>, now: f64) {
+    let state = match weak.upgrade() {
+        Some(state) => state,
+        None => return,
+    };
+
+    if context_loss::is_lost() {
+        // Paused until the context is restored, the page being set up again
+        state.borrow_mut().last_render = None;
+        request_tick(&state);
+        return;
+    }
+
+    let (frames, mut callback) = {
+        let mut state = state.borrow_mut();
+        let elapsed = state
+            .last_render
+            .map(|last_render| (now - last_render).min(MAX_FRAME_TIME))
+            .unwrap_or(0.0);
+        state.last_render = Some(now);
+
+        let mut frames = Vec::new();
+        match state.fixed_timestep {
+            Some(step) => {
+                state.accumulator += elapsed;
+                while state.accumulator >= step {
+                    state.accumulator -= step;
+                    state.elapsed += step;
+                    frames.push(Frame {
+                        delta: step,
+                        elapsed: state.elapsed,
+                        count: state.count,
+                    });
+                    state.count += 1;
+                }
+            }
+            None => {
+                state.elapsed += elapsed;
+                frames.push(Frame {
+                    delta: elapsed,
+                    elapsed: state.elapsed,
+                    count: state.count,
+                });
+                state.count += 1;
+            }
+        }
+        (frames, state.callback.take())
+    };
+
+    // The state is not borrowed while the callback runs, so that it can pause
+    // or resume the loop.
+    if let Some(mut running) = callback.take() {
+        match frames.into_iter().try_for_each(&mut running) {
+            Ok(()) => callback = Some(running),
+            // Dropping the callback stops the loop for good
+            Err(error) => log::error!("Animation stopped: {}", error_message(error)),
+        }
+    }
+
+    let paused = {
+        let mut state = state.borrow_mut();
+        state.paused |= callback.is_none();
+        state.callback = callback;
+        state.paused
+    };
+    if !paused {
+        request_tick(&state);
+    }
+}
+
+/// Run `callback` every frame while the calling component is mounted, until it
+/// returns an error
+#[yew::hook]
+pub fn use_animation_loop<F>(callback: F)
+where
+    F: FnMut(Frame) -> Result<(), JsError> + 'static,
+{
+    yew::use_effect_with_deps(
+        move |_| {
+            let animation = AnimationLoop::try_new(callback);
+            move || drop(animation)
+        },
+        (),
+    )
+}
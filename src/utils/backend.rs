@@ -0,0 +1,297 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::{HtmlImageElement, WebGl2RenderingContext as GL};
+
+use super::debug::{self, enum_name};
+use super::texture::TextureBuilder;
+use super::{ShaderProgram, Texture, VertexArray, VertexLayout, WebGl2RenderingContextExt};
+
+/// A value set to a uniform variable through a `RenderBackend`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UniformValue {
+    Float(f32),
+    Int(i32),
+    UInt(u32),
+    Bool(bool),
+    Vec2(na::Vector2<f32>),
+    Vec3(na::Vector3<f32>),
+    Vec4(na::Vector4<f32>),
+    Mat3(na::Matrix3<f32>),
+    Mat4(na::Matrix4<f32>),
+}
+
+impl From<f32> for UniformValue {
+    fn from(value: f32) -> Self {
+        UniformValue::Float(value)
+    }
+}
+
+impl From<i32> for UniformValue {
+    fn from(value: i32) -> Self {
+        UniformValue::Int(value)
+    }
+}
+
+impl From<u32> for UniformValue {
+    fn from(value: u32) -> Self {
+        UniformValue::UInt(value)
+    }
+}
+
+impl From<bool> for UniformValue {
+    fn from(value: bool) -> Self {
+        UniformValue::Bool(value)
+    }
+}
+
+impl From<[f32; 2]> for UniformValue {
+    fn from(value: [f32; 2]) -> Self {
+        UniformValue::Vec2(value.into())
+    }
+}
+
+impl From<[f32; 3]> for UniformValue {
+    fn from(value: [f32; 3]) -> Self {
+        UniformValue::Vec3(value.into())
+    }
+}
+
+impl From<[f32; 4]> for UniformValue {
+    fn from(value: [f32; 4]) -> Self {
+        UniformValue::Vec4(value.into())
+    }
+}
+
+impl From<na::Vector2<f32>> for UniformValue {
+    fn from(value: na::Vector2<f32>) -> Self {
+        UniformValue::Vec2(value)
+    }
+}
+
+impl From<na::Vector3<f32>> for UniformValue {
+    fn from(value: na::Vector3<f32>) -> Self {
+        UniformValue::Vec3(value)
+    }
+}
+
+impl From<na::Vector4<f32>> for UniformValue {
+    fn from(value: na::Vector4<f32>) -> Self {
+        UniformValue::Vec4(value)
+    }
+}
+
+impl From<na::Matrix3<f32>> for UniformValue {
+    fn from(value: na::Matrix3<f32>) -> Self {
+        UniformValue::Mat3(value)
+    }
+}
+
+impl From<na::Matrix4<f32>> for UniformValue {
+    fn from(value: na::Matrix4<f32>) -> Self {
+        UniformValue::Mat4(value)
+    }
+}
+
+/// The indices of a vertex array, of the type given to `draw_elements`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indices<'a> {
+    U8(&'a [u8]),
+    U16(&'a [u16]),
+    U32(&'a [u32]),
+}
+
+impl<'a> From<&'a [u8]> for Indices<'a> {
+    fn from(indices: &'a [u8]) -> Self {
+        Indices::U8(indices)
+    }
+}
+
+impl<'a> From<&'a [u16]> for Indices<'a> {
+    fn from(indices: &'a [u16]) -> Self {
+        Indices::U16(indices)
+    }
+}
+
+impl<'a> From<&'a [u32]> for Indices<'a> {
+    fn from(indices: &'a [u32]) -> Self {
+        Indices::U32(indices)
+    }
+}
+
+/// What the scenes of the pages draw with, so that they can be described once
+/// and run either in WebGL or natively in the tests
+pub trait RenderBackend {
+    type Program;
+    type VertexArray;
+    type Texture;
+    /// The source of the pixels of a texture
+    type Image;
+
+    /// Compile and link a program, and use it
+    fn create_program(&mut self, vertex: &str, fragment: &str) -> Result<Self::Program, JsError>;
+    /// Upload each buffer of vertices laid out as its layout, and the `indices`
+    /// drawn by `draw_elements` if any, into a vertex array left bound
+    fn create_vertex_array(
+        &mut self,
+        program: &Self::Program,
+        buffers: &[(&VertexLayout, &[f32])],
+        indices: Option<Indices>,
+    ) -> Result<Self::VertexArray, JsError>;
+    fn create_texture(
+        &mut self,
+        options: &TextureBuilder,
+        image: &Self::Image,
+    ) -> Result<Self::Texture, JsError>;
+    fn set_uniform(
+        &mut self,
+        program: &Self::Program,
+        name: &str,
+        value: UniformValue,
+    ) -> Result<(), JsError>;
+
+    /// Set the value of an attribute no buffer is given for
+    fn vertex_attrib(
+        &mut self,
+        program: &Self::Program,
+        name: &str,
+        value: [f32; 4],
+    ) -> Result<(), JsError>;
+
+    fn use_program(&mut self, program: &Self::Program);
+    fn bind_vertex_array(&mut self, vertex_array: &Self::VertexArray);
+    fn clear_color(&mut self, red: f32, green: f32, blue: f32, alpha: f32);
+    fn enable(&mut self, capability: u32);
+    fn polygon_offset(&mut self, factor: f32, units: f32);
+    fn clear(&mut self, mask: u32);
+    fn draw_arrays(&mut self, mode: u32, first: i32, count: i32);
+    /// Draw `count` of the indices of the vertex array bound, of type `ty`
+    fn draw_elements(&mut self, mode: u32, count: i32, ty: u32);
+}
+
+/// The backend drawing in a WebGL2 context
+#[derive(Debug, Clone)]
+pub struct WebGl2Backend {
+    gl: GL,
+}
+
+impl WebGl2Backend {
+    pub fn new(gl: GL) -> Self {
+        Self { gl }
+    }
+}
+
+impl RenderBackend for WebGl2Backend {
+    type Program = ShaderProgram;
+    type VertexArray = VertexArray;
+    type Texture = Texture;
+    type Image = HtmlImageElement;
+
+    fn create_program(&mut self, vertex: &str, fragment: &str) -> Result<Self::Program, JsError> {
+        Ok(self.gl.init_shaders(vertex, fragment)?)
+    }
+
+    fn create_vertex_array(
+        &mut self,
+        program: &Self::Program,
+        buffers: &[(&VertexLayout, &[f32])],
+        indices: Option<Indices>,
+    ) -> Result<Self::VertexArray, JsError> {
+        let mut vao = VertexArray::new(&self.gl)?;
+        for (layout, data) in buffers {
+            vao = vao.buffer(program, layout, data)?;
+        }
+        match indices {
+            Some(Indices::U8(indices)) => vao.indices(indices),
+            Some(Indices::U16(indices)) => vao.indices(indices),
+            Some(Indices::U32(indices)) => vao.indices(indices),
+            None => Ok(vao),
+        }
+    }
+
+    fn create_texture(
+        &mut self,
+        options: &TextureBuilder,
+        image: &Self::Image,
+    ) -> Result<Self::Texture, JsError> {
+        options.build(&self.gl, image)
+    }
+
+    fn set_uniform(
+        &mut self,
+        program: &Self::Program,
+        name: &str,
+        value: UniformValue,
+    ) -> Result<(), JsError> {
+        match value {
+            UniformValue::Float(value) => program.set_uniform(name, &value),
+            UniformValue::Int(value) => program.set_uniform(name, &value),
+            UniformValue::UInt(value) => program.set_uniform(name, &value),
+            UniformValue::Bool(value) => program.set_uniform(name, &value),
+            UniformValue::Vec2(value) => program.set_uniform(name, &value),
+            UniformValue::Vec3(value) => program.set_uniform(name, &value),
+            UniformValue::Vec4(value) => program.set_uniform(name, &value),
+            UniformValue::Mat3(value) => program.set_uniform(name, &value),
+            UniformValue::Mat4(value) => program.set_uniform(name, &value),
+        }?;
+        Ok(())
+    }
+
+    #[track_caller]
+    fn vertex_attrib(
+        &mut self,
+        program: &Self::Program,
+        name: &str,
+        value: [f32; 4],
+    ) -> Result<(), JsError> {
+        let location = program.attrib_location(name)?;
+        self.gl.vertex_attrib4fv_with_f32_array(location, &value);
+        debug::check_errors(&self.gl, || format!("vertexAttrib4fv({})", name));
+        Ok(())
+    }
+
+    #[track_caller]
+    fn use_program(&mut self, program: &Self::Program) {
+        program.use_program();
+    }
+
+    #[track_caller]
+    fn bind_vertex_array(&mut self, vertex_array: &Self::VertexArray) {
+        vertex_array.bind();
+    }
+
+    #[track_caller]
+    fn clear_color(&mut self, red: f32, green: f32, blue: f32, alpha: f32) {
+        self.gl.clear_color(red, green, blue, alpha);
+        debug::check_errors(&self.gl, || {
+            format!("clearColor({}, {}, {}, {})", red, green, blue, alpha)
+        });
+    }
+
+    #[track_caller]
+    fn enable(&mut self, capability: u32) {
+        self.gl.enable(capability);
+        debug::check_errors(&self.gl, || format!("enable({})", enum_name(capability)));
+    }
+
+    #[track_caller]
+    fn polygon_offset(&mut self, factor: f32, units: f32) {
+        self.gl.polygon_offset(factor, units);
+        debug::check_errors(&self.gl, || format!("polygonOffset({}, {})", factor, units));
+    }
+
+    #[track_caller]
+    fn clear(&mut self, mask: u32) {
+        self.gl.clear(mask);
+        debug::check_errors(&self.gl, || format!("clear(0x{:X})", mask));
+    }
+
+    #[track_caller]
+    fn draw_arrays(&mut self, mode: u32, first: i32, count: i32) {
+        self.gl.checked_draw_arrays(mode, first, count);
+    }
+
+    #[track_caller]
+    fn draw_elements(&mut self, mode: u32, count: i32, ty: u32) {
+        self.gl.checked_draw_elements(mode, count, ty, 0);
+    }
+}
@@ -0,0 +1,428 @@
+use std::f32::consts::FRAC_PI_2;
+
+use nalgebra as na;
+
+// Radians per pixel of mouse movement
+const ROTATE_SPEED: f32 = 0.01;
+
+// The pitch stays short of the poles, where the view would flip
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// How a `Camera` projects the view space onto the canvas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// `fovy` is the vertical field of view in radians
+    Perspective { fovy: f32, near: f32, far: f32 },
+    /// `height` is the height of the view volume, its width follows the aspect
+    /// ratio of the canvas
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Projection {
+    pub fn matrix(&self, aspect: f32) -> na::Matrix4<f32> {
+        match *self {
+            Projection::Perspective { fovy, near, far } => {
+                na::Matrix4::new_perspective(aspect, fovy, near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let (half_width, half_height) = (height * aspect / 2.0, height / 2.0);
+                na::Matrix4::new_orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+        }
+    }
+
+    /// The height of the view volume at `distance` from the eye, in world units
+    fn height_at(&self, distance: f32) -> f32 {
+        match *self {
+            Projection::Perspective { fovy, .. } => 2.0 * distance * (fovy / 2.0).tan(),
+            Projection::Orthographic { height, .. } => height,
+        }
+    }
+}
+
+/// A camera at `eye` looking at `target`, the view being upright along `up`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub eye: na::Point3<f32>,
+    pub target: na::Point3<f32>,
+    pub up: na::Vector3<f32>,
+    pub projection: Projection,
+}
+
+impl Camera {
+    /// A camera with the y axis up
+    pub fn new(eye: na::Point3<f32>, target: na::Point3<f32>, projection: Projection) -> Self {
+        Self {
+            eye,
+            target,
+            up: na::Vector3::y(),
+            projection,
+        }
+    }
+
+    pub fn view_matrix(&self) -> na::Matrix4<f32> {
+        na::Matrix4::look_at_rh(&self.eye, &self.target, &self.up)
+    }
+
+    pub fn projection_matrix(&self, aspect: f32) -> na::Matrix4<f32> {
+        self.projection.matrix(aspect)
+    }
+
+    pub fn view_proj_matrix(&self, aspect: f32) -> na::Matrix4<f32> {
+        self.projection_matrix(aspect) * self.view_matrix()
+    }
+}
+
+/// Turns, zooms and pans a camera around a target point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitController {
+    pub target: na::Point3<f32>,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+impl OrbitController {
+    /// Orbit around the target of `camera`, from its current eye
+    pub fn from_camera(camera: &Camera) -> Self {
+        let offset = camera.eye - camera.target;
+        let distance = offset.norm();
+        let (yaw, pitch) = if distance > 0.0 {
+            (
+                offset.x.atan2(offset.z),
+                (offset.y / distance).clamp(-1.0, 1.0).asin(),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        Self {
+            target: camera.target,
+            distance,
+            yaw,
+            pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+            min_distance: distance / 100.0,
+            max_distance: distance * 100.0,
+        }
+    }
+
+    pub fn eye(&self) -> na::Point3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        self.target
+            + na::Vector3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw) * self.distance
+    }
+
+    /// Turn around the target as if the scene were dragged by `dx`, `dy` pixels
+    pub fn rotate(&mut self, dx: f32, dy: f32) {
+        self.turn(-dx * ROTATE_SPEED, dy * ROTATE_SPEED);
+    }
+
+    /// Turn the eye by `yaw` radians around the y axis through the target, and
+    /// raise it by `pitch` radians
+    pub fn turn(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch = (self.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Move the target in the view plane as if the scene were dragged by `dx`,
+    /// `dy` pixels of a canvas `viewport_height` pixels high
+    pub fn pan(&mut self, dx: f32, dy: f32, viewport_height: f32, projection: &Projection) {
+        if viewport_height <= 0.0 {
+            return;
+        }
+        let scale = projection.height_at(self.distance) / viewport_height;
+        let forward = (self.target - self.eye()).normalize();
+        let right = forward.cross(&na::Vector3::y()).normalize();
+        let up = right.cross(&forward);
+        self.target += (up * dy - right * dx) * scale;
+    }
+
+    /// Zoom in for positive `steps`, out for negative ones, by 10% a step
+    pub fn zoom(&mut self, steps: f32, projection: &mut Projection) {
+        let factor = 0.9f32.powf(steps);
+        match projection {
+            Projection::Perspective { .. } => {
+                self.distance =
+                    (self.distance * factor).clamp(self.min_distance, self.max_distance);
+            }
+            Projection::Orthographic { height, .. } => *height *= factor,
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.eye();
+        camera.target = self.target;
+        camera.up = na::Vector3::y();
+    }
+}
+
+/// Keys held down for a `FlyController`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct FlyKeys {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+}
+
+/// Moves a camera like a first-person view: WASD to move along the view
+/// direction, Q and E to go down and up, dragging to look around
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlyController {
+    pub position: na::Point3<f32>,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// World units per second
+    pub speed: f32,
+    keys: FlyKeys,
+}
+
+impl FlyController {
+    /// Fly from the eye of `camera`, looking at its target
+    pub fn from_camera(camera: &Camera, speed: f32) -> Self {
+        let direction = camera.target - camera.eye;
+        let length = direction.norm();
+        let (yaw, pitch) = if length > 0.0 {
+            (
+                (-direction.x).atan2(-direction.z),
+                (direction.y / length).clamp(-1.0, 1.0).asin(),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        Self {
+            position: camera.eye,
+            yaw,
+            pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+            speed,
+            keys: FlyKeys::default(),
+        }
+    }
+
+    pub fn forward(&self) -> na::Vector3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        na::Vector3::new(-cos_pitch * sin_yaw, sin_pitch, -cos_pitch * cos_yaw)
+    }
+
+    /// Turn the view as if following the mouse by `dx`, `dy` pixels
+    pub fn look(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * ROTATE_SPEED;
+        self.pitch = (self.pitch - dy * ROTATE_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Record a key press or release, `code` being a `KeyboardEvent.code`
+    pub fn set_key(&mut self, code: &str, pressed: bool) -> bool {
+        let key = match code {
+            "KeyW" => &mut self.keys.forward,
+            "KeyS" => &mut self.keys.backward,
+            "KeyA" => &mut self.keys.left,
+            "KeyD" => &mut self.keys.right,
+            "KeyE" => &mut self.keys.up,
+            "KeyQ" => &mut self.keys.down,
+            _ => return false,
+        };
+        *key = pressed;
+        true
+    }
+
+    /// Release all the keys, when the page loses the focus
+    pub fn release_keys(&mut self) {
+        self.keys = FlyKeys::default();
+    }
+
+    /// Move for `delta` seconds with the keys held down
+    pub fn update(&mut self, delta: f32) -> bool {
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        let forward = self.forward();
+        let right = forward.cross(&na::Vector3::y()).normalize();
+        let direction = forward * axis(self.keys.forward, self.keys.backward)
+            + right * axis(self.keys.right, self.keys.left)
+            + na::Vector3::y() * axis(self.keys.up, self.keys.down);
+        match direction.try_normalize(f32::EPSILON) {
+            Some(direction) => {
+                self.position += direction * self.speed * delta;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.eye = self.position;
+        camera.target = self.position + self.forward();
+        camera.up = na::Vector3::y();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: na::Point3<f32>, b: na::Point3<f32>) {
+        assert!((a - b).norm() < 1.0e-4, "{} != {}", a, b);
+    }
+
+    fn camera() -> Camera {
+        Camera::new(
+            na::Point3::new(0.0, 0.0, 5.0),
+            na::Point3::origin(),
+            Projection::Perspective {
+                fovy: 60f32.to_radians(),
+                near: 0.1,
+                far: 100.0,
+            },
+        )
+    }
+
+    #[test]
+    fn view_matrix_moves_the_eye_to_the_origin() {
+        let camera = Camera::new(
+            na::Point3::new(1.0, 2.0, 3.0),
+            na::Point3::new(1.0, 2.0, 0.0),
+            camera().projection,
+        );
+        let view = camera.view_matrix();
+        assert_near(view.transform_point(&camera.eye), na::Point3::origin());
+        // The target is in front, down the -z axis
+        assert_near(
+            view.transform_point(&camera.target),
+            na::Point3::new(0.0, 0.0, -3.0),
+        );
+    }
+
+    #[test]
+    fn orthographic_width_follows_the_aspect_ratio() {
+        let projection = Projection::Orthographic {
+            height: 2.0,
+            near: 0.0,
+            far: 2.0,
+        };
+        assert_eq!(
+            projection.matrix(1.0),
+            na::Matrix4::new_orthographic(-1.0, 1.0, -1.0, 1.0, 0.0, 2.0)
+        );
+        let corner = projection
+            .matrix(2.0)
+            .transform_point(&na::Point3::new(2.0, 1.0, -1.0));
+        assert_near(corner, na::Point3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn orbit_round_trips_the_camera() {
+        let mut camera = Camera::new(
+            na::Point3::new(3.0, 4.0, -2.0),
+            na::Point3::new(1.0, 0.5, 0.0),
+            camera().projection,
+        );
+        let orbit = OrbitController::from_camera(&camera);
+        assert_near(orbit.eye(), camera.eye);
+        let before = camera;
+        orbit.apply(&mut camera);
+        assert_near(camera.eye, before.eye);
+        assert_eq!(camera.target, before.target);
+    }
+
+    #[test]
+    fn orbit_rotation_keeps_the_distance_and_clamps_the_pitch() {
+        let mut orbit = OrbitController::from_camera(&camera());
+        orbit.rotate(-157.0, 0.0);
+        // Dragging the scene left brings the eye toward +x
+        assert!(orbit.eye().x > 4.9);
+        assert!(((orbit.eye() - orbit.target).norm() - 5.0).abs() < 1.0e-4);
+
+        orbit.rotate(0.0, 1000.0);
+        assert_eq!(orbit.pitch, MAX_PITCH);
+        orbit.rotate(0.0, -5000.0);
+        assert_eq!(orbit.pitch, -MAX_PITCH);
+    }
+
+    #[test]
+    fn orbit_zoom_scales_the_distance_or_the_view_volume() {
+        let mut camera = camera();
+        let mut orbit = OrbitController::from_camera(&camera);
+        orbit.zoom(1.0, &mut camera.projection);
+        assert!((orbit.distance - 4.5).abs() < 1.0e-5);
+        orbit.zoom(-1000.0, &mut camera.projection);
+        assert_eq!(orbit.distance, orbit.max_distance);
+
+        let mut projection = Projection::Orthographic {
+            height: 2.0,
+            near: 0.0,
+            far: 2.0,
+        };
+        let distance = orbit.distance;
+        orbit.zoom(2.0, &mut projection);
+        assert_eq!(orbit.distance, distance);
+        match projection {
+            Projection::Orthographic { height, .. } => assert!((height - 1.62).abs() < 1.0e-5),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn orbit_pan_follows_the_mouse() {
+        let camera = camera();
+        let mut orbit = OrbitController::from_camera(&camera);
+        // The view is 2 * 5 * tan(30°) high at the target
+        let height = 10.0 * 30f32.to_radians().tan();
+        orbit.pan(100.0, 50.0, 100.0, &camera.projection);
+        assert_near(orbit.target, na::Point3::new(-height, height / 2.0, 0.0));
+        // The eye moves along
+        assert_near(orbit.eye(), orbit.target + na::Vector3::new(0.0, 0.0, 5.0));
+    }
+
+    #[test]
+    fn fly_starts_looking_at_the_target() {
+        let mut camera = Camera::new(
+            na::Point3::new(1.0, 2.0, 3.0),
+            na::Point3::new(4.0, -1.0, 0.0),
+            camera().projection,
+        );
+        let fly = FlyController::from_camera(&camera, 1.0);
+        let direction = (camera.target - camera.eye).normalize();
+        assert!((fly.forward() - direction).norm() < 1.0e-4);
+
+        fly.apply(&mut camera);
+        assert_near(camera.target, camera.eye + direction);
+    }
+
+    #[test]
+    fn fly_moves_with_the_keys_held_down() {
+        let mut fly = FlyController::from_camera(&camera(), 2.0);
+        assert!(!fly.update(1.0));
+
+        assert!(fly.set_key("KeyW", true));
+        assert!(!fly.set_key("KeyX", true));
+        assert!(fly.update(0.5));
+        assert_near(fly.position, na::Point3::new(0.0, 0.0, 4.0));
+
+        // Diagonal moves are not faster
+        fly.set_key("KeyD", true);
+        fly.update(1.0);
+        let step = 2.0 / 2f32.sqrt();
+        assert_near(fly.position, na::Point3::new(step, 0.0, 4.0 - step));
+
+        fly.release_keys();
+        assert!(!fly.update(1.0));
+    }
+
+    #[test]
+    fn fly_look_turns_toward_the_mouse() {
+        let mut fly = FlyController::from_camera(&camera(), 1.0);
+        fly.look(10.0, -10.0);
+        let forward = fly.forward();
+        assert!(forward.x > 0.0 && forward.y > 0.0 && forward.z < 0.0);
+    }
+}
@@ -0,0 +1,277 @@
+use nalgebra as na;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, KeyboardEvent, PointerEvent, WheelEvent};
+
+use super::camera::{Camera, FlyController, OrbitController};
+use super::gesture::{Gesture, GestureRecognizer};
+use super::input::PointerCapture;
+
+// Degrees turned by an arrow key
+const ARROW_STEP: f32 = 2.0;
+
+// Pixels of a wheel notch, the unit of `WheelEvent.deltaY` in lines mode
+const LINE_HEIGHT: f64 = 40.0;
+
+/// How a `CameraControl` moves its camera
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlMode {
+    /// Drag to turn around the target, right-drag or drag two fingers to pan,
+    /// wheel or pinch to zoom, twist two fingers or use the arrow keys to turn
+    Orbit,
+    /// Drag to look around, WASD to move, Q and E to go down and up
+    Fly,
+}
+
+/// What dragging one pointer does, after the button pressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DragAction {
+    Rotate,
+    Pan,
+}
+
+/// A `Camera` moved by the pointer and the keyboard events of a canvas
+#[derive(Debug)]
+pub struct CameraControl {
+    camera: Camera,
+    mode: ControlMode,
+    orbit: OrbitController,
+    fly: FlyController,
+    gestures: GestureRecognizer,
+    capture: PointerCapture,
+    // What the pointer down first does, and the height of its canvas
+    action: DragAction,
+    viewport_height: f32,
+    // The camera a double tap goes back to
+    home: Camera,
+}
+
+impl CameraControl {
+    pub fn new(camera: Camera, mode: ControlMode) -> Self {
+        let orbit = OrbitController::from_camera(&camera);
+        // Crossing the distance to the target takes 2 seconds
+        let fly = FlyController::from_camera(&camera, orbit.distance.max(0.1) / 2.0);
+        Self {
+            camera,
+            mode,
+            orbit,
+            fly,
+            gestures: GestureRecognizer::default(),
+            capture: PointerCapture::default(),
+            action: DragAction::Rotate,
+            viewport_height: 0.0,
+            home: camera,
+        }
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn mode(&self) -> ControlMode {
+        self.mode
+    }
+
+    /// Switch to `mode`, keeping the camera where it is
+    pub fn set_mode(&mut self, mode: ControlMode) {
+        self.stop_tracking();
+        match mode {
+            ControlMode::Orbit if self.mode == ControlMode::Fly => {
+                // Orbit around the point ahead, as far as before
+                let orbit = self.orbit;
+                self.camera.target = self.camera.eye + self.fly.forward() * orbit.distance;
+                self.orbit = OrbitController {
+                    min_distance: orbit.min_distance,
+                    max_distance: orbit.max_distance,
+                    ..OrbitController::from_camera(&self.camera)
+                };
+                self.orbit.apply(&mut self.camera);
+            }
+            ControlMode::Fly if self.mode == ControlMode::Orbit => {
+                self.fly = FlyController::from_camera(&self.camera, self.fly.speed);
+            }
+            _ => {}
+        }
+        self.mode = mode;
+    }
+
+    /// Forget the pointers and the keys held down
+    pub fn stop_tracking(&mut self) {
+        self.gestures.reset();
+        self.capture.release_all();
+        self.fly.release_keys();
+    }
+
+    /// Move the eye by `offset`, still looking at the target
+    pub fn move_eye(&mut self, offset: na::Vector3<f32>) {
+        self.camera.eye += offset;
+        self.sync_controllers();
+    }
+
+    /// Go back to the camera the control was created with
+    fn go_home(&mut self) {
+        self.camera = self.home;
+        self.sync_controllers();
+    }
+
+    fn sync_controllers(&mut self) {
+        self.orbit = OrbitController {
+            min_distance: self.orbit.min_distance,
+            max_distance: self.orbit.max_distance,
+            ..OrbitController::from_camera(&self.camera)
+        };
+        self.fly = FlyController::from_camera(&self.camera, self.fly.speed);
+    }
+
+    pub fn onpointerdown(&mut self, event: &PointerEvent) {
+        let action = match (event.button(), self.mode) {
+            (0, _) => DragAction::Rotate,
+            // Also the barrel button of a pen
+            (2, ControlMode::Orbit) => DragAction::Pan,
+            _ => return,
+        };
+        let first = !self.gestures.is_active();
+        if !self.gestures.onpointerdown(event.into()) {
+            return;
+        }
+        if first {
+            self.action = action;
+            self.viewport_height = event
+                .current_target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .map_or(0.0, |target| target.client_height() as f32);
+        }
+        self.capture.capture(event);
+    }
+
+    pub fn onpointermove(&mut self, event: &PointerEvent) -> bool {
+        let gestures = self.gestures.onpointermove(event.into());
+        self.apply_gestures(&gestures)
+    }
+
+    pub fn onpointerup(&mut self, event: &PointerEvent) -> bool {
+        self.capture.forget(event.pointer_id());
+        let gestures = self.gestures.onpointerup(event.into());
+        self.apply_gestures(&gestures)
+    }
+
+    /// Drop the pointer, the camera staying where its last move left it
+    pub fn onpointercancel(&mut self, event: &PointerEvent) {
+        self.capture.forget(event.pointer_id());
+        self.gestures.onpointercancel(event.pointer_id());
+    }
+
+    fn apply_gestures(&mut self, gestures: &[Gesture]) -> bool {
+        let mut changed = false;
+        for &gesture in gestures {
+            changed |= match (self.mode, gesture) {
+                (_, Gesture::DoubleTap { .. }) => {
+                    self.go_home();
+                    true
+                }
+                (ControlMode::Orbit, Gesture::Drag { dx, dy }) => {
+                    match self.action {
+                        DragAction::Rotate => self.orbit.rotate(dx, dy),
+                        DragAction::Pan => {
+                            self.orbit
+                                .pan(dx, dy, self.viewport_height, &self.camera.projection)
+                        }
+                    }
+                    true
+                }
+                (ControlMode::Orbit, Gesture::Pan { dx, dy }) => {
+                    self.orbit
+                        .pan(dx, dy, self.viewport_height, &self.camera.projection);
+                    true
+                }
+                (ControlMode::Orbit, Gesture::Pinch { scale }) => {
+                    // Spreading the fingers brings the target closer, a zoom
+                    // step multiplying the distance by 0.9
+                    self.orbit
+                        .zoom(-scale.ln() / 0.9f32.ln(), &mut self.camera.projection);
+                    true
+                }
+                (ControlMode::Orbit, Gesture::Rotate { angle }) => {
+                    self.orbit.turn(-angle, 0.0);
+                    true
+                }
+                (ControlMode::Fly, Gesture::Drag { dx, dy }) => {
+                    self.fly.look(dx, dy);
+                    true
+                }
+                // Flying is done with the keys
+                (ControlMode::Fly, _) => false,
+            };
+        }
+        if changed {
+            self.apply();
+        }
+        changed
+    }
+
+    /// Zoom in orbit mode
+    pub fn onwheel(&mut self, event: &WheelEvent) -> bool {
+        if self.mode != ControlMode::Orbit {
+            return false;
+        }
+        event.prevent_default();
+        let delta = match event.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => event.delta_y() * LINE_HEIGHT,
+            _ => event.delta_y(),
+        };
+        // A notch scrolls about 100 pixels down to zoom out one step
+        self.orbit
+            .zoom(-delta as f32 / 100.0, &mut self.camera.projection);
+        self.apply();
+        true
+    }
+
+    pub fn onkeydown(&mut self, event: &KeyboardEvent) -> bool {
+        match self.mode {
+            ControlMode::Orbit => {
+                let step = ARROW_STEP.to_radians();
+                let (yaw, pitch) = match event.key().as_str() {
+                    "ArrowLeft" => (step, 0.0),
+                    "ArrowRight" => (-step, 0.0),
+                    "ArrowUp" => (0.0, step),
+                    "ArrowDown" => (0.0, -step),
+                    _ => return false,
+                };
+                // Turning by keys is ignored while dragging
+                if self.gestures.is_active() {
+                    return false;
+                }
+                event.prevent_default();
+                self.orbit.turn(yaw, pitch);
+                self.apply();
+                true
+            }
+            // Movements are applied by update()
+            ControlMode::Fly => {
+                self.fly.set_key(&event.code(), true);
+                false
+            }
+        }
+    }
+
+    pub fn onkeyup(&mut self, event: &KeyboardEvent) {
+        self.fly.set_key(&event.code(), false);
+    }
+
+    /// Move the camera for the `delta` milliseconds elapsed since the last
+    /// frame, with the keys held down in fly mode
+    pub fn update(&mut self, delta: f64) -> bool {
+        if self.mode == ControlMode::Fly && self.fly.update(delta as f32 / 1000.0) {
+            self.apply();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn apply(&mut self) {
+        match self.mode {
+            ControlMode::Orbit => self.orbit.apply(&mut self.camera),
+            ControlMode::Fly => self.fly.apply(&mut self.camera),
+        }
+    }
+}
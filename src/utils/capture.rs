@@ -0,0 +1,530 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gloo::render::AnimationFrame;
+use js_sys::{Array, Function, Map, Object, Proxy, Reflect, WeakMap};
+use serde::Serialize;
+use wasm_bindgen::prelude::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL, WebGlProgram};
+use yew::Callback;
+
+use super::debug::enum_name;
+
+/// The calls after which the state used by the draw is recorded
+const DRAW_CALLS: [&str; 5] = [
+    "drawArrays",
+    "drawElements",
+    "drawArraysInstanced",
+    "drawElementsInstanced",
+    "drawRangeElements",
+];
+
+/// The capabilities listed in the state of a draw when enabled
+const CAPABILITIES: [(&str, u32); 6] = [
+    ("BLEND", GL::BLEND),
+    ("CULL_FACE", GL::CULL_FACE),
+    ("DEPTH_TEST", GL::DEPTH_TEST),
+    ("POLYGON_OFFSET_FILL", GL::POLYGON_OFFSET_FILL),
+    ("SCISSOR_TEST", GL::SCISSOR_TEST),
+    ("STENCIL_TEST", GL::STENCIL_TEST),
+];
+
+/// Typed arrays longer than this are shortened when described
+const MAX_ELEMENTS: u32 = 16;
+
+/// Strings longer than this, such as shader sources, are shortened
+const MAX_CHARS: usize = 40;
+
+/// The GL calls of the page during one frame
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Capture {
+    pub calls: Vec<Call>,
+}
+
+impl Capture {
+    pub fn draw_count(&self) -> usize {
+        self.calls
+            .iter()
+            .filter(|call| call.state.is_some())
+            .count()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.calls
+            .iter()
+            .filter(|call| !call.errors.is_empty())
+            .count()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Call {
+    /// The method called and its arguments
+    pub name: String,
+    /// What `getError` returned after the call
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    /// The state used by a draw call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<DrawState>,
+}
+
+/// The objects bound and the uniform values when drawing
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DrawState {
+    pub program: Option<String>,
+    pub vertex_array: Option<String>,
+    pub array_buffer: Option<String>,
+    pub element_array_buffer: Option<String>,
+    /// `None` for the canvas
+    pub framebuffer: Option<String>,
+    pub viewport: String,
+    pub enabled: Vec<String>,
+    pub uniforms: Vec<UniformState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UniformState {
+    pub name: String,
+    pub value: String,
+    /// The texture bound to the unit of a sampler
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+}
+
+// The context wrapped for a capture, and the traps of its proxy
+struct Wrapper {
+    gl: GL,
+    _get: Closure<dyn Fn(JsValue, JsValue, JsValue) -> JsValue>,
+    _apply: Closure<dyn Fn(Function, JsValue, Array) -> Result<JsValue, JsValue>>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    // Wrapping the contexts created
+    enabled: bool,
+    wrappers: Vec<Wrapper>,
+    // Recording from the next call
+    armed: bool,
+    // The calls of the frame being recorded, and the names of the objects
+    // they use
+    calls: Option<(Vec<Call>, Labels)>,
+    // The errors read after the calls recorded, until `getError` returns them
+    errors: Vec<u32>,
+    end: Option<AnimationFrame>,
+    last: Option<Rc<Capture>>,
+    listener: Option<Callback<()>>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Recorder> = RefCell::new(Recorder::default());
+}
+
+fn notify() {
+    // The listener may read the recorder
+    let listener = RECORDER.with(|recorder| recorder.borrow().listener.clone());
+    if let Some(listener) = listener {
+        listener.emit(());
+    }
+}
+
+/// Wrap the contexts created from now on to record their calls, or stop
+pub fn enable(enabled: bool) {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        recorder.enabled = enabled;
+        recorder.forget_replaced();
+    });
+}
+
+/// `gl` wrapped to record its calls when capturing is enabled
+pub fn wrap(gl: GL) -> GL {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        recorder.forget_replaced();
+        if !recorder.enabled {
+            return gl;
+        }
+        let (wrapper, proxy) = Wrapper::new(gl);
+        recorder.wrappers.push(wrapper);
+        proxy.unchecked_into()
+    })
+}
+
+impl Recorder {
+    // Drop the wrappers of the canvases the page replaced, which are not used
+    // anymore
+    fn forget_replaced(&mut self) {
+        self.wrappers.retain(|wrapper| {
+            wrapper
+                .gl
+                .canvas()
+                .and_then(|canvas| canvas.dyn_into::<HtmlCanvasElement>().ok())
+                .is_some_and(|canvas| canvas.is_connected())
+        });
+    }
+}
+
+impl Wrapper {
+    fn new(gl: GL) -> (Self, Proxy) {
+        let apply = {
+            let gl = gl.clone();
+            Closure::<dyn Fn(Function, JsValue, Array) -> Result<JsValue, JsValue>>::wrap(Box::new(
+                move |method, _this, arguments| call(&gl, &method, &arguments),
+            ))
+        };
+        let calls = Object::new();
+        Reflect::set(&calls, &"apply".into(), apply.as_ref()).unwrap();
+
+        // The methods of the context, bound to it and trapped
+        let methods = Map::new();
+        let get = Closure::<dyn Fn(JsValue, JsValue, JsValue) -> JsValue>::wrap(Box::new(
+            move |target, key, _receiver| {
+                // The getters of the context need it as `this`
+                let value = Reflect::get(&target, &key).unwrap_or_default();
+                let method = match value.dyn_ref::<Function>() {
+                    Some(method) => method,
+                    None => return value,
+                };
+                let trapped = methods.get(&key);
+                if !trapped.is_undefined() {
+                    return trapped;
+                }
+                let trapped = Proxy::new(&method.bind0(&target), &calls);
+                methods.set(&key, &trapped);
+                trapped.into()
+            },
+        ));
+        let properties = Object::new();
+        Reflect::set(&properties, &"get".into(), get.as_ref()).unwrap();
+
+        let proxy = Proxy::new(&gl, &properties);
+        let wrapper = Self {
+            gl,
+            _get: get,
+            _apply: apply,
+        };
+        (wrapper, proxy)
+    }
+}
+
+// Make the call of `method`, bound to `gl`, and record it when capturing
+fn call(gl: &GL, method: &Function, arguments: &Array) -> Result<JsValue, JsValue> {
+    let name = String::from(method.name());
+    let name = name.strip_prefix("bound ").unwrap_or(&name);
+    if name == "getError" {
+        // The errors read by the recorder first
+        let error = RECORDER.with(|recorder| {
+            let mut recorder = recorder.borrow_mut();
+            (!recorder.errors.is_empty()).then(|| recorder.errors.remove(0))
+        });
+        if let Some(error) = error {
+            return Ok(error.into());
+        }
+    }
+    let result = method.apply(&JsValue::UNDEFINED, arguments)?;
+    if name != "getError" && is_capturing() {
+        record(gl, name, arguments);
+    }
+    Ok(result)
+}
+
+/// Record the calls from the next one to the end of its frame
+pub fn arm() {
+    RECORDER.with(|recorder| recorder.borrow_mut().armed = true);
+    notify();
+}
+
+/// Whether a capture is waiting for a call or recording
+pub fn is_capturing() -> bool {
+    RECORDER.with(|recorder| {
+        let recorder = recorder.borrow();
+        recorder.armed || recorder.calls.is_some()
+    })
+}
+
+/// The last frame captured
+pub fn last() -> Option<Rc<Capture>> {
+    RECORDER.with(|recorder| recorder.borrow().last.clone())
+}
+
+/// Forget the capture of the page left
+pub fn clear() {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        recorder.armed = false;
+        recorder.calls = None;
+        recorder.end = None;
+        recorder.last = None;
+    });
+    notify();
+}
+
+/// Call `listener` when a capture starts or ends
+pub fn listen(listener: Option<Callback<()>>) {
+    RECORDER.with(|recorder| recorder.borrow_mut().listener = listener);
+}
+
+fn finish() {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        let calls = recorder.calls.take().map(|(calls, _)| calls);
+        recorder.last = Some(Rc::new(Capture {
+            calls: calls.unwrap_or_default(),
+        }));
+        recorder.end = None;
+    });
+    notify();
+}
+
+fn record(gl: &GL, name: &str, arguments: &Array) {
+    RECORDER.with(|recorder| {
+        let mut recorder = recorder.borrow_mut();
+        if recorder.armed {
+            recorder.armed = false;
+            recorder.calls = Some((Vec::new(), Labels::new()));
+            // After the callbacks of the current frame
+            recorder.end = Some(gloo::render::request_animation_frame(|_| finish()));
+        }
+        let errors = std::iter::from_fn(|| Some(gl.get_error()))
+            .take_while(|error| *error != GL::NO_ERROR && *error != GL::CONTEXT_LOST_WEBGL)
+            .collect::<Vec<_>>();
+        // Kept for `getError`, as by the context
+        for error in &errors {
+            if !recorder.errors.contains(error) {
+                recorder.errors.push(*error);
+            }
+        }
+        let (calls, labels) = match recorder.calls.as_mut() {
+            Some(calls) => calls,
+            None => return,
+        };
+        let arguments = arguments
+            .iter()
+            .map(|argument| labels.describe(&argument))
+            .collect::<Vec<_>>();
+        let state = DRAW_CALLS.contains(&name).then(|| draw_state(gl, labels));
+        calls.push(Call {
+            name: format!("{}({})", name, arguments.join(", ")),
+            errors: errors.iter().map(|error| enum_name(*error)).collect(),
+            state,
+        });
+    });
+}
+
+fn draw_state(gl: &GL, labels: &mut Labels) -> DrawState {
+    let parameter = |name| gl.get_parameter(name).unwrap_or(JsValue::NULL);
+    let object = |name| Some(parameter(name)).filter(|value| !value.is_null());
+    let mut described = |name| object(name).map(|value| labels.describe(&value));
+
+    let program = described(GL::CURRENT_PROGRAM);
+    let vertex_array = described(GL::VERTEX_ARRAY_BINDING);
+    let array_buffer = described(GL::ARRAY_BUFFER_BINDING);
+    let element_array_buffer = described(GL::ELEMENT_ARRAY_BUFFER_BINDING);
+    let framebuffer = described(GL::FRAMEBUFFER_BINDING);
+    let enabled = CAPABILITIES
+        .iter()
+        .filter(|(_, capability)| gl.is_enabled(*capability))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    DrawState {
+        program,
+        vertex_array,
+        array_buffer,
+        element_array_buffer,
+        framebuffer,
+        viewport: labels.describe(&parameter(GL::VIEWPORT)),
+        enabled,
+        uniforms: object(GL::CURRENT_PROGRAM)
+            .and_then(|value| value.dyn_into().ok())
+            .map(|program| uniforms(gl, &program, labels))
+            .unwrap_or_default(),
+    }
+}
+
+fn uniforms(gl: &GL, program: &WebGlProgram, labels: &mut Labels) -> Vec<UniformState> {
+    let count = gl
+        .get_program_parameter(program, GL::ACTIVE_UNIFORMS)
+        .as_f64()
+        .unwrap_or(0.0) as u32;
+    let active_texture = gl.get_parameter(GL::ACTIVE_TEXTURE).ok();
+
+    let uniforms = (0..count)
+        .filter_map(|index| gl.get_active_uniform(program, index))
+        .filter_map(|info| {
+            // Not the members of uniform blocks
+            let location = gl.get_uniform_location(program, &info.name())?;
+            let value = gl.get_uniform(program, &location);
+            let target = match info.type_() {
+                GL::SAMPLER_2D | GL::SAMPLER_2D_SHADOW => Some(GL::TEXTURE_BINDING_2D),
+                GL::SAMPLER_CUBE => Some(GL::TEXTURE_BINDING_CUBE_MAP),
+                _ => None,
+            };
+            let texture = target.and_then(|target| {
+                let unit = value.as_f64()? as u32;
+                gl.active_texture(GL::TEXTURE0 + unit);
+                let texture = gl.get_parameter(target).ok()?;
+                Some(texture)
+                    .filter(|texture| !texture.is_null())
+                    .map(|texture| labels.describe(&texture))
+            });
+            Some(UniformState {
+                name: info.name(),
+                value: labels.describe(&value),
+                texture,
+            })
+        })
+        .collect();
+
+    if let Some(unit) = active_texture.and_then(|unit| unit.as_f64()) {
+        gl.active_texture(unit as u32);
+    }
+    uniforms
+}
+
+/// Names for the WebGL objects, numbered by type as they are first seen
+struct Labels {
+    names: WeakMap,
+    counts: HashMap<String, u32>,
+}
+
+impl Labels {
+    fn new() -> Self {
+        Self {
+            names: WeakMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    fn describe(&mut self, value: &JsValue) -> String {
+        if let Some(number) = value.as_f64() {
+            return describe_number(number);
+        }
+        if let Some(text) = value.as_string() {
+            return quote(&text);
+        }
+        if let Some(value) = value.as_bool() {
+            return value.to_string();
+        }
+        if value.is_null() {
+            return "null".into();
+        }
+        let object = match value.dyn_ref::<Object>() {
+            Some(object) => object,
+            None => return format!("{:?}", value),
+        };
+        if let Some(name) = self.names.get(object).as_string() {
+            return name;
+        }
+        if Array::is_array(value) || js_sys::ArrayBuffer::is_view(value) {
+            let elements = Array::from(value);
+            let shown = elements
+                .iter()
+                .take(MAX_ELEMENTS as usize)
+                .map(|element| self.describe(&element))
+                .collect::<Vec<_>>();
+            let more = if elements.length() > MAX_ELEMENTS {
+                ", …"
+            } else {
+                ""
+            };
+            let kind = if Array::is_array(value) {
+                String::new()
+            } else {
+                type_name(object)
+            };
+            return format!("{}[{}{}]", kind, shown.join(", "), more);
+        }
+        let kind = type_name(object);
+        if !kind.starts_with("WebGL") {
+            return kind;
+        }
+        let count = self.counts.entry(kind.clone()).or_insert(0);
+        *count += 1;
+        let name = format!("{} {}", kind, count);
+        self.names.set(object, &JsValue::from(&name));
+        name
+    }
+}
+
+// The GL enums named, but the small numbers which are more likely counts
+fn describe_number(number: f64) -> String {
+    if number.fract() == 0.0 && (256.0..=f64::from(u32::MAX)).contains(&number) {
+        let name = enum_name(number as u32);
+        if !name.starts_with("0x") {
+            return name;
+        }
+    }
+    number.to_string()
+}
+
+fn quote(text: &str) -> String {
+    match text.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{:?}…", &text[..end]),
+        None => format!("{:?}", text),
+    }
+}
+
+fn type_name(object: &Object) -> String {
+    object.constructor().name().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_the_state_of_draws_only() {
+        let capture = Capture {
+            calls: vec![
+                Call {
+                    name: "clear(16384)".into(),
+                    errors: Vec::new(),
+                    state: None,
+                },
+                Call {
+                    name: "drawArrays(TRIANGLES, 0, 3)".into(),
+                    errors: vec![enum_name(GL::INVALID_OPERATION)],
+                    state: Some(DrawState {
+                        program: Some("WebGLProgram 1".into()),
+                        viewport: "Int32Array[0, 0, 400, 400]".into(),
+                        ..DrawState::default()
+                    }),
+                },
+            ],
+        };
+        assert_eq!((capture.draw_count(), capture.error_count()), (1, 1));
+
+        let json: serde_json::Value = serde_json::from_str(&capture.to_json()).unwrap();
+        let calls = json["calls"].as_array().unwrap();
+        assert!(calls[0].get("state").is_none());
+        assert!(calls[0].get("errors").is_none());
+        assert_eq!(calls[1]["errors"][0], "INVALID_OPERATION");
+        assert_eq!(calls[1]["state"]["program"], "WebGLProgram 1");
+        assert_eq!(calls[1]["state"]["framebuffer"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn names_enums_and_shortens_strings() {
+        assert_eq!(describe_number(f64::from(GL::ARRAY_BUFFER)), "ARRAY_BUFFER");
+        assert_eq!(describe_number(f64::from(GL::TEXTURE_2D)), "TEXTURE_2D");
+        // COLOR_BUFFER_BIT | DEPTH_BUFFER_BIT
+        assert_eq!(describe_number(16640.0), "16640");
+        // TRIANGLES, or a count
+        assert_eq!(describe_number(4.0), "4");
+        assert_eq!(describe_number(0.5), "0.5");
+
+        assert_eq!(quote("u_Sampler"), "\"u_Sampler\"");
+        let source = "#version 300 es\nin vec4 a_Position;\nvoid main() {}";
+        assert_eq!(
+            quote(source),
+            "\"#version 300 es\\nin vec4 a_Position;\\nvoid\"…"
+        );
+    }
+}
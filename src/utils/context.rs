@@ -1,16 +1,24 @@
+use std::rc::Rc;
+
 use wasm_bindgen::{JsCast, JsError, JsValue, UnwrapThrowExt};
 use web_sys::{
     CanvasRenderingContext2d, HtmlCanvasElement, WebGl2RenderingContext, WebGlProgram, WebGlShader,
 };
-use yew::NodeRef;
+use yew::{Callback, NodeRef};
+
+use super::debug::{self, enum_name};
+use super::shader_error::parse_info_log;
+use super::{
+    capture, context_loss, shader_registry, Preprocessor, ShaderError, ShaderProgram, ShaderStage,
+};
 
 fn noop() {}
 
 #[yew::hook]
-pub fn use_canvas_render<C: FnOnce(HtmlCanvasElement) -> Result<(), JsValue> + 'static>(
-    node: NodeRef,
-    render: C,
-) {
+pub fn use_canvas_render<C>(node: NodeRef, render: C)
+where
+    C: FnOnce(HtmlCanvasElement) -> Result<(), JsValue> + 'static,
+{
     yew::use_effect_with_deps(
         move |_| {
             let canvas = node.cast::<HtmlCanvasElement>().unwrap();
@@ -22,12 +30,10 @@ pub fn use_canvas_render<C: FnOnce(HtmlCanvasElement) -> Result<(), JsValue> + '
 }
 
 #[yew::hook]
-pub fn use_2d_canvas_render<
+pub fn use_2d_canvas_render<C>(node: NodeRef, render: C)
+where
     C: FnOnce(CanvasRenderingContext2d) -> Result<(), JsError> + 'static,
->(
-    node: NodeRef,
-    render: C,
-) {
+{
     use_canvas_render(node, |canvas| {
         let ctx = canvas
             .get_context("2d")
@@ -41,84 +47,142 @@ pub fn use_2d_canvas_render<
 }
 
 #[yew::hook]
-pub fn use_webgl2_canvas_render<
-    C: FnOnce(WebGl2RenderingContext) -> Result<(), JsError> + 'static,
->(
-    node: NodeRef,
-    render: C,
-) {
+pub fn use_webgl2_canvas_render<C>(node: NodeRef, render: C)
+where
+    C: Fn(WebGl2RenderingContext) -> Result<(), JsError> + 'static,
+{
     use_canvas_render(node, |canvas| {
-        let ctx = canvas
-            .get_context("webgl2")
-            .unwrap_throw()
-            .unwrap()
-            .dyn_into::<WebGl2RenderingContext>()
-            .unwrap();
-        render(ctx)?;
+        let render = Rc::new(render);
+        let onrestored = {
+            let render = render.clone();
+            Callback::from(move |gl| render(gl).map_err(JsValue::from).unwrap_throw())
+        };
+        render(webgl2_context(&canvas, onrestored))?;
         Ok(())
     })
 }
 
+/// The WebGL2 context of `canvas`, wrapped when capturing, `onrestored` setting
+/// the page up again once the context is restored after a loss
+pub fn webgl2_context(
+    canvas: &HtmlCanvasElement,
+    onrestored: Callback<WebGl2RenderingContext>,
+) -> WebGl2RenderingContext {
+    let gl = canvas
+        .get_context("webgl2")
+        .unwrap_throw()
+        .unwrap()
+        .dyn_into::<WebGl2RenderingContext>()
+        .unwrap();
+    let gl = capture::wrap(gl);
+    debug::log_renderer_info(&gl);
+    context_loss::watch(canvas, &gl, onrestored);
+    gl
+}
+
+/// The message of an error thrown or returned by the GL helpers, to show it in
+/// the page
+pub fn error_message(error: impl Into<JsValue>) -> String {
+    let error = error.into();
+    match error.dyn_ref::<js_sys::Error>() {
+        Some(error) => error.message().into(),
+        None => error.as_string().unwrap_or_else(|| format!("{:?}", error)),
+    }
+}
+
 pub trait WebGl2RenderingContextExt {
     fn compile_shader_from_source(
         &self,
         shader_type: u32,
         source: &str,
-    ) -> Result<WebGlShader, JsError>;
+    ) -> Result<WebGlShader, ShaderError>;
     fn link_program_with_shaders(
         &self,
         vert_shader: &WebGlShader,
         frag_shader: &WebGlShader,
-    ) -> Result<WebGlProgram, JsError>;
-    fn init_shaders(&self, vert_shader: &str, frag_shader: &str) -> Result<WebGlProgram, JsError>;
+    ) -> Result<WebGlProgram, ShaderError>;
+    /// Preprocess and compile a shader, the diagnostics pointing to the lines of
+    /// `source` and of the files it includes
+    fn compile_shader_with(
+        &self,
+        preprocessor: &Preprocessor,
+        shader_type: u32,
+        source: &str,
+    ) -> Result<WebGlShader, ShaderError>;
+    fn init_shaders_with(
+        &self,
+        preprocessor: &Preprocessor,
+        vert_shader: &str,
+        frag_shader: &str,
+    ) -> Result<ShaderProgram, ShaderError>;
 
-    fn compile_vertex_shader_from_source(&self, source: &str) -> Result<WebGlShader, JsError> {
-        self.compile_shader_from_source(WebGl2RenderingContext::VERTEX_SHADER, source)
+    #[track_caller]
+    fn init_shaders(
+        &self,
+        vert_shader: &str,
+        frag_shader: &str,
+    ) -> Result<ShaderProgram, ShaderError> {
+        self.init_shaders_with(&Preprocessor::default(), vert_shader, frag_shader)
     }
 
-    fn compile_fragment_shader_from_source(&self, source: &str) -> Result<WebGlShader, JsError> {
-        self.compile_shader_from_source(WebGl2RenderingContext::FRAGMENT_SHADER, source)
-    }
+    /// `drawArrays`, its errors checked
+    fn checked_draw_arrays(&self, mode: u32, first: i32, count: i32);
+    /// `drawElements` from the byte `offset` of the indices bound, its errors
+    /// checked
+    fn checked_draw_elements(&self, mode: u32, count: i32, ty: u32, offset: i32);
 }
 
 impl WebGl2RenderingContextExt for WebGl2RenderingContext {
+    #[track_caller]
     fn compile_shader_from_source(
         &self,
         shader_type: u32,
         source: &str,
-    ) -> Result<WebGlShader, JsError> {
+    ) -> Result<WebGlShader, ShaderError> {
         let shader = self
             .create_shader(shader_type)
-            .ok_or_else(|| JsError::new("Unable to create shader object"))?;
+            .ok_or(ShaderError::CreateFailed("shader"))?;
         self.shader_source(&shader, source);
         self.compile_shader(&shader);
+        debug::check_errors(self, || {
+            format!("compileShader({})", enum_name(shader_type))
+        });
 
         if self
             .get_shader_parameter(&shader, WebGl2RenderingContext::COMPILE_STATUS)
             .as_bool()
             .unwrap_or(false)
         {
+            debug::log_translated_source(self, &shader);
             Ok(shader)
         } else {
-            Err(self.get_shader_info_log(&shader).map_or_else(
-                || JsError::new("Unknown error creating shader"),
-                |info_log| JsError::new(&info_log),
-            ))
+            let diagnostics = self
+                .get_shader_info_log(&shader)
+                .map(|info_log| parse_info_log(&info_log))
+                .unwrap_or_default();
+            self.delete_shader(Some(&shader));
+            Err(ShaderError::Compile {
+                stage: ShaderStage::from_type(shader_type),
+                source: source.to_string(),
+                diagnostics,
+            })
         }
     }
 
+    #[track_caller]
     fn link_program_with_shaders(
         &self,
         vert_shader: &WebGlShader,
         frag_shader: &WebGlShader,
-    ) -> Result<WebGlProgram, JsError> {
+    ) -> Result<WebGlProgram, ShaderError> {
         let program = self
             .create_program()
-            .ok_or_else(|| JsError::new("Unable to create shader object"))?;
+            .ok_or(ShaderError::CreateFailed("program"))?;
 
         self.attach_shader(&program, vert_shader);
         self.attach_shader(&program, frag_shader);
         self.link_program(&program);
+        debug::check_errors(self, || "linkProgram".to_string());
 
         if self
             .get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS)
@@ -127,18 +191,106 @@ impl WebGl2RenderingContextExt for WebGl2RenderingContext {
         {
             Ok(program)
         } else {
-            Err(self.get_program_info_log(&program).map_or_else(
-                || JsError::new("Unknown error creating program object"),
-                |info_log| JsError::new(&info_log),
-            ))
+            let log = self.get_program_info_log(&program).unwrap_or_default();
+            self.delete_program(Some(&program));
+            Err(ShaderError::Link {
+                log: log
+                    .trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+                    .to_string(),
+            })
+        }
+    }
+
+    #[track_caller]
+    fn compile_shader_with(
+        &self,
+        preprocessor: &Preprocessor,
+        shader_type: u32,
+        source: &str,
+    ) -> Result<WebGlShader, ShaderError> {
+        let stage = ShaderStage::from_type(shader_type);
+        let compile_error = |diagnostics| ShaderError::Compile {
+            stage,
+            source: source.to_string(),
+            diagnostics,
+        };
+        let preprocessed = preprocessor.process(stage, source).map_err(compile_error)?;
+        self.compile_shader_from_source(shader_type, &preprocessed.source)
+            .map_err(|error| match error {
+                ShaderError::Compile { diagnostics, .. } => {
+                    compile_error(preprocessed.map_diagnostics(diagnostics))
+                }
+                error => error,
+            })
+    }
+
+    #[track_caller]
+    fn init_shaders_with(
+        &self,
+        preprocessor: &Preprocessor,
+        vert_shader: &str,
+        frag_shader: &str,
+    ) -> Result<ShaderProgram, ShaderError> {
+        // In the GLSL version chosen in the shader panel unless the page sets
+        // one, the shaders may have been edited there
+        let preprocessor = &preprocessor
+            .clone()
+            .default_version(shader_registry::version());
+        if let Some((vertex, fragment)) =
+            shader_registry::register(self, preprocessor, vert_shader, frag_shader)
+        {
+            match build_program(self, preprocessor, &vertex, &fragment) {
+                Ok(program) => return Ok(program),
+                Err(error) => log::warn!("{}, using the original shaders", error),
+            }
         }
+        build_program(self, preprocessor, vert_shader, frag_shader)
     }
 
-    fn init_shaders(&self, vert_shader: &str, frag_shader: &str) -> Result<WebGlProgram, JsError> {
-        let vert_shader = self.compile_vertex_shader_from_source(vert_shader)?;
-        let frag_shader = self.compile_fragment_shader_from_source(frag_shader)?;
-        let program = self.link_program_with_shaders(&vert_shader, &frag_shader)?;
-        self.use_program(Some(&program));
-        Ok(program)
+    #[track_caller]
+    fn checked_draw_arrays(&self, mode: u32, first: i32, count: i32) {
+        self.draw_arrays(mode, first, count);
+        debug::check_errors(self, || {
+            format!("drawArrays({}, {}, {})", enum_name(mode), first, count)
+        });
     }
+
+    #[track_caller]
+    fn checked_draw_elements(&self, mode: u32, count: i32, ty: u32, offset: i32) {
+        self.draw_elements_with_i32(mode, count, ty, offset);
+        debug::check_errors(self, || {
+            format!(
+                "drawElements({}, {}, {}, {})",
+                enum_name(mode),
+                count,
+                enum_name(ty),
+                offset
+            )
+        });
+    }
+}
+
+#[track_caller]
+fn build_program(
+    gl: &WebGl2RenderingContext,
+    preprocessor: &Preprocessor,
+    vert_shader: &str,
+    frag_shader: &str,
+) -> Result<ShaderProgram, ShaderError> {
+    let vert_shader = gl.compile_shader_with(
+        preprocessor,
+        WebGl2RenderingContext::VERTEX_SHADER,
+        vert_shader,
+    )?;
+    let frag_shader = gl.compile_shader_with(
+        preprocessor,
+        WebGl2RenderingContext::FRAGMENT_SHADER,
+        frag_shader,
+    )?;
+    let program = ShaderProgram::new(
+        gl,
+        gl.link_program_with_shaders(&vert_shader, &frag_shader)?,
+    );
+    program.use_program();
+    Ok(program)
 }
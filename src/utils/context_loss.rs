@@ -0,0 +1,187 @@
+use std::cell::RefCell;
+
+use gloo::events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as GL, WebglLoseContext};
+use yew::Callback;
+
+struct Context {
+    gl: GL,
+    canvas: HtmlCanvasElement,
+    lost: bool,
+    // Listening to the loss and the restoration of the context
+    _listeners: [EventListener; 2],
+    // The extension which lost the context when simulated, to restore it
+    simulated: Option<WebglLoseContext>,
+    // Sets the page up again on the restored context
+    onrestored: Callback<GL>,
+}
+
+#[derive(Default)]
+struct Manager {
+    // The contexts of the page shown
+    contexts: Vec<Context>,
+    listener: Option<Callback<()>>,
+}
+
+thread_local! {
+    static MANAGER: RefCell<Manager> = RefCell::new(Manager::default());
+}
+
+fn notify() {
+    // The listener may read the manager
+    let listener = MANAGER.with(|manager| manager.borrow().listener.clone());
+    if let Some(listener) = listener {
+        listener.emit(());
+    }
+}
+
+/// Call `f` with the context `gl` if it is tracked, and not a context of a page
+/// left or of a canvas replaced
+fn with_context<R>(gl: &GL, f: impl FnOnce(&mut Context) -> R) -> Option<R> {
+    MANAGER.with(|manager| {
+        let mut manager = manager.borrow_mut();
+        manager
+            .contexts
+            .iter_mut()
+            .find(|context| context.gl == *gl)
+            .map(f)
+    })
+}
+
+fn on_lost(gl: &GL) {
+    if with_context(gl, |context| context.lost = true).is_some() {
+        log::warn!("WebGL context lost");
+        notify();
+    }
+}
+
+fn on_restored(gl: &GL) {
+    let onrestored = with_context(gl, |context| {
+        context.lost = false;
+        context.simulated = None;
+        context.onrestored.clone()
+    });
+    if let Some(onrestored) = onrestored {
+        log::info!("WebGL context restored");
+        // The objects of the page are gone with the lost context
+        onrestored.emit(gl.clone());
+        notify();
+    }
+}
+
+/// Track the loss and the restoration of the context `gl` of `canvas`, calling
+/// `onrestored` to set the page up again once restored
+pub fn watch(canvas: &HtmlCanvasElement, gl: &GL, onrestored: Callback<GL>) {
+    // Set up again on the same context
+    if with_context(gl, |context| context.onrestored = onrestored.clone()).is_some() {
+        return;
+    }
+    let lost = {
+        let gl = gl.clone();
+        EventListener::new_with_options(
+            canvas,
+            "webglcontextlost",
+            EventListenerOptions::enable_prevent_default(),
+            move |event| {
+                // Or the context is never restored
+                event.prevent_default();
+                on_lost(&gl);
+            },
+        )
+    };
+    let restored = {
+        let gl = gl.clone();
+        EventListener::new(canvas, "webglcontextrestored", move |_| on_restored(&gl))
+    };
+    MANAGER.with(|manager| {
+        let mut manager = manager.borrow_mut();
+        // The canvases the page replaced
+        manager
+            .contexts
+            .retain(|context| context.canvas.is_connected());
+        manager.contexts.push(Context {
+            gl: gl.clone(),
+            canvas: canvas.clone(),
+            lost: false,
+            _listeners: [lost, restored],
+            simulated: None,
+            onrestored,
+        });
+    });
+}
+
+/// Whether a context of the page is lost, the animation loops being paused until
+/// it is restored
+pub fn is_lost() -> bool {
+    MANAGER.with(|manager| manager.borrow().contexts.iter().any(|context| context.lost))
+}
+
+/// Whether the contexts lost were all lost by `lose_context`, which
+/// `restore_context` restores
+pub fn is_simulated() -> bool {
+    MANAGER.with(|manager| {
+        manager
+            .borrow()
+            .contexts
+            .iter()
+            .filter(|context| context.lost)
+            .all(|context| context.simulated.is_some())
+    })
+}
+
+/// Lose the contexts of the page through `WEBGL_lose_context`, as when the GPU
+/// is reset
+pub fn lose_context() {
+    let extensions = MANAGER.with(|manager| {
+        let mut manager = manager.borrow_mut();
+        manager
+            .contexts
+            .iter_mut()
+            // Not available anymore once the context is lost
+            .filter(|context| !context.gl.is_context_lost())
+            .filter_map(|context| {
+                let extension = context
+                    .gl
+                    .get_extension("WEBGL_lose_context")
+                    .ok()
+                    .flatten()?
+                    .unchecked_into::<WebglLoseContext>();
+                context.simulated = Some(extension.clone());
+                Some(extension)
+            })
+            .collect::<Vec<_>>()
+    });
+    if extensions.is_empty() {
+        log::warn!("WEBGL_lose_context is not available");
+    }
+    // Outside of the manager, the events being dispatched right away
+    for extension in extensions {
+        extension.lose_context();
+    }
+}
+
+/// Restore the contexts lost by `lose_context`
+pub fn restore_context() {
+    let extensions = MANAGER.with(|manager| {
+        manager
+            .borrow()
+            .contexts
+            .iter()
+            .filter_map(|context| context.simulated.clone())
+            .collect::<Vec<_>>()
+    });
+    for extension in extensions {
+        extension.restore_context();
+    }
+}
+
+/// Stop tracking the contexts of the page, before it is created again
+pub fn clear() {
+    MANAGER.with(|manager| manager.borrow_mut().contexts.clear());
+}
+
+/// Call `listener` when a context is lost or restored
+pub fn listen(listener: Option<Callback<()>>) {
+    MANAGER.with(|manager| manager.borrow_mut().listener = listener);
+}
@@ -0,0 +1,177 @@
+use web_sys::{WebGl2RenderingContext as GL, WebGlShader};
+
+/// The name of the constant of a GL enum, such as the errors returned by
+/// `getError` and the enums passed by the helpers, or its value in hex
+pub fn enum_name(value: u32) -> String {
+    let name = match value {
+        // Errors
+        GL::INVALID_ENUM => "INVALID_ENUM",
+        GL::INVALID_VALUE => "INVALID_VALUE",
+        GL::INVALID_OPERATION => "INVALID_OPERATION",
+        GL::OUT_OF_MEMORY => "OUT_OF_MEMORY",
+        GL::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
+        GL::CONTEXT_LOST_WEBGL => "CONTEXT_LOST_WEBGL",
+        // Shaders
+        GL::VERTEX_SHADER => "VERTEX_SHADER",
+        GL::FRAGMENT_SHADER => "FRAGMENT_SHADER",
+        // Buffers
+        GL::ARRAY_BUFFER => "ARRAY_BUFFER",
+        GL::ELEMENT_ARRAY_BUFFER => "ELEMENT_ARRAY_BUFFER",
+        GL::UNIFORM_BUFFER => "UNIFORM_BUFFER",
+        GL::STATIC_DRAW => "STATIC_DRAW",
+        GL::DYNAMIC_DRAW => "DYNAMIC_DRAW",
+        GL::STREAM_DRAW => "STREAM_DRAW",
+        // Types
+        GL::BYTE => "BYTE",
+        GL::UNSIGNED_BYTE => "UNSIGNED_BYTE",
+        GL::SHORT => "SHORT",
+        GL::UNSIGNED_SHORT => "UNSIGNED_SHORT",
+        GL::INT => "INT",
+        GL::UNSIGNED_INT => "UNSIGNED_INT",
+        GL::FLOAT => "FLOAT",
+        GL::HALF_FLOAT => "HALF_FLOAT",
+        // Textures
+        GL::TEXTURE_2D => "TEXTURE_2D",
+        GL::TEXTURE_CUBE_MAP => "TEXTURE_CUBE_MAP",
+        GL::TEXTURE_3D => "TEXTURE_3D",
+        GL::TEXTURE_2D_ARRAY => "TEXTURE_2D_ARRAY",
+        GL::TEXTURE_WRAP_S => "TEXTURE_WRAP_S",
+        GL::TEXTURE_WRAP_T => "TEXTURE_WRAP_T",
+        GL::TEXTURE_MIN_FILTER => "TEXTURE_MIN_FILTER",
+        GL::TEXTURE_MAG_FILTER => "TEXTURE_MAG_FILTER",
+        GL::REPEAT => "REPEAT",
+        GL::CLAMP_TO_EDGE => "CLAMP_TO_EDGE",
+        GL::MIRRORED_REPEAT => "MIRRORED_REPEAT",
+        GL::NEAREST => "NEAREST",
+        GL::LINEAR => "LINEAR",
+        GL::NEAREST_MIPMAP_NEAREST => "NEAREST_MIPMAP_NEAREST",
+        GL::LINEAR_MIPMAP_NEAREST => "LINEAR_MIPMAP_NEAREST",
+        GL::NEAREST_MIPMAP_LINEAR => "NEAREST_MIPMAP_LINEAR",
+        GL::LINEAR_MIPMAP_LINEAR => "LINEAR_MIPMAP_LINEAR",
+        // Formats
+        GL::ALPHA => "ALPHA",
+        GL::LUMINANCE => "LUMINANCE",
+        GL::LUMINANCE_ALPHA => "LUMINANCE_ALPHA",
+        GL::RED => "RED",
+        GL::RG => "RG",
+        GL::RGB => "RGB",
+        GL::RGBA => "RGBA",
+        GL::RGB8 => "RGB8",
+        GL::RGBA8 => "RGBA8",
+        GL::DEPTH_COMPONENT => "DEPTH_COMPONENT",
+        GL::DEPTH_COMPONENT16 => "DEPTH_COMPONENT16",
+        GL::DEPTH_COMPONENT24 => "DEPTH_COMPONENT24",
+        GL::DEPTH_COMPONENT32F => "DEPTH_COMPONENT32F",
+        GL::DEPTH_STENCIL => "DEPTH_STENCIL",
+        // Framebuffers
+        GL::FRAMEBUFFER => "FRAMEBUFFER",
+        GL::RENDERBUFFER => "RENDERBUFFER",
+        GL::COLOR_ATTACHMENT0 => "COLOR_ATTACHMENT0",
+        GL::DEPTH_ATTACHMENT => "DEPTH_ATTACHMENT",
+        GL::DEPTH_STENCIL_ATTACHMENT => "DEPTH_STENCIL_ATTACHMENT",
+        // Draw modes, but POINTS and LINES
+        GL::LINE_LOOP => "LINE_LOOP",
+        GL::LINE_STRIP => "LINE_STRIP",
+        GL::TRIANGLES => "TRIANGLES",
+        GL::TRIANGLE_STRIP => "TRIANGLE_STRIP",
+        GL::TRIANGLE_FAN => "TRIANGLE_FAN",
+        // Capabilities
+        GL::BLEND => "BLEND",
+        GL::CULL_FACE => "CULL_FACE",
+        GL::DEPTH_TEST => "DEPTH_TEST",
+        GL::POLYGON_OFFSET_FILL => "POLYGON_OFFSET_FILL",
+        GL::SCISSOR_TEST => "SCISSOR_TEST",
+        GL::STENCIL_TEST => "STENCIL_TEST",
+        value => return format!("0x{:04X}", value),
+    };
+    name.to_string()
+}
+
+/// Log the errors of the GL calls just made, `call` describing them
+#[track_caller]
+#[inline]
+pub fn check_errors<F: FnOnce() -> String>(gl: &GL, call: F) {
+    if !cfg!(all(debug_assertions, feature = "gl-debug")) {
+        return;
+    }
+    // Through the capture wrapper, the errors it read after the calls
+    let errors = std::iter::from_fn(|| Some(gl.get_error()))
+        .take_while(|error| *error != GL::NO_ERROR && *error != GL::CONTEXT_LOST_WEBGL)
+        .map(enum_name)
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        log::error!(
+            "{} at {}: {}",
+            call(),
+            std::panic::Location::caller(),
+            errors.join(", ")
+        );
+    }
+}
+
+/// Log the GPU and driver of the context, from `WEBGL_debug_renderer_info`
+pub fn log_renderer_info(gl: &GL) {
+    #[cfg(all(debug_assertions, feature = "gl-debug"))]
+    {
+        use web_sys::WebglDebugRendererInfo as Info;
+
+        if gl.is_context_lost() {
+            return;
+        }
+        let renderer = match gl.get_extension("WEBGL_debug_renderer_info") {
+            Ok(Some(_)) => {
+                [Info::UNMASKED_VENDOR_WEBGL, Info::UNMASKED_RENDERER_WEBGL].map(|name| {
+                    gl.get_parameter(name)
+                        .ok()
+                        .and_then(|value| value.as_string())
+                })
+            }
+            _ => [None, None],
+        };
+        let version = gl
+            .get_parameter(GL::VERSION)
+            .ok()
+            .and_then(|value| value.as_string());
+        log::info!(
+            "{} on {} by {}",
+            version.as_deref().unwrap_or("WebGL"),
+            renderer[1].as_deref().unwrap_or("an unknown renderer"),
+            renderer[0].as_deref().unwrap_or("an unknown vendor"),
+        );
+    }
+    #[cfg(not(all(debug_assertions, feature = "gl-debug")))]
+    let _ = gl;
+}
+
+/// Log the source of a compiled shader as the driver translated it
+pub fn log_translated_source(gl: &GL, shader: &WebGlShader) {
+    #[cfg(all(debug_assertions, feature = "gl-debug"))]
+    {
+        use wasm_bindgen::JsCast;
+        use web_sys::WebglDebugShaders;
+
+        if let Ok(Some(extension)) = gl.get_extension("WEBGL_debug_shaders") {
+            let extension = extension.unchecked_into::<WebglDebugShaders>();
+            log::debug!(
+                "Shader translated by the driver:\n{}",
+                extension.get_translated_shader_source(shader)
+            );
+        }
+    }
+    #[cfg(not(all(debug_assertions, feature = "gl-debug")))]
+    let _ = (gl, shader);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_errors_and_helper_enums() {
+        assert_eq!(enum_name(GL::INVALID_OPERATION), "INVALID_OPERATION");
+        assert_eq!(enum_name(GL::TEXTURE_2D), "TEXTURE_2D");
+        assert_eq!(enum_name(GL::DEPTH_COMPONENT24), "DEPTH_COMPONENT24");
+        // NONE, ZERO, POINTS and NO_ERROR
+        assert_eq!(enum_name(0), "0x0000");
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+use gloo::net::http::{Request, Response};
+
+#[derive(Debug, Clone)]
+pub struct FetchError {
+    url: String,
+    reason: String,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to load {}: {}", self.url, self.reason)
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl FetchError {
+    fn new(url: &str, reason: impl ToString) -> Self {
+        Self {
+            url: url.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Fetch the text file at `url`, failing on HTTP error statuses too
+pub async fn fetch_text(url: &str) -> Result<String, FetchError> {
+    let response = fetch(url).await?;
+    response
+        .text()
+        .await
+        .map_err(|err| FetchError::new(url, err))
+}
+
+/// Fetch the binary file at `url`, failing on HTTP error statuses too
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, FetchError> {
+    let response = fetch(url).await?;
+    response
+        .binary()
+        .await
+        .map_err(|err| FetchError::new(url, err))
+}
+
+async fn fetch(url: &str) -> Result<Response, FetchError> {
+    let response = Request::get(url)
+        .send()
+        .await
+        .map_err(|err| FetchError::new(url, err))?;
+    if !response.ok() {
+        return Err(FetchError::new(
+            url,
+            format!("{} {}", response.status(), response.status_text()),
+        ));
+    }
+    Ok(response)
+}
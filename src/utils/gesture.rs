@@ -0,0 +1,558 @@
+use std::f32::consts::PI;
+
+/// A pointer at (`x`, `y`) pixels at `time` milliseconds, as reported by the
+/// Pointer Events of a mouse, a pen or a finger
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerInput {
+    pub id: i32,
+    pub x: f32,
+    pub y: f32,
+    pub time: f64,
+}
+
+/// What the pointers did, the amounts being relative to the previous gesture of
+/// the same kind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// One pointer moved by (`dx`, `dy`) pixels
+    Drag { dx: f32, dy: f32 },
+    /// The middle of two pointers moved by (`dx`, `dy`) pixels
+    Pan { dx: f32, dy: f32 },
+    /// The distance between two pointers was multiplied by `scale`
+    Pinch { scale: f32 },
+    /// The line between two pointers turned by `angle` radians, clockwise on the
+    /// screen
+    Rotate { angle: f32 },
+    /// Two quick taps at about (`x`, `y`)
+    DoubleTap { x: f32, y: f32 },
+}
+
+/// How far the pointers move before a gesture is recognized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureConfig {
+    /// Pixels a pointer moves before a drag or a pan starts, shorter moves being
+    /// taps
+    pub drag_threshold: f32,
+    /// Relative change of the distance between two pointers before a pinch
+    /// starts
+    pub pinch_threshold: f32,
+    /// Radians turned by two pointers before a rotation starts
+    pub rotate_threshold: f32,
+    /// Milliseconds between the ends of the two taps of a double tap
+    pub double_tap_interval: f64,
+    /// Pixels between the two taps of a double tap
+    pub double_tap_distance: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            drag_threshold: 4.0,
+            pinch_threshold: 0.05,
+            rotate_threshold: 10f32.to_radians(),
+            double_tap_interval: 300.0,
+            double_tap_distance: 20.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pointer {
+    id: i32,
+    x: f32,
+    y: f32,
+}
+
+impl Pointer {
+    fn new(input: PointerInput) -> Self {
+        Self {
+            id: input.id,
+            x: input.x,
+            y: input.y,
+        }
+    }
+}
+
+/// The middle, length and direction of the line between two pointers
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    x: f32,
+    y: f32,
+    distance: f32,
+    angle: f32,
+}
+
+impl Span {
+    fn new(a: Pointer, b: Pointer) -> Self {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        Self {
+            x: (a.x + b.x) / 2.0,
+            y: (a.y + b.y) / 2.0,
+            distance: dx.hypot(dy),
+            angle: dy.atan2(dx),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Idle,
+    /// One pointer down, which has not moved far enough to drag from where it
+    /// went down
+    Pressed {
+        start: Pointer,
+    },
+    Dragging {
+        pointer: Pointer,
+    },
+    /// Two pointers down
+    Multi {
+        pointers: [Pointer; 2],
+        start: Span,
+        last: Span,
+        panning: bool,
+        pinching: bool,
+        rotating: bool,
+    },
+}
+
+/// Turn the Pointer Events of an element into gestures
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    state: State,
+    // The position and time of the last single tap
+    last_tap: Option<(f32, f32, f64)>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new(GestureConfig::default())
+    }
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            state: State::Idle,
+            last_tap: None,
+        }
+    }
+
+    /// Whether a pointer is down
+    pub fn is_active(&self) -> bool {
+        !matches!(self.state, State::Idle)
+    }
+
+    /// Forget the pointers down and the last tap
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+        self.last_tap = None;
+    }
+
+    /// Returns whether the pointer is tracked, the third and next pointers being
+    /// ignored
+    pub fn onpointerdown(&mut self, input: PointerInput) -> bool {
+        let pointer = Pointer::new(input);
+        self.state = match self.state {
+            State::Idle => State::Pressed { start: pointer },
+            State::Pressed { start: first } | State::Dragging { pointer: first }
+                if first.id != input.id =>
+            {
+                let span = Span::new(first, pointer);
+                State::Multi {
+                    pointers: [first, pointer],
+                    start: span,
+                    last: span,
+                    panning: false,
+                    pinching: false,
+                    rotating: false,
+                }
+            }
+            _ => return false,
+        };
+        true
+    }
+
+    pub fn onpointermove(&mut self, input: PointerInput) -> Vec<Gesture> {
+        let config = self.config;
+        let mut gestures = Vec::new();
+        match &mut self.state {
+            State::Pressed { start } if start.id == input.id => {
+                let (dx, dy) = (input.x - start.x, input.y - start.y);
+                if dx.hypot(dy) >= config.drag_threshold {
+                    // The drag includes the move before the threshold
+                    gestures.push(Gesture::Drag { dx, dy });
+                    self.state = State::Dragging {
+                        pointer: Pointer::new(input),
+                    };
+                }
+            }
+            State::Dragging { pointer } if pointer.id == input.id => {
+                let (dx, dy) = (input.x - pointer.x, input.y - pointer.y);
+                *pointer = Pointer::new(input);
+                if dx != 0.0 || dy != 0.0 {
+                    gestures.push(Gesture::Drag { dx, dy });
+                }
+            }
+            State::Multi {
+                pointers,
+                start,
+                last,
+                panning,
+                pinching,
+                rotating,
+            } => {
+                let pointer = match pointers.iter_mut().find(|p| p.id == input.id) {
+                    Some(pointer) => pointer,
+                    None => return gestures,
+                };
+                *pointer = Pointer::new(input);
+                let span = Span::new(pointers[0], pointers[1]);
+
+                // The first gesture of a kind includes the moves before its
+                // threshold
+                let (dx, dy) = (span.x - start.x, span.y - start.y);
+                let from = if *panning { *last } else { *start };
+                *panning |= dx.hypot(dy) >= config.drag_threshold;
+                if *panning && (span.x != from.x || span.y != from.y) {
+                    gestures.push(Gesture::Pan {
+                        dx: span.x - from.x,
+                        dy: span.y - from.y,
+                    });
+                }
+
+                // Two pointers at the same place cannot pinch
+                let from = if *pinching { *last } else { *start };
+                if start.distance > 0.0 && from.distance > 0.0 {
+                    *pinching |=
+                        (span.distance / start.distance - 1.0).abs() >= config.pinch_threshold;
+                    if *pinching && span.distance != from.distance {
+                        gestures.push(Gesture::Pinch {
+                            scale: span.distance / from.distance,
+                        });
+                    }
+                }
+
+                let from = if *rotating { *last } else { *start };
+                *rotating |= wrap_angle(span.angle - start.angle).abs() >= config.rotate_threshold;
+                let angle = wrap_angle(span.angle - from.angle);
+                if *rotating && angle != 0.0 {
+                    gestures.push(Gesture::Rotate { angle });
+                }
+
+                *last = span;
+            }
+            _ => {}
+        }
+        gestures
+    }
+
+    pub fn onpointerup(&mut self, input: PointerInput) -> Vec<Gesture> {
+        let mut gestures = self.onpointermove(input);
+        match self.state {
+            State::Pressed { start } if start.id == input.id => {
+                self.state = State::Idle;
+                let config = &self.config;
+                let double = self.last_tap.take().filter(|&(x, y, time)| {
+                    input.time - time <= config.double_tap_interval
+                        && (input.x - x).hypot(input.y - y) <= config.double_tap_distance
+                });
+                match double {
+                    Some(_) => gestures.push(Gesture::DoubleTap {
+                        x: input.x,
+                        y: input.y,
+                    }),
+                    None => self.last_tap = Some((input.x, input.y, input.time)),
+                }
+            }
+            State::Dragging { pointer } if pointer.id == input.id => {
+                self.state = State::Idle;
+            }
+            _ => self.remove_pointer(input.id),
+        }
+        gestures
+    }
+
+    /// Drop the pointer without a last move, the browser having taken it over
+    pub fn onpointercancel(&mut self, id: i32) {
+        match self.state {
+            State::Pressed { start: pointer } | State::Dragging { pointer } if pointer.id == id => {
+                self.state = State::Idle;
+            }
+            _ => self.remove_pointer(id),
+        }
+    }
+
+    /// The other pointer of a multi-pointer gesture drags from where it is, and
+    /// does not tap
+    fn remove_pointer(&mut self, id: i32) {
+        if let State::Multi { pointers, .. } = self.state {
+            if let Some(index) = pointers.iter().position(|p| p.id == id) {
+                self.state = State::Dragging {
+                    pointer: pointers[1 - index],
+                };
+                self.last_tap = None;
+            }
+        }
+    }
+}
+
+/// The same angle in (-PI, PI]
+fn wrap_angle(angle: f32) -> f32 {
+    let angle = angle.rem_euclid(2.0 * PI);
+    if angle > PI {
+        angle - 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(id: i32, x: f32, y: f32, time: f64) -> PointerInput {
+        PointerInput { id, x, y, time }
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1.0e-4, "{} != {}", a, b);
+    }
+
+    fn tap(recognizer: &mut GestureRecognizer, x: f32, y: f32, time: f64) -> Vec<Gesture> {
+        assert!(recognizer.onpointerdown(at(1, x, y, time)));
+        recognizer.onpointerup(at(1, x + 1.0, y, time + 50.0))
+    }
+
+    // Put two pointers down and move them to the given positions
+    fn two_pointers(
+        recognizer: &mut GestureRecognizer,
+        from: [(f32, f32); 2],
+        to: [(f32, f32); 2],
+        steps: usize,
+    ) -> Vec<Gesture> {
+        assert!(recognizer.onpointerdown(at(1, from[0].0, from[0].1, 0.0)));
+        assert!(recognizer.onpointerdown(at(2, from[1].0, from[1].1, 0.0)));
+        let mut gestures = Vec::new();
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            for (id, (a, b)) in [(1, (from[0], to[0])), (2, (from[1], to[1]))] {
+                let (x, y) = (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+                gestures.extend(recognizer.onpointermove(at(id, x, y, 0.0)));
+            }
+        }
+        gestures
+    }
+
+    #[test]
+    fn small_moves_are_taps() {
+        let mut recognizer = GestureRecognizer::default();
+        assert!(recognizer.onpointerdown(at(1, 10.0, 10.0, 0.0)));
+        assert!(recognizer.onpointermove(at(1, 12.0, 11.0, 10.0)).is_empty());
+        assert!(recognizer.onpointerup(at(1, 12.0, 11.0, 20.0)).is_empty());
+        assert!(!recognizer.is_active());
+    }
+
+    #[test]
+    fn drag_starts_with_the_move_before_the_threshold() {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.onpointerdown(at(1, 10.0, 10.0, 0.0));
+        assert!(recognizer.onpointermove(at(1, 12.0, 10.0, 10.0)).is_empty());
+        assert_eq!(
+            recognizer.onpointermove(at(1, 16.0, 13.0, 20.0)),
+            vec![Gesture::Drag { dx: 6.0, dy: 3.0 }]
+        );
+        assert_eq!(
+            recognizer.onpointermove(at(1, 15.0, 13.0, 30.0)),
+            vec![Gesture::Drag { dx: -1.0, dy: 0.0 }]
+        );
+        assert_eq!(
+            recognizer.onpointerup(at(1, 15.0, 15.0, 40.0)),
+            vec![Gesture::Drag { dx: 0.0, dy: 2.0 }]
+        );
+        // A drag is not a tap
+        assert!(tap(&mut recognizer, 15.0, 15.0, 100.0).is_empty());
+    }
+
+    #[test]
+    fn other_pointers_are_ignored() {
+        let mut recognizer = GestureRecognizer::default();
+        recognizer.onpointerdown(at(1, 0.0, 0.0, 0.0));
+        assert!(recognizer.onpointermove(at(7, 50.0, 0.0, 0.0)).is_empty());
+        assert!(recognizer.onpointerup(at(7, 50.0, 0.0, 0.0)).is_empty());
+        recognizer.onpointerdown(at(2, 100.0, 0.0, 0.0));
+        assert!(!recognizer.onpointerdown(at(3, 200.0, 0.0, 0.0)));
+        assert!(recognizer.onpointermove(at(3, 300.0, 0.0, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn double_tap_needs_close_and_quick_taps() {
+        let config = GestureConfig::default();
+        let mut recognizer = GestureRecognizer::new(config);
+        assert!(tap(&mut recognizer, 100.0, 100.0, 0.0).is_empty());
+        assert_eq!(
+            tap(&mut recognizer, 105.0, 100.0, 200.0),
+            vec![Gesture::DoubleTap { x: 106.0, y: 100.0 }]
+        );
+        // A third tap starts a new double tap
+        assert!(tap(&mut recognizer, 105.0, 100.0, 400.0).is_empty());
+
+        // Too slow
+        recognizer.reset();
+        assert!(tap(&mut recognizer, 100.0, 100.0, 0.0).is_empty());
+        assert!(tap(
+            &mut recognizer,
+            100.0,
+            100.0,
+            config.double_tap_interval + 100.0
+        )
+        .is_empty());
+
+        // Too far
+        recognizer.reset();
+        assert!(tap(&mut recognizer, 100.0, 100.0, 0.0).is_empty());
+        assert!(tap(&mut recognizer, 150.0, 100.0, 100.0).is_empty());
+    }
+
+    #[test]
+    fn spreading_pointers_pinch() {
+        let mut recognizer = GestureRecognizer::default();
+        let gestures = two_pointers(
+            &mut recognizer,
+            [(90.0, 100.0), (110.0, 100.0)],
+            [(60.0, 100.0), (140.0, 100.0)],
+            10,
+        );
+        assert!(!gestures.is_empty());
+        let mut scale = 1.0;
+        for gesture in gestures {
+            match gesture {
+                Gesture::Pinch { scale: s } => scale *= s,
+                gesture => panic!("unexpected {:?}", gesture),
+            }
+        }
+        // The distance went from 20 to 80 pixels
+        assert_close(scale, 4.0);
+    }
+
+    #[test]
+    fn twisting_pointers_rotate() {
+        let mut recognizer = GestureRecognizer::default();
+        // A quarter turn, clockwise on the screen, in 30 steps
+        let mut gestures = two_pointers(
+            &mut recognizer,
+            [(50.0, 100.0), (150.0, 100.0)],
+            [(50.0, 100.0), (150.0, 100.0)],
+            0,
+        );
+        for step in 1..=30 {
+            let angle = (step as f32 * 3.0).to_radians();
+            let (dx, dy) = (50.0 * angle.cos(), 50.0 * angle.sin());
+            gestures.extend(recognizer.onpointermove(at(1, 100.0 - dx, 100.0 - dy, 0.0)));
+            gestures.extend(recognizer.onpointermove(at(2, 100.0 + dx, 100.0 + dy, 0.0)));
+        }
+        let mut angle = 0.0;
+        for gesture in gestures {
+            match gesture {
+                Gesture::Rotate { angle: a } => angle += a,
+                gesture => panic!("unexpected {:?}", gesture),
+            }
+        }
+        assert_close(angle, PI / 2.0);
+    }
+
+    #[test]
+    fn moving_pointers_together_pan() {
+        let mut recognizer = GestureRecognizer::default();
+        let gestures = two_pointers(
+            &mut recognizer,
+            [(0.0, 0.0), (100.0, 0.0)],
+            [(30.0, 40.0), (130.0, 40.0)],
+            20,
+        );
+        let (mut x, mut y) = (0.0, 0.0);
+        for gesture in gestures {
+            match gesture {
+                Gesture::Pan { dx, dy } => {
+                    x += dx;
+                    y += dy;
+                }
+                gesture => panic!("unexpected {:?}", gesture),
+            }
+        }
+        assert_close(x, 30.0);
+        assert_close(y, 40.0);
+    }
+
+    #[test]
+    fn thresholds_are_configurable() {
+        let config = GestureConfig {
+            pinch_threshold: 0.5,
+            rotate_threshold: PI,
+            ..GestureConfig::default()
+        };
+        let mut recognizer = GestureRecognizer::new(config);
+        // 20 to 25 pixels apart is not enough to pinch
+        let gestures = two_pointers(
+            &mut recognizer,
+            [(90.0, 100.0), (110.0, 100.0)],
+            [(88.0, 100.0), (113.0, 100.0)],
+            4,
+        );
+        assert!(gestures.is_empty(), "{:?}", gestures);
+
+        let mut recognizer = GestureRecognizer::new(GestureConfig {
+            drag_threshold: 50.0,
+            ..config
+        });
+        recognizer.onpointerdown(at(1, 0.0, 0.0, 0.0));
+        assert!(recognizer.onpointermove(at(1, 30.0, 0.0, 0.0)).is_empty());
+        assert_eq!(
+            recognizer.onpointermove(at(1, 60.0, 0.0, 0.0)),
+            vec![Gesture::Drag { dx: 60.0, dy: 0.0 }]
+        );
+    }
+
+    #[test]
+    fn remaining_pointer_drags_from_where_it_is() {
+        let mut recognizer = GestureRecognizer::default();
+        two_pointers(
+            &mut recognizer,
+            [(0.0, 0.0), (100.0, 0.0)],
+            [(0.0, 0.0), (200.0, 0.0)],
+            2,
+        );
+        recognizer.onpointerup(at(1, 0.0, 0.0, 0.0));
+        assert!(recognizer.is_active());
+        assert_eq!(
+            recognizer.onpointermove(at(2, 205.0, 0.0, 0.0)),
+            vec![Gesture::Drag { dx: 5.0, dy: 0.0 }]
+        );
+        // Lifting it is not a tap
+        assert!(recognizer.onpointerup(at(2, 205.0, 0.0, 0.0)).is_empty());
+        assert!(!recognizer.is_active());
+        assert!(tap(&mut recognizer, 205.0, 0.0, 10.0).is_empty());
+    }
+
+    #[test]
+    fn cancel_ends_without_a_tap() {
+        let mut recognizer = GestureRecognizer::default();
+        assert!(tap(&mut recognizer, 10.0, 10.0, 0.0).is_empty());
+        recognizer.onpointerdown(at(1, 10.0, 10.0, 100.0));
+        recognizer.onpointercancel(1);
+        assert!(!recognizer.is_active());
+        assert!(recognizer
+            .onpointermove(at(1, 50.0, 10.0, 150.0))
+            .is_empty());
+
+        recognizer.onpointerdown(at(1, 0.0, 0.0, 0.0));
+        recognizer.onpointerdown(at(2, 10.0, 0.0, 0.0));
+        recognizer.onpointercancel(2);
+        assert_eq!(
+            recognizer.onpointermove(at(1, 0.0, 1.0, 0.0)),
+            vec![Gesture::Drag { dx: 0.0, dy: 1.0 }]
+        );
+    }
+}
@@ -0,0 +1,919 @@
+use std::fmt;
+
+use base64::Engine;
+use nalgebra as na;
+use serde::Deserialize;
+
+use super::accessor::{self, Accessor, AccessorError, BufferView};
+use super::Node;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GltfError(String);
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+impl From<AccessorError> for GltfError {
+    fn from(err: AccessorError) -> Self {
+        Self(err.to_string())
+    }
+}
+
+fn error<T>(message: impl Into<String>) -> Result<T, GltfError> {
+    Err(GltfError(message.into()))
+}
+
+// `index` if the document has that many objects of `kind`
+fn check_index(index: usize, count: usize, kind: &str) -> Result<usize, GltfError> {
+    if index < count {
+        Ok(index)
+    } else {
+        error(format!("{} {} does not exist", kind, index))
+    }
+}
+
+// Magic numbers of the binary container
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const CHUNK_JSON: u32 = 0x4e4f_534a;
+const CHUNK_BIN: u32 = 0x004e_4942;
+
+// Primitive modes and sampler parameters, same values as the GL enums
+const TRIANGLES: u32 = 4;
+const LINEAR: u32 = 0x2601;
+const LINEAR_MIPMAP_LINEAR: u32 = 0x2703;
+const REPEAT: u32 = 0x2901;
+
+/// The JSON part of a glTF asset, the parts of it this loader uses
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Document {
+    pub scene: Option<usize>,
+    pub scenes: Vec<SceneDesc>,
+    pub nodes: Vec<NodeDesc>,
+    pub meshes: Vec<MeshDesc>,
+    pub accessors: Vec<Accessor>,
+    pub buffer_views: Vec<BufferView>,
+    pub buffers: Vec<BufferDesc>,
+    pub materials: Vec<MaterialDesc>,
+    pub textures: Vec<TextureDesc>,
+    pub images: Vec<ImageDesc>,
+    pub samplers: Vec<SamplerDesc>,
+    pub cameras: Vec<CameraDesc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SceneDesc {
+    pub nodes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NodeDesc {
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+    pub camera: Option<usize>,
+    pub matrix: Option<[f32; 16]>,
+    pub translation: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 4]>,
+    pub scale: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MeshDesc {
+    pub primitives: Vec<PrimitiveDesc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PrimitiveDesc {
+    pub attributes: std::collections::HashMap<String, usize>,
+    pub indices: Option<usize>,
+    pub material: Option<usize>,
+    pub mode: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferDesc {
+    pub uri: Option<String>,
+    pub byte_length: usize,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaterialDesc {
+    pub pbr_metallic_roughness: Option<PbrDesc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PbrDesc {
+    pub base_color_factor: Option<[f32; 4]>,
+    pub base_color_texture: Option<TextureRef>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TextureRef {
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TextureDesc {
+    pub source: Option<usize>,
+    pub sampler: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageDesc {
+    pub uri: Option<String>,
+    pub buffer_view: Option<usize>,
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplerDesc {
+    pub mag_filter: Option<u32>,
+    pub min_filter: Option<u32>,
+    pub wrap_s: Option<u32>,
+    pub wrap_t: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CameraDesc {
+    pub perspective: Option<PerspectiveDesc>,
+    pub orthographic: Option<OrthographicDesc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerspectiveDesc {
+    pub yfov: f32,
+    pub aspect_ratio: Option<f32>,
+    pub znear: f32,
+    pub zfar: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OrthographicDesc {
+    pub xmag: f32,
+    pub ymag: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
+
+/// Where the content of a buffer comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferSource {
+    /// The binary chunk of the `.glb` file
+    Glb,
+    /// Decoded from a `data:` URI
+    Data(Vec<u8>),
+    /// A file to fetch, relative to the asset
+    Uri(String),
+}
+
+impl Document {
+    /// Parse a `.gltf` file, or a `.glb` file and its binary chunk
+    pub fn parse(bytes: &[u8]) -> Result<(Self, Option<Vec<u8>>), GltfError> {
+        if bytes.len() >= 4 && read_u32(bytes, 0) == GLB_MAGIC {
+            let (json, bin) = split_glb(bytes)?;
+            Ok((Self::parse_json(json)?, bin.map(<[u8]>::to_vec)))
+        } else {
+            Ok((Self::parse_json(bytes)?, None))
+        }
+    }
+
+    fn parse_json(json: &[u8]) -> Result<Self, GltfError> {
+        serde_json::from_slice(json).map_err(|err| GltfError(format!("invalid glTF JSON: {}", err)))
+    }
+
+    pub fn buffer_source(&self, index: usize) -> Result<BufferSource, GltfError> {
+        let buffer = match self.buffers.get(index) {
+            Some(buffer) => buffer,
+            None => return error(format!("buffer {} does not exist", index)),
+        };
+        match buffer.uri.as_deref() {
+            None => Ok(BufferSource::Glb),
+            Some(uri) if uri.starts_with("data:") => Ok(BufferSource::Data(decode_data_uri(uri)?)),
+            Some(uri) => Ok(BufferSource::Uri(uri.to_string())),
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Split a `.glb` file into its JSON chunk and its optional binary chunk
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), GltfError> {
+    if bytes.len() < 12 {
+        return error("GLB header is truncated");
+    }
+    let version = read_u32(bytes, 4);
+    if version != 2 {
+        return error(format!("unsupported GLB version {}", version));
+    }
+    let length = read_u32(bytes, 8) as usize;
+    if length > bytes.len() {
+        return error(format!(
+            "GLB length is {} but the file has {} bytes",
+            length,
+            bytes.len()
+        ));
+    }
+
+    let mut chunks = Vec::new();
+    let mut offset = 12;
+    while offset < length {
+        if offset + 8 > length {
+            return error("GLB chunk header is truncated");
+        }
+        let chunk_length = read_u32(bytes, offset) as usize;
+        let chunk_type = read_u32(bytes, offset + 4);
+        let start = offset + 8;
+        let data = match bytes[..length].get(start..start + chunk_length) {
+            Some(data) => data,
+            None => return error("GLB chunk is truncated"),
+        };
+        chunks.push((chunk_type, data));
+        offset = start + chunk_length;
+    }
+
+    match chunks[..] {
+        [(CHUNK_JSON, json)] => Ok((json, None)),
+        [(CHUNK_JSON, json), (CHUNK_BIN, bin), ..] => Ok((json, Some(bin))),
+        _ => error("GLB must start with a JSON chunk"),
+    }
+}
+
+fn decode_data_uri(uri: &str) -> Result<Vec<u8>, GltfError> {
+    let data = match uri.split_once(";base64,") {
+        Some((_, data)) => data,
+        None => return error("only base64 data URIs are supported"),
+    };
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|err| GltfError(format!("invalid base64 data URI: {}", err)))
+}
+
+/// A glTF scene decoded into plain vertex data, ready to upload
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    pub meshes: Vec<Vec<Primitive>>,
+    pub materials: Vec<Material>,
+    pub textures: Vec<Texture>,
+    pub images: Vec<Image>,
+    pub cameras: Vec<Camera>,
+    /// The node hierarchy of the default scene
+    pub scene: Node<NodeContent>,
+}
+
+/// Vertex data of a mesh primitive
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Primitive {
+    pub positions: Vec<f32>,
+    /// Computed from the triangles when the asset has none
+    pub normals: Vec<f32>,
+    /// `TEXCOORD_0`, zeros when the asset has none
+    pub tex_coords: Vec<f32>,
+    pub indices: Option<Vec<u32>>,
+    pub material: Option<usize>,
+    /// `GL::TRIANGLES`, `GL::LINES`, ...
+    pub mode: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub base_color_factor: [f32; 4],
+    /// Index in `Model::textures`
+    pub base_color_texture: Option<usize>,
+}
+
+impl Default for Material {
+    /// The glTF default material, white
+    fn default() -> Self {
+        Self {
+            base_color_factor: [1.0; 4],
+            base_color_texture: None,
+        }
+    }
+}
+
+/// An image and how it is sampled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Texture {
+    /// Index in `Model::images`
+    pub image: usize,
+    pub mag_filter: u32,
+    pub min_filter: u32,
+    pub wrap_s: u32,
+    pub wrap_t: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Image {
+    /// A file relative to the asset, or a data URI
+    Uri(String),
+    /// An image stored in a buffer
+    Bytes { data: Vec<u8>, mime_type: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Camera {
+    Perspective {
+        yfov: f32,
+        aspect_ratio: Option<f32>,
+        znear: f32,
+        zfar: Option<f32>,
+    },
+    Orthographic {
+        xmag: f32,
+        ymag: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+impl Camera {
+    /// The projection matrix, `aspect` is used when the camera does not set its
+    /// aspect ratio
+    pub fn projection(&self, aspect: f32) -> na::Matrix4<f32> {
+        match *self {
+            Camera::Perspective {
+                yfov,
+                aspect_ratio,
+                znear,
+                zfar,
+            } => {
+                let aspect = aspect_ratio.unwrap_or(aspect);
+                // glTF uses an infinite projection when zfar is not set
+                let zfar = zfar.unwrap_or(znear * 1.0e6);
+                na::Matrix4::new_perspective(aspect, yfov, znear, zfar)
+            }
+            Camera::Orthographic {
+                xmag,
+                ymag,
+                znear,
+                zfar,
+            } => na::Matrix4::new_orthographic(-xmag, xmag, -ymag, ymag, znear, zfar),
+        }
+    }
+}
+
+/// What a node of `Model::scene` holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeContent {
+    /// Index in `Model::meshes`
+    pub mesh: Option<usize>,
+    /// Index in `Model::cameras`
+    pub camera: Option<usize>,
+}
+
+impl Model {
+    /// Decode the default scene of `document`, `buffers` holding the content of
+    /// each of its buffers
+    pub fn new(document: &Document, buffers: &[Vec<u8>]) -> Result<Self, GltfError> {
+        for (index, buffer) in document.buffers.iter().enumerate() {
+            match buffers.get(index) {
+                Some(data) if data.len() >= buffer.byte_length => {}
+                _ => return error(format!("buffer {} is missing or too short", index)),
+            }
+        }
+
+        let meshes = document
+            .meshes
+            .iter()
+            .map(|mesh| {
+                mesh.primitives
+                    .iter()
+                    .map(|primitive| decode_primitive(document, buffers, primitive))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        let materials = document
+            .materials
+            .iter()
+            .map(|material| {
+                let pbr = material.pbr_metallic_roughness.clone().unwrap_or_default();
+                Ok(Material {
+                    base_color_factor: pbr.base_color_factor.unwrap_or([1.0; 4]),
+                    base_color_texture: pbr
+                        .base_color_texture
+                        .map(|texture| {
+                            check_index(texture.index, document.textures.len(), "texture")
+                        })
+                        .transpose()?,
+                })
+            })
+            .collect::<Result<_, GltfError>>()?;
+
+        let textures = document
+            .textures
+            .iter()
+            .map(|texture| {
+                let image = match texture.source {
+                    Some(image) => check_index(image, document.images.len(), "image")?,
+                    None => return error("textures without an image are not supported"),
+                };
+                let sampler =
+                    match texture.sampler {
+                        Some(index) => document.samplers.get(index).cloned().ok_or_else(|| {
+                            GltfError(format!("sampler {} does not exist", index))
+                        })?,
+                        None => SamplerDesc::default(),
+                    };
+                Ok(Texture {
+                    image,
+                    mag_filter: sampler.mag_filter.unwrap_or(LINEAR),
+                    min_filter: sampler.min_filter.unwrap_or(LINEAR_MIPMAP_LINEAR),
+                    wrap_s: sampler.wrap_s.unwrap_or(REPEAT),
+                    wrap_t: sampler.wrap_t.unwrap_or(REPEAT),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let images = document
+            .images
+            .iter()
+            .map(|image| match (&image.uri, image.buffer_view) {
+                (Some(uri), _) => Ok(Image::Uri(uri.clone())),
+                (None, Some(view)) => {
+                    let view = document
+                        .buffer_views
+                        .get(view)
+                        .ok_or_else(|| GltfError(format!("buffer view {} does not exist", view)))?;
+                    let buffer = buffers.get(view.buffer).ok_or_else(|| {
+                        GltfError(format!("buffer {} does not exist", view.buffer))
+                    })?;
+                    let data = view
+                        .byte_offset
+                        .checked_add(view.byte_length)
+                        .and_then(|end| buffer.get(view.byte_offset..end))
+                        .ok_or_else(|| GltfError("image is out of its buffer".to_string()))?;
+                    Ok(Image::Bytes {
+                        data: data.to_vec(),
+                        mime_type: image.mime_type.clone().unwrap_or_default(),
+                    })
+                }
+                (None, None) => error("image without a uri or a buffer view"),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let cameras = document
+            .cameras
+            .iter()
+            .map(|camera| match (&camera.perspective, &camera.orthographic) {
+                (Some(p), _) => Ok(Camera::Perspective {
+                    yfov: p.yfov,
+                    aspect_ratio: p.aspect_ratio,
+                    znear: p.znear,
+                    zfar: p.zfar,
+                }),
+                (None, Some(o)) => Ok(Camera::Orthographic {
+                    xmag: o.xmag,
+                    ymag: o.ymag,
+                    znear: o.znear,
+                    zfar: o.zfar,
+                }),
+                (None, None) => error("camera without a projection"),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut scene = Node::new(na::Matrix4::identity());
+        let roots = match document.scene.or(if document.scenes.is_empty() {
+            None
+        } else {
+            Some(0)
+        }) {
+            Some(index) => match document.scenes.get(index) {
+                Some(scene) => scene.nodes.clone(),
+                None => return error(format!("scene {} does not exist", index)),
+            },
+            // Without scenes, the nodes that are nobody's child
+            None => (0..document.nodes.len())
+                .filter(|i| !document.nodes.iter().any(|n| n.children.contains(i)))
+                .collect(),
+        };
+        for root in roots {
+            scene
+                .children
+                .push(build_node(document, root, &mut Vec::new())?);
+        }
+
+        Ok(Self {
+            meshes,
+            materials,
+            textures,
+            images,
+            cameras,
+            scene,
+        })
+    }
+
+    /// The world space bounding box of the meshes of the scene
+    pub fn bounds(&self) -> Option<(na::Point3<f32>, na::Point3<f32>)> {
+        let mut bounds: Option<(na::Point3<f32>, na::Point3<f32>)> = None;
+        let mut stack = super::MatrixStack::default();
+        let _ = self
+            .scene
+            .traverse::<(), _>(&mut stack, &mut |content, matrix| {
+                let primitives = content.mesh.and_then(|mesh| self.meshes.get(mesh));
+                for primitive in primitives.into_iter().flatten() {
+                    for position in primitive.positions.chunks_exact(3) {
+                        let point = matrix.transform_point(&na::Point3::new(
+                            position[0],
+                            position[1],
+                            position[2],
+                        ));
+                        bounds = Some(match bounds {
+                            Some((min, max)) => (min.inf(&point), max.sup(&point)),
+                            None => (point, point),
+                        });
+                    }
+                }
+                Ok(())
+            });
+        bounds
+    }
+}
+
+fn build_node(
+    document: &Document,
+    index: usize,
+    ancestors: &mut Vec<usize>,
+) -> Result<Node<NodeContent>, GltfError> {
+    let desc = match document.nodes.get(index) {
+        Some(desc) => desc,
+        None => return error(format!("node {} does not exist", index)),
+    };
+    if ancestors.contains(&index) {
+        return error(format!("node {} is its own ancestor", index));
+    }
+
+    let transform = match desc.matrix {
+        // Column-major, as nalgebra stores it
+        Some(matrix) => na::Matrix4::from_column_slice(&matrix),
+        None => {
+            let [tx, ty, tz] = desc.translation.unwrap_or([0.0; 3]);
+            let [x, y, z, w] = desc.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+            let [sx, sy, sz] = desc.scale.unwrap_or([1.0; 3]);
+            na::Matrix4::new_translation(&na::Vector3::new(tx, ty, tz))
+                * na::UnitQuaternion::new_normalize(na::Quaternion::new(w, x, y, z))
+                    .to_homogeneous()
+                * na::Matrix4::new_nonuniform_scaling(&na::Vector3::new(sx, sy, sz))
+        }
+    };
+
+    let mut node = Node::new(transform);
+    if desc.mesh.is_some() || desc.camera.is_some() {
+        node.drawable = Some(NodeContent {
+            mesh: desc
+                .mesh
+                .map(|mesh| check_index(mesh, document.meshes.len(), "mesh"))
+                .transpose()?,
+            camera: desc
+                .camera
+                .map(|camera| check_index(camera, document.cameras.len(), "camera"))
+                .transpose()?,
+        });
+    }
+    ancestors.push(index);
+    for &child in &desc.children {
+        node.children.push(build_node(document, child, ancestors)?);
+    }
+    ancestors.pop();
+    Ok(node)
+}
+
+fn decode_primitive(
+    document: &Document,
+    buffers: &[Vec<u8>],
+    primitive: &PrimitiveDesc,
+) -> Result<Primitive, GltfError> {
+    let accessor = |index: usize| {
+        document
+            .accessors
+            .get(index)
+            .ok_or_else(|| GltfError(format!("accessor {} does not exist", index)))
+    };
+    let attribute = |name: &str| -> Result<Option<Vec<f32>>, GltfError> {
+        match primitive.attributes.get(name) {
+            Some(&index) => Ok(Some(accessor::read_floats(
+                accessor(index)?,
+                &document.buffer_views,
+                buffers,
+            )?)),
+            None => Ok(None),
+        }
+    };
+
+    let positions = match attribute("POSITION")? {
+        Some(positions) => positions,
+        None => return error("primitive without POSITION"),
+    };
+    let indices = match primitive.indices {
+        Some(index) => Some(accessor::read_indices(
+            accessor(index)?,
+            &document.buffer_views,
+            buffers,
+        )?),
+        None => None,
+    };
+    let vertex_count = positions.len() / 3;
+    if let Some(&max) = indices.iter().flatten().max() {
+        if max as usize >= vertex_count {
+            return error(format!(
+                "index {} is out of range ({} vertices)",
+                max, vertex_count
+            ));
+        }
+    }
+
+    let mode = primitive.mode.unwrap_or(TRIANGLES);
+    let normals = match attribute("NORMAL")? {
+        Some(normals) => normals,
+        None if mode == TRIANGLES => compute_normals(&positions, indices.as_deref()),
+        None => vec![0.0; positions.len()],
+    };
+    let tex_coords = attribute("TEXCOORD_0")?.unwrap_or_else(|| vec![0.0; vertex_count * 2]);
+
+    Ok(Primitive {
+        positions,
+        normals,
+        tex_coords,
+        indices,
+        material: primitive
+            .material
+            .map(|material| check_index(material, document.materials.len(), "material"))
+            .transpose()?,
+        mode,
+    })
+}
+
+/// Smooth normals of a triangle list, the average of the normals of the
+/// triangles around each vertex weighted by their area
+fn compute_normals(positions: &[f32], indices: Option<&[u32]>) -> Vec<f32> {
+    let vertex_count = positions.len() / 3;
+    let point =
+        |i: usize| na::Point3::new(positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]);
+    let triangles: Vec<[usize; 3]> = match indices {
+        Some(indices) => indices
+            .chunks_exact(3)
+            .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+            .collect(),
+        None => (0..vertex_count / 3)
+            .map(|t| [t * 3, t * 3 + 1, t * 3 + 2])
+            .collect(),
+    };
+
+    let mut normals = vec![na::Vector3::zeros(); vertex_count];
+    for [a, b, c] in triangles {
+        // Twice the area times the unit normal
+        let normal = (point(b) - point(a)).cross(&(point(c) - point(a)));
+        for vertex in [a, b, c] {
+            normals[vertex] += normal;
+        }
+    }
+    normals
+        .iter()
+        .flat_map(|normal| {
+            let normal = normal.try_normalize(f32::EPSILON).unwrap_or_default();
+            [normal.x, normal.y, normal.z]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A triangle in the z = 0 plane with u16 indices
+    fn triangle_buffer() -> Vec<u8> {
+        let mut buffer: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        buffer.extend([0u16, 1, 2].iter().flat_map(|value| value.to_le_bytes()));
+        // Pad to a multiple of 4 bytes
+        buffer.extend([0, 0]);
+        buffer
+    }
+
+    fn triangle_json(buffer_uri: &str) -> String {
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [
+                    {{ "children": [1], "translation": [0, 0, -5] }},
+                    {{ "mesh": 0, "scale": [2, 2, 2], "camera": 0 }}
+                ],
+                "meshes": [{{ "primitives": [{{
+                    "attributes": {{ "POSITION": 0 }},
+                    "indices": 1,
+                    "material": 0
+                }}] }}],
+                "materials": [{{ "pbrMetallicRoughness": {{
+                    "baseColorFactor": [1, 0, 0, 1],
+                    "baseColorTexture": {{ "index": 0 }}
+                }} }}],
+                "textures": [{{ "source": 0 }}],
+                "images": [{{ "uri": "sky.jpg" }}],
+                "cameras": [{{ "perspective": {{ "yfov": 0.8, "znear": 0.1 }} }}],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "buffers": [{{ {}"byteLength": 44 }}]
+            }}"#,
+            buffer_uri
+        )
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        // Chunks are padded to 4 bytes, with spaces for JSON
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+        let mut bytes = Vec::new();
+        for value in [GLB_MAGIC, 2, length as u32, json.len() as u32, CHUNK_JSON] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(&json);
+        for value in [bin.len() as u32, CHUNK_BIN] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(bin);
+        bytes
+    }
+
+    #[test]
+    fn embedded_base64_buffer() {
+        let data = base64::engine::general_purpose::STANDARD.encode(triangle_buffer());
+        let json = triangle_json(&format!(
+            r#""uri": "data:application/octet-stream;base64,{}", "#,
+            data
+        ));
+        let (document, bin) = Document::parse(json.as_bytes()).unwrap();
+        assert_eq!(bin, None);
+        assert_eq!(
+            document.buffer_source(0).unwrap(),
+            BufferSource::Data(triangle_buffer())
+        );
+
+        let model = Model::new(&document, &[triangle_buffer()]).unwrap();
+        let primitive = &model.meshes[0][0];
+        assert_eq!(primitive.indices.as_deref(), Some(&[0, 1, 2][..]));
+        assert_eq!(primitive.positions.len(), 9);
+        // Computed normals face +z, texture coordinates default to zeros
+        assert_eq!(&primitive.normals[..3], [0.0, 0.0, 1.0]);
+        assert_eq!(primitive.tex_coords, [0.0; 6]);
+        assert_eq!(primitive.mode, TRIANGLES);
+        assert_eq!(model.materials[0].base_color_factor, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(model.materials[0].base_color_texture, Some(0));
+        assert_eq!(model.textures[0].min_filter, LINEAR_MIPMAP_LINEAR);
+        assert_eq!(model.images[0], Image::Uri("sky.jpg".to_string()));
+    }
+
+    #[test]
+    fn sibling_buffer() {
+        let json = triangle_json(r#""uri": "triangle.bin", "#);
+        let (document, _) = Document::parse(json.as_bytes()).unwrap();
+        assert_eq!(
+            document.buffer_source(0).unwrap(),
+            BufferSource::Uri("triangle.bin".to_string())
+        );
+    }
+
+    #[test]
+    fn binary_container() {
+        let bytes = glb(&triangle_json(""), &triangle_buffer());
+        let (document, bin) = Document::parse(&bytes).unwrap();
+        assert_eq!(document.buffer_source(0).unwrap(), BufferSource::Glb);
+        assert_eq!(bin.as_deref(), Some(&triangle_buffer()[..]));
+        assert!(Model::new(&document, &[bin.unwrap()]).is_ok());
+    }
+
+    #[test]
+    fn malformed_binary_container() {
+        let bytes = glb(&triangle_json(""), &triangle_buffer());
+        let error = |bytes: &[u8]| Document::parse(bytes).unwrap_err().to_string();
+
+        assert_eq!(error(&bytes[..8]), "GLB header is truncated");
+        let mut version = bytes.clone();
+        version[4] = 1;
+        assert_eq!(error(&version), "unsupported GLB version 1");
+        assert!(error(&bytes[..bytes.len() - 4]).starts_with("GLB length is"));
+        let mut chunk = bytes.clone();
+        chunk[12] = 0xff;
+        assert_eq!(error(&chunk), "GLB chunk is truncated");
+    }
+
+    #[test]
+    fn node_hierarchy_and_cameras() {
+        let json = triangle_json(r#""uri": "triangle.bin", "#);
+        let (document, _) = Document::parse(json.as_bytes()).unwrap();
+        let model = Model::new(&document, &[triangle_buffer()]).unwrap();
+
+        let mut visited = Vec::new();
+        let mut stack = crate::utils::MatrixStack::default();
+        model
+            .scene
+            .traverse::<(), _>(&mut stack, &mut |content, matrix| {
+                visited.push((*content, *matrix));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(visited.len(), 1);
+        let (content, matrix) = visited[0];
+        assert_eq!(content.mesh, Some(0));
+        assert_eq!(content.camera, Some(0));
+        // The child scale is applied before the parent translation
+        let corner = matrix.transform_point(&na::Point3::new(1.0, 0.0, 0.0));
+        assert_eq!(corner, na::Point3::new(2.0, 0.0, -5.0));
+
+        let (min, max) = model.bounds().unwrap();
+        assert_eq!(min, na::Point3::new(0.0, 0.0, -5.0));
+        assert_eq!(max, na::Point3::new(2.0, 2.0, -5.0));
+
+        assert!(matches!(
+            model.cameras[0],
+            Camera::Perspective {
+                zfar: None,
+                aspect_ratio: None,
+                ..
+            }
+        ));
+        let projection = model.cameras[0].projection(1.0);
+        assert!(projection.iter().all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let json = triangle_json(r#""uri": "triangle.bin", "#);
+        let (mut document, _) = Document::parse(json.as_bytes()).unwrap();
+        let error = |document: &Document, buffers: &[Vec<u8>]| {
+            Model::new(document, buffers).unwrap_err().to_string()
+        };
+
+        assert_eq!(error(&document, &[]), "buffer 0 is missing or too short");
+
+        document.nodes[1].children.push(0);
+        assert_eq!(
+            error(&document, &[triangle_buffer()]),
+            "node 0 is its own ancestor"
+        );
+        document.nodes[1].children.clear();
+
+        document.nodes[1].mesh = Some(1);
+        assert_eq!(
+            error(&document, &[triangle_buffer()]),
+            "mesh 1 does not exist"
+        );
+        document.nodes[1].mesh = Some(0);
+        document.nodes[1].camera = Some(2);
+        assert_eq!(
+            error(&document, &[triangle_buffer()]),
+            "camera 2 does not exist"
+        );
+        document.nodes[1].camera = Some(0);
+        document.textures[0].source = Some(1);
+        assert_eq!(
+            error(&document, &[triangle_buffer()]),
+            "image 1 does not exist"
+        );
+        document.textures[0].source = Some(0);
+        assert!(document.buffer_source(1).is_err());
+
+        document.accessors[1].count = 4;
+        assert_eq!(
+            error(&document, &[triangle_buffer()]),
+            "accessor needs 8 bytes but buffer view 1 has 6"
+        );
+
+        assert!(Document::parse(b"{ \"nodes\": 3 }")
+            .unwrap_err()
+            .to_string()
+            .starts_with("invalid glTF JSON"));
+    }
+}
@@ -0,0 +1,95 @@
+use gloo::events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, PointerEvent};
+
+use super::gesture::PointerInput;
+
+/// Listen to the `event_type` events of the whole document until the
+/// returned listener is dropped.
+///
+/// The handlers set by others on the document are left alone, and the
+/// callback may prevent the default action of the event. The events aimed at
+/// text fields, like the keys typed in the shader editor, are left to them.
+pub fn listen_document<E, F>(event_type: &'static str, mut callback: F) -> EventListener
+where
+    E: JsCast,
+    F: FnMut(&E) + 'static,
+{
+    EventListener::new_with_options(
+        &gloo::utils::document(),
+        event_type,
+        EventListenerOptions::enable_prevent_default(),
+        move |event| {
+            if !is_editable(event.target()) {
+                callback(event.unchecked_ref());
+            }
+        },
+    )
+}
+
+fn is_editable(target: Option<web_sys::EventTarget>) -> bool {
+    target
+        .and_then(|target| target.dyn_into::<Element>().ok())
+        .is_some_and(|element| {
+            element
+                .matches("input, textarea, select, [contenteditable]")
+                .unwrap_or(false)
+        })
+}
+
+impl From<&PointerEvent> for PointerInput {
+    fn from(event: &PointerEvent) -> Self {
+        Self {
+            id: event.pointer_id(),
+            x: event.client_x() as f32,
+            y: event.client_y() as f32,
+            time: event.time_stamp(),
+        }
+    }
+}
+
+/// The pointers captured by an element, released when dropped
+#[derive(Debug, Default)]
+pub struct PointerCapture {
+    target: Option<Element>,
+    pointers: Vec<i32>,
+}
+
+impl PointerCapture {
+    /// Capture the pointer by the element the event listener is attached to
+    pub fn capture(&mut self, event: &PointerEvent) {
+        let target = match event
+            .current_target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+        {
+            Some(target) => target,
+            None => return,
+        };
+        if target.set_pointer_capture(event.pointer_id()).is_ok() {
+            self.pointers.push(event.pointer_id());
+            self.target = Some(target);
+        }
+    }
+
+    /// Forget the pointer, the browser releasing it when it goes up
+    pub fn forget(&mut self, pointer_id: i32) {
+        self.pointers.retain(|&id| id != pointer_id);
+    }
+
+    pub fn release_all(&mut self) {
+        if let Some(target) = self.target.as_ref() {
+            for id in self.pointers.drain(..) {
+                if target.has_pointer_capture(id) {
+                    let _ = target.release_pointer_capture(id);
+                }
+            }
+        }
+        self.pointers.clear();
+    }
+}
+
+impl Drop for PointerCapture {
+    fn drop(&mut self) {
+        self.release_all();
+    }
+}
@@ -0,0 +1,513 @@
+use std::f32::consts::PI;
+
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::WebGl2RenderingContext as GL;
+
+use super::{ShaderProgram, VertexArray, VertexLayout};
+
+/// Indexed triangles generated for a common shape, with one position, normal,
+/// texture coordinate and tangent per vertex
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<f32>,
+    pub normals: Vec<f32>,
+    pub tex_coords: Vec<f32>,
+    /// The direction of increasing u in `xyz`, and in `w` the sign to apply to
+    /// `cross(normal, tangent)` to get the direction of increasing v
+    pub tangents: Vec<f32>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// A cube of side `size` centered at the origin, its faces in the order of
+    /// the book: front, right, up, left, down and back, 4 vertices each
+    pub fn cube(size: f32) -> Self {
+        let half = size / 2.0;
+        // The normal of each face and the directions of its u and v axes
+        let faces = [
+            (na::Vector3::z(), na::Vector3::x(), na::Vector3::y()),
+            (na::Vector3::x(), -na::Vector3::z(), na::Vector3::y()),
+            (na::Vector3::y(), na::Vector3::x(), -na::Vector3::z()),
+            (-na::Vector3::x(), na::Vector3::z(), na::Vector3::y()),
+            (-na::Vector3::y(), na::Vector3::x(), na::Vector3::z()),
+            (-na::Vector3::z(), -na::Vector3::x(), na::Vector3::y()),
+        ];
+
+        let mut builder = Builder::default();
+        for (normal, u, v) in faces {
+            let first = builder.vertex_count();
+            // Counterclockwise from the top-right corner, as in the book
+            for (s, t) in [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)] {
+                let position = (normal + u * s + v * t) * half;
+                builder.vertex(position, normal, [(s + 1.0) / 2.0, (t + 1.0) / 2.0]);
+            }
+            builder.quad(first, first + 1, first + 2, first + 3);
+        }
+        builder.build()
+    }
+
+    /// A UV sphere of `radius` centered at the origin, with `segments` slices
+    /// around the y axis and `rings` stacks from pole to pole
+    pub fn sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let (segments, rings) = (segments.max(3), rings.max(2));
+        let mut builder = Builder::default();
+        for j in 0..=rings {
+            // From the north pole down
+            let theta = j as f32 * PI / rings as f32;
+            for i in 0..=segments {
+                let phi = i as f32 * 2.0 * PI / segments as f32;
+                let normal = na::Vector3::new(
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                    theta.sin() * phi.cos(),
+                );
+                let tex_coord = [i as f32 / segments as f32, 1.0 - j as f32 / rings as f32];
+                builder.vertex(normal * radius, normal, tex_coord);
+            }
+        }
+
+        let row = segments + 1;
+        for j in 0..rings {
+            for i in 0..segments {
+                let (a, d) = (j * row + i, j * row + i + 1);
+                let (b, c) = (a + row, d + row);
+                // The triangles touching a pole in a single point are left out
+                if j != rings - 1 {
+                    builder.triangle(a, b, c);
+                }
+                if j != 0 {
+                    builder.triangle(a, c, d);
+                }
+            }
+        }
+        builder.build()
+    }
+
+    /// A closed cylinder of `radius` and `height` centered at the origin, along
+    /// the y axis, with `segments` slices
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        frustum(radius, radius, height, segments)
+    }
+
+    /// A cone of `radius` and `height` centered at the origin, its apex up the y
+    /// axis, with `segments` slices
+    pub fn cone(radius: f32, height: f32, segments: u32) -> Self {
+        frustum(radius, 0.0, height, segments)
+    }
+
+    /// A torus around the y axis, `radius` being the distance from its center to
+    /// the center of the tube of radius `tube`
+    pub fn torus(radius: f32, tube: f32, segments: u32, sides: u32) -> Self {
+        let (segments, sides) = (segments.max(3), sides.max(3));
+        let mut builder = Builder::default();
+        for i in 0..=segments {
+            let phi = i as f32 * 2.0 * PI / segments as f32;
+            let outward = na::Vector3::new(phi.sin(), 0.0, phi.cos());
+            for j in 0..=sides {
+                let theta = j as f32 * 2.0 * PI / sides as f32;
+                let normal = outward * theta.cos() + na::Vector3::y() * theta.sin();
+                let tex_coord = [i as f32 / segments as f32, j as f32 / sides as f32];
+                builder.vertex(outward * radius + normal * tube, normal, tex_coord);
+            }
+        }
+
+        let row = sides + 1;
+        for i in 0..segments {
+            for j in 0..sides {
+                let a = i * row + j;
+                builder.quad(a, a + row, a + row + 1, a + 1);
+            }
+        }
+        builder.build()
+    }
+
+    /// A plane of `width` along x and `depth` along z, facing up the y axis,
+    /// divided in `x_divisions` by `z_divisions` quads
+    pub fn plane(width: f32, depth: f32, x_divisions: u32, z_divisions: u32) -> Self {
+        let (x_divisions, z_divisions) = (x_divisions.max(1), z_divisions.max(1));
+        let mut builder = Builder::default();
+        for j in 0..=z_divisions {
+            let t = j as f32 / z_divisions as f32;
+            for i in 0..=x_divisions {
+                let s = i as f32 / x_divisions as f32;
+                let position = na::Vector3::new((s - 0.5) * width, 0.0, (t - 0.5) * depth);
+                builder.vertex(position, na::Vector3::y(), [s, 1.0 - t]);
+            }
+        }
+
+        let row = x_divisions + 1;
+        for j in 0..z_divisions {
+            for i in 0..x_divisions {
+                let a = j * row + i;
+                builder.quad(a, a + row, a + row + 1, a + 1);
+            }
+        }
+        builder.build()
+    }
+
+    /// The same triangles, each with its own 3 vertices having the normal of the
+    /// triangle
+    pub fn with_flat_normals(&self) -> Self {
+        let mut builder = Builder::default();
+        for triangle in self.indices.chunks_exact(3) {
+            let point = |i: u32| vector3(&self.positions, i as usize);
+            let [a, b, c] = [point(triangle[0]), point(triangle[1]), point(triangle[2])];
+            let normal = (b - a)
+                .cross(&(c - a))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(na::Vector3::y);
+
+            let first = builder.vertex_count();
+            for &index in triangle {
+                let index = index as usize;
+                let tex_coord = [self.tex_coords[index * 2], self.tex_coords[index * 2 + 1]];
+                builder.vertex(point(index as u32), normal, tex_coord);
+            }
+            builder.triangle(first, first + 1, first + 2);
+        }
+        builder.build()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 3
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// The layouts of the attributes `a_Position`, `a_Normal`, `a_TexCoord` and
+    /// `a_Tangent` with their data, those a program doesn't use being skipped
+    pub fn buffers(&self) -> [(VertexLayout, &[f32]); 4] {
+        let layout = |name, size| {
+            VertexLayout::new()
+                .attribute(name, size, GL::FLOAT, false)
+                .skip_inactive()
+        };
+        [
+            (layout("a_Position", 3), &self.positions),
+            (layout("a_Normal", 3), &self.normals),
+            (layout("a_TexCoord", 2), &self.tex_coords),
+            (layout("a_Tangent", 4), &self.tangents),
+        ]
+    }
+
+    /// Upload the mesh into a vertex array object, for the attributes of
+    /// `Mesh::buffers` that `program` uses
+    pub fn vertex_array(&self, gl: &GL, program: &ShaderProgram) -> Result<VertexArray, JsError> {
+        let mut vertex_array = VertexArray::new(gl)?;
+        for (layout, data) in self.buffers() {
+            vertex_array = vertex_array.buffer(program, &layout, data)?;
+        }
+        vertex_array.indices(&self.indices)
+    }
+}
+
+/// The side of a cone cut parallel to its base, closed by flat caps, the top one
+/// being left out when `top_radius` is 0
+fn frustum(bottom_radius: f32, top_radius: f32, height: f32, segments: u32) -> Mesh {
+    let segments = segments.max(3);
+    let half = height / 2.0;
+    let angle = |i: u32| i as f32 * 2.0 * PI / segments as f32;
+    let mut builder = Builder::default();
+
+    // The side, the bottom and top vertices of each slice
+    for i in 0..=segments {
+        let phi = angle(i);
+        let outward = na::Vector3::new(phi.sin(), 0.0, phi.cos());
+        // Perpendicular to the slope of the side
+        let normal =
+            (outward * height + na::Vector3::y() * (bottom_radius - top_radius)).normalize();
+        let u = i as f32 / segments as f32;
+        builder.vertex(
+            outward * bottom_radius - na::Vector3::y() * half,
+            normal,
+            [u, 0.0],
+        );
+        builder.vertex(
+            outward * top_radius + na::Vector3::y() * half,
+            normal,
+            [u, 1.0],
+        );
+    }
+    for i in 0..segments {
+        let (b0, t0, b1, t1) = (i * 2, i * 2 + 1, i * 2 + 2, i * 2 + 3);
+        builder.triangle(b0, b1, t1);
+        // The apex of a cone is a single point
+        if top_radius > 0.0 {
+            builder.triangle(b0, t1, t0);
+        }
+    }
+
+    let mut cap = |radius: f32, y: f32| {
+        let normal = na::Vector3::y() * y.signum();
+        let center = builder.vertex_count();
+        builder.vertex(na::Vector3::new(0.0, y, 0.0), normal, [0.5, 0.5]);
+        for i in 0..segments {
+            let (sin, cos) = angle(i).sin_cos();
+            let position = na::Vector3::new(sin * radius, y, cos * radius);
+            builder.vertex(
+                position,
+                normal,
+                [0.5 + sin / 2.0, 0.5 - cos / 2.0 * y.signum()],
+            );
+        }
+        for i in 0..segments {
+            let (a, b) = (center + 1 + i, center + 1 + (i + 1) % segments);
+            // Counterclockwise seen from outside
+            if y > 0.0 {
+                builder.triangle(center, a, b);
+            } else {
+                builder.triangle(center, b, a);
+            }
+        }
+    };
+    cap(bottom_radius, -half);
+    if top_radius > 0.0 {
+        cap(top_radius, half);
+    }
+    builder.build()
+}
+
+fn vector3(data: &[f32], index: usize) -> na::Vector3<f32> {
+    na::Vector3::new(data[index * 3], data[index * 3 + 1], data[index * 3 + 2])
+}
+
+/// Vertices and triangles of a mesh being generated, the tangents being computed
+/// at the end
+#[derive(Default)]
+struct Builder {
+    mesh: Mesh,
+}
+
+impl Builder {
+    fn vertex_count(&self) -> u32 {
+        (self.mesh.positions.len() / 3) as u32
+    }
+
+    fn vertex(
+        &mut self,
+        position: na::Vector3<f32>,
+        normal: na::Vector3<f32>,
+        tex_coord: [f32; 2],
+    ) {
+        self.mesh.positions.extend_from_slice(position.as_slice());
+        self.mesh.normals.extend_from_slice(normal.as_slice());
+        self.mesh.tex_coords.extend_from_slice(&tex_coord);
+    }
+
+    /// A counterclockwise triangle
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.mesh.indices.extend_from_slice(&[a, b, c]);
+    }
+
+    /// A counterclockwise quad, split along its `a`-`c` diagonal
+    fn quad(&mut self, a: u32, b: u32, c: u32, d: u32) {
+        self.triangle(a, b, c);
+        self.triangle(a, c, d);
+    }
+
+    fn build(mut self) -> Mesh {
+        self.mesh.tangents = compute_tangents(&self.mesh);
+        self.mesh
+    }
+}
+
+/// Tangents following the texture coordinates, averaged over the triangles
+/// around each vertex and made perpendicular to its normal
+fn compute_tangents(mesh: &Mesh) -> Vec<f32> {
+    let vertex_count = mesh.vertex_count();
+    let tex_coord = |i: usize| na::Vector2::new(mesh.tex_coords[i * 2], mesh.tex_coords[i * 2 + 1]);
+    let mut tangents = vec![na::Vector3::zeros(); vertex_count];
+    let mut bitangents = vec![na::Vector3::zeros(); vertex_count];
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+        let (e1, e2) = (
+            vector3(&mesh.positions, b) - vector3(&mesh.positions, a),
+            vector3(&mesh.positions, c) - vector3(&mesh.positions, a),
+        );
+        let (d1, d2) = (tex_coord(b) - tex_coord(a), tex_coord(c) - tex_coord(a));
+        let det = d1.x * d2.y - d2.x * d1.y;
+        if det.abs() <= f32::EPSILON {
+            continue;
+        }
+        let tangent = (e1 * d2.y - e2 * d1.y) / det;
+        let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+        for vertex in [a, b, c] {
+            tangents[vertex] += tangent;
+            bitangents[vertex] += bitangent;
+        }
+    }
+
+    let mut result = Vec::with_capacity(vertex_count * 4);
+    for (vertex, (tangent, bitangent)) in tangents.iter().zip(&bitangents).enumerate() {
+        let normal = vector3(&mesh.normals, vertex);
+        // Gram-Schmidt, with any perpendicular direction where the texture
+        // coordinates do not give one
+        let tangent = (tangent - normal * normal.dot(tangent))
+            .try_normalize(1.0e-6)
+            .unwrap_or_else(|| {
+                let axis = if normal.x.abs() < 0.9 {
+                    na::Vector3::x()
+                } else {
+                    na::Vector3::y()
+                };
+                (axis - normal * normal.dot(&axis)).normalize()
+            });
+        let handedness = if normal.cross(&tangent).dot(bitangent) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        result.extend_from_slice(&[tangent.x, tangent.y, tangent.z, handedness]);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shapes() -> Vec<(&'static str, Mesh)> {
+        vec![
+            ("cube", Mesh::cube(2.0)),
+            ("sphere", Mesh::sphere(1.5, 16, 8)),
+            ("cylinder", Mesh::cylinder(1.0, 2.0, 12)),
+            ("cone", Mesh::cone(1.0, 2.0, 12)),
+            ("torus", Mesh::torus(1.0, 0.25, 16, 8)),
+            ("plane", Mesh::plane(4.0, 2.0, 4, 2)),
+        ]
+    }
+
+    fn assert_unit_length(name: &str, data: &[f32], components: usize) {
+        for vector in data.chunks_exact(components) {
+            let length = vector[..3].iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!((length - 1.0).abs() < 1.0e-5, "{}: {:?}", name, vector);
+        }
+    }
+
+    #[test]
+    fn vertex_and_index_counts() {
+        let counts: Vec<_> = shapes()
+            .iter()
+            .map(|(name, mesh)| (*name, mesh.vertex_count(), mesh.triangle_count()))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                ("cube", 24, 12),
+                // 9 rings of 17 vertices, no triangles in a point at the poles
+                ("sphere", 9 * 17, 16 * (2 * 8 - 2)),
+                // The side and 2 caps with their center
+                ("cylinder", 13 * 2 + 2 * 13, 12 * 2 + 2 * 12),
+                ("cone", 13 * 2 + 13, 12 + 12),
+                ("torus", 17 * 9, 16 * 8 * 2),
+                ("plane", 5 * 3, 4 * 2 * 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn attributes_have_one_value_per_vertex() {
+        for (name, mesh) in shapes() {
+            let n = mesh.vertex_count();
+            assert_eq!(mesh.positions.len(), n * 3, "{}", name);
+            assert_eq!(mesh.normals.len(), n * 3, "{}", name);
+            assert_eq!(mesh.tex_coords.len(), n * 2, "{}", name);
+            assert_eq!(mesh.tangents.len(), n * 4, "{}", name);
+            assert!(mesh.indices.iter().all(|&i| (i as usize) < n), "{}", name);
+        }
+    }
+
+    #[test]
+    fn normals_and_tangents_are_unit_length() {
+        for (name, mesh) in shapes() {
+            assert_unit_length(name, &mesh.normals, 3);
+            assert_unit_length(name, &mesh.tangents, 4);
+            assert_unit_length(name, &mesh.with_flat_normals().normals, 3);
+        }
+    }
+
+    #[test]
+    fn tangents_are_perpendicular_to_normals() {
+        for (name, mesh) in shapes() {
+            for (normal, tangent) in mesh.normals.chunks_exact(3).zip(mesh.tangents.chunks(4)) {
+                let dot: f32 = normal.iter().zip(tangent).map(|(n, t)| n * t).sum();
+                assert!(dot.abs() < 1.0e-5, "{}: {:?} {:?}", name, normal, tangent);
+                assert!(tangent[3] == 1.0 || tangent[3] == -1.0, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn triangles_face_outward() {
+        // The face normal of every counterclockwise triangle agrees with the
+        // normals of its vertices
+        for (name, mesh) in shapes() {
+            for triangle in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [triangle[0], triangle[1], triangle[2]]
+                    .map(|i| vector3(&mesh.positions, i as usize));
+                let face = (b - a).cross(&(c - a));
+                assert!(face.norm() > 0.0, "{}: degenerate {:?}", name, triangle);
+                for &vertex in triangle {
+                    let normal = vector3(&mesh.normals, vertex as usize);
+                    assert!(face.dot(&normal) > 0.0, "{}: {:?}", name, triangle);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cube_matches_the_book() {
+        let mesh = Mesh::cube(2.0);
+        // v0-v1-v2-v3 front
+        assert_eq!(
+            mesh.positions[..12],
+            [1.0, 1.0, 1.0, -1.0, 1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, 1.0]
+        );
+        assert_eq!(mesh.indices[..6], [0, 1, 2, 0, 2, 3]);
+        // The faces follow the book's order
+        let normals: Vec<_> = mesh
+            .normals
+            .chunks(12)
+            .map(|face| face[..3].to_vec())
+            .collect();
+        assert_eq!(
+            normals,
+            [
+                [0.0, 0.0, 1.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [-1.0, 0.0, 0.0],
+                [0.0, -1.0, 0.0],
+                [0.0, 0.0, -1.0],
+            ]
+        );
+        // The front tangent is the direction of increasing u
+        assert_eq!(mesh.tangents[..4], [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn sphere_vertices_are_on_the_surface() {
+        let mesh = Mesh::sphere(2.0, 10, 6);
+        for (position, normal) in mesh.positions.chunks(3).zip(mesh.normals.chunks(3)) {
+            for axis in 0..3 {
+                assert!((position[axis] - normal[axis] * 2.0).abs() < 1.0e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn flat_normals_unweld_the_triangles() {
+        let mesh = Mesh::sphere(1.0, 8, 4);
+        let flat = mesh.with_flat_normals();
+        assert_eq!(flat.triangle_count(), mesh.triangle_count());
+        assert_eq!(flat.vertex_count(), mesh.triangle_count() * 3);
+        for triangle in flat.normals.chunks(9) {
+            assert_eq!(triangle[..3], triangle[3..6]);
+            assert_eq!(triangle[..3], triangle[6..]);
+        }
+    }
+}
@@ -1,7 +1,53 @@
+mod accessor;
+mod animation;
+pub mod backend;
+mod camera;
+mod camera_control;
+pub mod capture;
 mod context;
-mod draging;
+pub mod context_loss;
+mod debug;
+mod fetch;
+mod gesture;
+pub mod gltf;
+mod input;
+mod mesh;
+mod mouse;
+mod obj;
+mod picking;
+mod preprocessor;
+mod program;
+#[cfg(test)]
+pub mod recording_backend;
+mod render_target;
+mod scene;
+mod shader_error;
+pub mod shader_registry;
+mod shadow_map;
+#[cfg(test)]
+pub mod software;
+mod texture;
+mod vertex_layout;
 
+pub use self::animation::{use_animation_loop, AnimationLoop, Frame};
+pub use self::backend::{RenderBackend, WebGl2Backend};
+pub use self::camera::{Camera, Projection};
+pub use self::camera_control::{CameraControl, ControlMode};
 pub use self::context::{
-    use_2d_canvas_render, use_webgl2_canvas_render, WebGl2RenderingContextExt,
+    error_message, use_2d_canvas_render, use_webgl2_canvas_render, webgl2_context,
+    WebGl2RenderingContextExt,
 };
-pub use self::draging::Draging;
+pub use self::fetch::{fetch_bytes, fetch_text};
+pub use self::input::listen_document;
+pub use self::mesh::Mesh;
+pub use self::mouse::canvas_position;
+pub use self::obj::{Material, ObjModel};
+pub use self::picking::Picker;
+pub use self::preprocessor::{GlslVersion, Preprocessor};
+pub use self::program::ShaderProgram;
+pub use self::render_target::RenderTarget;
+pub use self::scene::{MatrixStack, Node};
+pub use self::shader_error::{Diagnostic, ShaderError, ShaderStage};
+pub use self::shadow_map::{ShadowFormat, ShadowMap};
+pub use self::texture::{load_image, load_image_bytes, LoadImageError, Texture};
+pub use self::vertex_layout::{VertexArray, VertexLayout};
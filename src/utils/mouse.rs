@@ -0,0 +1,27 @@
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlCanvasElement, MouseEvent};
+
+/// Position of the mouse in pixels of the drawing buffer, relative to the
+/// top-left corner of the element the event listener is attached to
+pub fn canvas_position(event: &MouseEvent) -> (f32, f32) {
+    let x = event.client_x() as f32;
+    let y = event.client_y() as f32;
+    let target = event
+        .current_target()
+        .and_then(|target| target.dyn_into::<Element>().ok());
+    let target = match target {
+        Some(target) => target,
+        None => return (x, y),
+    };
+    let rect = target.get_bounding_client_rect();
+    let (x, y) = (x - rect.left() as f32, y - rect.top() as f32);
+
+    // The canvas may be displayed at a different size than its drawing buffer
+    match target.dyn_into::<HtmlCanvasElement>() {
+        Ok(canvas) if rect.width() > 0.0 && rect.height() > 0.0 => (
+            x * canvas.width() as f32 / rect.width() as f32,
+            y * canvas.height() as f32 / rect.height() as f32,
+        ),
+        _ => (x, y),
+    }
+}
@@ -0,0 +1,632 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use nalgebra as na;
+use web_sys::WebGl2RenderingContext as GL;
+
+use super::VertexLayout;
+
+/// An error in an OBJ or MTL file, with the line it was found on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjError {
+    line: usize,
+    message: String,
+}
+
+impl ObjError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// A range of `ObjModel::indices` sharing a group name and a material
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    /// Name given by the last `g` or `o` statement, empty before any
+    pub name: String,
+    /// Name given by the last `usemtl` statement
+    pub material: Option<String>,
+    /// Index of the first index of the range
+    pub first: usize,
+    /// Number of indices, a multiple of 3
+    pub count: usize,
+}
+
+/// A triangle mesh read from a Wavefront OBJ file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjModel {
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+    pub groups: Vec<Group>,
+    /// Files named by `mtllib` statements
+    pub material_libraries: Vec<String>,
+}
+
+// Where the normal of a vertex comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NormalSource {
+    Index(usize),
+    // The normal of the n-th face
+    Face(usize),
+}
+
+#[derive(Default)]
+struct ObjParser {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    tex_coords: Vec<[f32; 2]>,
+    vertex_indices: HashMap<(usize, Option<usize>, NormalSource), u32>,
+    face_count: usize,
+    group_name: String,
+    material: Option<String>,
+    model: ObjModel,
+}
+
+impl ObjModel {
+    /// Layout of `ObjModel::vertices`, with the attributes `a_Position`,
+    /// `a_Normal` and `a_TexCoord`
+    pub fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .attribute("a_Position", 3, GL::FLOAT, false)
+            .attribute("a_Normal", 3, GL::FLOAT, false)
+            .attribute("a_TexCoord", 2, GL::FLOAT, false)
+            .skip_inactive()
+    }
+
+    /// Parse the content of an OBJ file
+    pub fn parse(source: &str) -> Result<Self, ObjError> {
+        let mut parser = ObjParser::default();
+        for (number, line) in source.lines().enumerate() {
+            parser.parse_line(number + 1, line)?;
+        }
+        Ok(parser.model)
+    }
+}
+
+impl ObjParser {
+    fn parse_line(&mut self, line: usize, text: &str) -> Result<(), ObjError> {
+        let text = strip_comment(text);
+        let mut words = text.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => return Ok(()),
+        };
+        let args: Vec<&str> = words.collect();
+        match keyword {
+            "v" => {
+                // The optional weight is ignored
+                let coords = parse_floats(line, keyword, &args, 3, 4)?;
+                self.positions.push([coords[0], coords[1], coords[2]]);
+            }
+            "vn" => {
+                let coords = parse_floats(line, keyword, &args, 3, 3)?;
+                self.normals.push([coords[0], coords[1], coords[2]]);
+            }
+            "vt" => {
+                let coords = parse_floats(line, keyword, &args, 1, 3)?;
+                self.tex_coords
+                    .push([coords[0], coords.get(1).copied().unwrap_or(0.0)]);
+            }
+            "f" => self.parse_face(line, &args)?,
+            "g" | "o" => self.group_name = args.join(" "),
+            "usemtl" => {
+                if args.is_empty() {
+                    return Err(ObjError::new(line, "usemtl without a material name"));
+                }
+                self.material = Some(args.join(" "));
+            }
+            "mtllib" => self
+                .model
+                .material_libraries
+                .extend(args.iter().map(|name| name.to_string())),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn parse_face(&mut self, line: usize, args: &[&str]) -> Result<(), ObjError> {
+        if args.len() < 3 {
+            return Err(ObjError::new(
+                line,
+                format!("a face needs at least 3 vertices, found {}", args.len()),
+            ));
+        }
+
+        let mut corners = Vec::with_capacity(args.len());
+        for arg in args {
+            if arg.split('/').count() > 3 {
+                return Err(ObjError::new(
+                    line,
+                    format!("invalid face vertex '{}'", arg),
+                ));
+            }
+            let mut parts = arg.split('/');
+            let position = parse_index(line, parts.next(), self.positions.len(), "vertex")?;
+            let tex_coord = match parts.next() {
+                None | Some("") => None,
+                Some(index) => Some(parse_index(
+                    line,
+                    Some(index),
+                    self.tex_coords.len(),
+                    "texture coordinate",
+                )?),
+            };
+            let normal = match parts.next() {
+                None | Some("") => None,
+                Some(index) => Some(parse_index(
+                    line,
+                    Some(index),
+                    self.normals.len(),
+                    "normal",
+                )?),
+            };
+            corners.push((position, tex_coord, normal));
+        }
+
+        let face = self.face_count;
+        self.face_count += 1;
+        let face_normal = {
+            let [a, b, c] = [0, 1, 2].map(|i| na_point(self.positions[corners[i].0]));
+            let normal = (b - a).cross(&(c - a));
+            let normal = normal.try_normalize(f32::EPSILON).unwrap_or_default();
+            [normal.x, normal.y, normal.z]
+        };
+
+        let indices: Vec<u32> = corners
+            .iter()
+            .map(|&(position, tex_coord, normal)| {
+                let source = normal.map_or(NormalSource::Face(face), NormalSource::Index);
+                self.vertex_index(position, tex_coord, source, face_normal)
+            })
+            .collect();
+
+        self.start_group_if_changed();
+        // Triangle fan around the first vertex
+        for i in 1..indices.len() - 1 {
+            self.model
+                .indices
+                .extend([indices[0], indices[i], indices[i + 1]]);
+        }
+        if let Some(group) = self.model.groups.last_mut() {
+            group.count = self.model.indices.len() - group.first;
+        }
+        Ok(())
+    }
+
+    fn vertex_index(
+        &mut self,
+        position: usize,
+        tex_coord: Option<usize>,
+        normal: NormalSource,
+        face_normal: [f32; 3],
+    ) -> u32 {
+        let key = (position, tex_coord, normal);
+        if let Some(&index) = self.vertex_indices.get(&key) {
+            return index;
+        }
+
+        let index = (self.model.vertices.len() / 8) as u32;
+        let normal = match normal {
+            NormalSource::Index(index) => self.normals[index],
+            NormalSource::Face(_) => face_normal,
+        };
+        let tex_coord = tex_coord.map_or([0.0, 0.0], |index| self.tex_coords[index]);
+        self.model.vertices.extend(self.positions[position]);
+        self.model.vertices.extend(normal);
+        self.model.vertices.extend(tex_coord);
+        self.vertex_indices.insert(key, index);
+        index
+    }
+
+    fn start_group_if_changed(&mut self) {
+        let changed =
+            self.model.groups.last().is_none_or(|group| {
+                group.name != self.group_name || group.material != self.material
+            });
+        if changed {
+            self.model.groups.push(Group {
+                name: self.group_name.clone(),
+                material: self.material.clone(),
+                first: self.model.indices.len(),
+                count: 0,
+            });
+        }
+    }
+}
+
+fn na_point([x, y, z]: [f32; 3]) -> na::Point3<f32> {
+    na::Point3::new(x, y, z)
+}
+
+/// Parse an OBJ index, 1-based or negative to count back from the last element,
+/// into a 0-based index below `count`
+fn parse_index(
+    line: usize,
+    arg: Option<&str>,
+    count: usize,
+    what: &str,
+) -> Result<usize, ObjError> {
+    let arg = arg.unwrap_or_default();
+    let index: i64 = arg
+        .parse()
+        .map_err(|_| ObjError::new(line, format!("invalid {} index '{}'", what, arg)))?;
+    let resolved = match index {
+        0 => None,
+        1.. => Some(index - 1),
+        _ => Some(count as i64 + index),
+    };
+    match resolved {
+        Some(resolved) if (0..count as i64).contains(&resolved) => Ok(resolved as usize),
+        _ => Err(ObjError::new(
+            line,
+            format!(
+                "{} index {} is out of range ({} defined)",
+                what, index, count
+            ),
+        )),
+    }
+}
+
+/// Cut the comment off `text`, `#` starting one at the start of the line or
+/// after whitespace only, so that names may contain it
+fn strip_comment(text: &str) -> &str {
+    let mut previous = None;
+    for (index, c) in text.char_indices() {
+        if c == '#' && previous.is_none_or(char::is_whitespace) {
+            return &text[..index];
+        }
+        previous = Some(c);
+    }
+    text
+}
+
+fn parse_floats(
+    line: usize,
+    keyword: &str,
+    args: &[&str],
+    min: usize,
+    max: usize,
+) -> Result<Vec<f32>, ObjError> {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max {
+            min.to_string()
+        } else {
+            format!("{} to {}", min, max)
+        };
+        return Err(ObjError::new(
+            line,
+            format!(
+                "{} expects {} numbers, found {}",
+                keyword,
+                expected,
+                args.len()
+            ),
+        ));
+    }
+    args.iter()
+        .map(|arg| {
+            arg.parse()
+                .map_err(|_| ObjError::new(line, format!("invalid number '{}'", arg)))
+        })
+        .collect()
+}
+
+/// A material of an MTL file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// `Ka`
+    pub ambient: [f32; 3],
+    /// `Kd`
+    pub diffuse: [f32; 3],
+    /// `Ks`
+    pub specular: [f32; 3],
+    /// `Ns`
+    pub shininess: f32,
+    /// `d`, or 1 - `Tr`
+    pub opacity: f32,
+}
+
+impl Default for Material {
+    /// A matte grey, also used for the properties a material does not set
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            ambient: [0.2; 3],
+            diffuse: [0.8; 3],
+            specular: [0.0; 3],
+            shininess: 0.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Material {
+    /// Parse the materials of an MTL file
+    pub fn parse_library(source: &str) -> Result<Vec<Material>, ObjError> {
+        let mut materials: Vec<Material> = Vec::new();
+        for (number, text) in source.lines().enumerate() {
+            let line = number + 1;
+            let text = strip_comment(text);
+            let mut words = text.split_whitespace();
+            let keyword = match words.next() {
+                Some(keyword) => keyword,
+                None => continue,
+            };
+            let args: Vec<&str> = words.collect();
+
+            if keyword == "newmtl" {
+                if args.is_empty() {
+                    return Err(ObjError::new(line, "newmtl without a material name"));
+                }
+                materials.push(Material {
+                    name: args.join(" "),
+                    ..Material::default()
+                });
+                continue;
+            }
+            if !matches!(keyword, "Ka" | "Kd" | "Ks" | "Ns" | "d" | "Tr") {
+                continue;
+            }
+            let material = materials.last_mut().ok_or_else(|| {
+                ObjError::new(line, format!("{} before any newmtl statement", keyword))
+            })?;
+            match keyword {
+                "Ka" => material.ambient = parse_color(line, keyword, &args)?,
+                "Kd" => material.diffuse = parse_color(line, keyword, &args)?,
+                "Ks" => material.specular = parse_color(line, keyword, &args)?,
+                "Ns" => material.shininess = parse_floats(line, keyword, &args, 1, 1)?[0],
+                "d" => material.opacity = parse_floats(line, keyword, &args, 1, 1)?[0],
+                "Tr" => material.opacity = 1.0 - parse_floats(line, keyword, &args, 1, 1)?[0],
+                _ => unreachable!(),
+            }
+        }
+        Ok(materials)
+    }
+}
+
+/// Parse `r g b`, or a single value for a grey
+fn parse_color(line: usize, keyword: &str, args: &[&str]) -> Result<[f32; 3], ObjError> {
+    if args.len() != 1 && args.len() != 3 {
+        return Err(ObjError::new(
+            line,
+            format!("{} expects 1 or 3 numbers, found {}", keyword, args.len()),
+        ));
+    }
+    let values = parse_floats(line, keyword, args, 1, 3)?;
+    match values[..] {
+        [r, g, b] => Ok([r, g, b]),
+        _ => Ok([values[0]; 3]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(model: &ObjModel, index: u32) -> &[f32] {
+        let start = index as usize * 8;
+        &model.vertices[start..start + 8]
+    }
+
+    fn error(source: &str) -> String {
+        ObjModel::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn triangle_with_all_attributes() {
+        let model = ObjModel::parse(
+            "# a triangle
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1
+",
+        )
+        .unwrap();
+        assert_eq!(model.indices, [0, 1, 2]);
+        assert_eq!(vertex(&model, 1), [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(ObjModel::layout().stride(), 8 * 4);
+    }
+
+    #[test]
+    fn polygons_are_split_into_fans_with_face_normals() {
+        let model = ObjModel::parse(
+            "v 0 0 0
+v 1 0 0
+v 1 0 -1
+v 0 0 -1
+f 1 2 3 4
+",
+        )
+        .unwrap();
+        assert_eq!(model.indices, [0, 1, 2, 0, 2, 3]);
+        // The quad faces up, texture coordinates default to 0
+        assert_eq!(vertex(&model, 3), [0.0, 0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn shared_vertices_are_reused() {
+        let model = ObjModel::parse(
+            "v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 1
+f 1//1 2//1 3//1
+f 1//1 3//1 4//1
+",
+        )
+        .unwrap();
+        assert_eq!(model.vertices.len(), 4 * 8);
+        assert_eq!(model.indices, [0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn negative_indices_count_back() {
+        let model = ObjModel::parse(
+            "v 0 0 0
+v 1 0 0
+v 0 1 0
+f -3 -2 -1
+v 5 5 5
+f 1 -1 3
+",
+        )
+        .unwrap();
+        assert_eq!(&vertex(&model, 4)[..3], [5.0, 5.0, 5.0]);
+    }
+
+    #[test]
+    fn groups_and_materials_split_the_indices() {
+        let model = ObjModel::parse(
+            "mtllib cube.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+g top
+usemtl red
+f 1 2 3
+f 1 2 3
+usemtl blue
+f 3 2 1
+",
+        )
+        .unwrap();
+        assert_eq!(model.material_libraries, ["cube.mtl"]);
+        let groups: Vec<_> = model
+            .groups
+            .iter()
+            .map(|g| (g.name.as_str(), g.material.as_deref(), g.first, g.count))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("", None, 0, 3),
+                ("top", Some("red"), 3, 6),
+                ("top", Some("blue"), 9, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_input_reports_the_line() {
+        assert_eq!(error("v 0 0 0\nv 1 x 0\n"), "line 2: invalid number 'x'");
+        assert_eq!(
+            error("v 0 0\n"),
+            "line 1: v expects 3 to 4 numbers, found 2"
+        );
+        assert_eq!(
+            error("v 0 0 0\nv 1 0 0\n\nf 1 2\n"),
+            "line 4: a face needs at least 3 vertices, found 2"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1 2 3\n"),
+            "line 2: vertex index 2 is out of range (1 defined)"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 0 1 1\n"),
+            "line 2: vertex index 0 is out of range (1 defined)"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1 -2 1\n"),
+            "line 2: vertex index -2 is out of range (1 defined)"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1/1 1 1\n"),
+            "line 2: texture coordinate index 1 is out of range (0 defined)"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1/a 1 1\n"),
+            "line 2: invalid texture coordinate index 'a'"
+        );
+        assert_eq!(
+            error("v 0 0 0\nf 1/1/1/1 1 1\n"),
+            "line 2: invalid face vertex '1/1/1/1'"
+        );
+    }
+
+    #[test]
+    fn unknown_statements_and_comments_are_ignored() {
+        let model = ObjModel::parse("s off\nl 1 2\n# f 1 2 3\nv 0 0 0 # origin\n").unwrap();
+        assert!(model.indices.is_empty());
+    }
+
+    #[test]
+    fn names_may_contain_hashes() {
+        let model = ObjModel::parse(
+            "mtllib part#2.mtl
+o body#1 # the body
+usemtl red#dark
+v 0 0 0
+f 1 1 1
+",
+        )
+        .unwrap();
+        assert_eq!(model.material_libraries, ["part#2.mtl"]);
+        assert_eq!(model.groups[0].name, "body#1");
+        assert_eq!(model.groups[0].material.as_deref(), Some("red#dark"));
+    }
+
+    #[test]
+    fn material_library() {
+        let materials = Material::parse_library(
+            "# two materials
+newmtl red
+Ka 0.1 0.1 0.1
+Kd 1 0 0
+Ks 0.5
+Ns 32
+illum 2
+
+newmtl glass
+Tr 0.75
+",
+        )
+        .unwrap();
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].name, "red");
+        assert_eq!(materials[0].diffuse, [1.0, 0.0, 0.0]);
+        assert_eq!(materials[0].specular, [0.5; 3]);
+        assert_eq!(materials[0].shininess, 32.0);
+        assert_eq!(materials[1].diffuse, [0.8; 3]);
+        assert_eq!(materials[1].opacity, 0.25);
+    }
+
+    #[test]
+    fn malformed_material_library() {
+        let error = |source| Material::parse_library(source).unwrap_err().to_string();
+        assert_eq!(
+            error("Kd 1 0 0\n"),
+            "line 1: Kd before any newmtl statement"
+        );
+        assert_eq!(
+            error("newmtl a\nKd 1 0\n"),
+            "line 2: Kd expects 1 or 3 numbers, found 2"
+        );
+        assert_eq!(
+            error("newmtl a\nKd 1 0 0 1\n"),
+            "line 2: Kd expects 1 or 3 numbers, found 4"
+        );
+        assert_eq!(
+            error("newmtl a\n\nNs high\n"),
+            "line 3: invalid number 'high'"
+        );
+    }
+}
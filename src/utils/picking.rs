@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use web_sys::WebGl2RenderingContext as GL;
+
+use super::RenderTarget;
+
+/// An offscreen framebuffer objects are drawn into with their ID as color, to
+/// find which one is under the mouse
+#[derive(Debug)]
+pub struct Picker {
+    gl: GL,
+    // Resized on demand while picking
+    target: RefCell<RenderTarget>,
+}
+
+impl Picker {
+    /// Create a picking framebuffer the size of the drawing buffer
+    pub fn new(gl: &GL) -> Result<Self, JsValue> {
+        let target = RenderTarget::new(
+            gl,
+            gl.drawing_buffer_width(),
+            gl.drawing_buffer_height(),
+            true,
+        )?;
+        Ok(Self {
+            gl: gl.clone(),
+            target: RefCell::new(target),
+        })
+    }
+
+    /// Draw the scene with `draw` into the picking framebuffer and return the ID
+    /// drawn at (`x`, `y`), in pixels from the top-left corner, if any
+    pub fn pick<F>(&self, x: f32, y: f32, draw: F) -> Result<Option<u32>, JsValue>
+    where
+        F: FnOnce() -> Result<(), JsError>,
+    {
+        let gl = &self.gl;
+        let mut target = self.target.borrow_mut();
+        // Follow the size of the canvas
+        let (width, height) = (gl.drawing_buffer_width(), gl.drawing_buffer_height());
+        if (width, height) != (target.width(), target.height()) {
+            target.resize(width, height)?;
+        }
+
+        let x = x.floor() as i32;
+        // The y axis of the framebuffer goes up
+        let y = height - 1 - y.floor() as i32;
+        if x < 0 || x >= width || y < 0 || y >= height {
+            return Ok(None);
+        }
+
+        let clear_color = gl
+            .get_parameter(GL::COLOR_CLEAR_VALUE)?
+            .unchecked_into::<js_sys::Float32Array>()
+            .to_vec();
+
+        target.bind()?;
+        // The background reads as 0, which is no ID
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+
+        let result = draw().map_err(JsValue::from).and_then(|()| {
+            let mut pixel = [0u8; 4];
+            gl.read_pixels_with_opt_u8_array(
+                x,
+                y,
+                1,
+                1,
+                GL::RGBA,
+                GL::UNSIGNED_BYTE,
+                Some(&mut pixel),
+            )?;
+            Ok(color_to_id(pixel))
+        });
+
+        target.unbind();
+        if let [r, g, b, a] = clear_color[..] {
+            gl.clear_color(r, g, b, a);
+        }
+
+        result
+    }
+}
+
+/// Inverse of `idColor()` in `src/shaders/picking/id_color.glsl`
+fn color_to_id([r, g, b, _]: [u8; 4]) -> Option<u32> {
+    let n = r as u32 | (g as u32) << 8 | (b as u32) << 16;
+    n.checked_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn background_is_no_id() {
+        assert_eq!(color_to_id([0, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn decodes_the_shader_colors() {
+        // The bytes readPixels returns for idColor(id), worked out by hand
+        let colors = [
+            (0, [1, 0, 0, 255]),
+            (254, [255, 0, 0, 255]),
+            (255, [0, 1, 0, 255]),
+            (1000, [233, 3, 0, 255]),
+            (65535, [0, 0, 1, 255]),
+            (0xfffffe, [255, 255, 255, 255]),
+        ];
+        for (id, color) in colors {
+            assert_eq!(color_to_id(color), Some(id), "id {}", id);
+        }
+    }
+}
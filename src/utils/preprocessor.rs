@@ -0,0 +1,702 @@
+use std::fmt;
+
+use super::shader_error::{Diagnostic, Severity, ShaderStage};
+
+/// The files `#include` can name, relative to `src/shaders`
+const FILES: &[(&str, &str)] = &[
+    (
+        "common/lighting.glsl",
+        include_str!("../shaders/common/lighting.glsl"),
+    ),
+    (
+        "picking/id_color.glsl",
+        include_str!("../shaders/picking/id_color.glsl"),
+    ),
+    (
+        "shadow/depth.glsl",
+        include_str!("../shaders/shadow/depth.glsl"),
+    ),
+];
+
+/// The precision of the fragment shaders declaring none
+const DEFAULT_PRECISION: &str = "precision mediump float;";
+
+/// The output replacing `gl_FragColor` in the shaders ported to GLSL ES 3.00
+const FRAG_COLOR: &str = "fragColor";
+
+/// The words GLSL ES 1.00 shaders may use as names, which GLSL ES 3.00 keeps for
+/// itself
+const ES300_RESERVED: &[&str] = &[
+    FRAG_COLOR,
+    // Keywords
+    "case",
+    "centroid",
+    "flat",
+    "layout",
+    "smooth",
+    "uint",
+    "uvec2",
+    "uvec3",
+    "uvec4",
+    "sampler2DArray",
+    "sampler2DArrayShadow",
+    "samplerCubeShadow",
+    "isampler2D",
+    "isampler3D",
+    "isamplerCube",
+    "isampler2DArray",
+    "usampler2D",
+    "usampler3D",
+    "usamplerCube",
+    "usampler2DArray",
+    // Reserved for future use
+    "active",
+    "atomic_uint",
+    "coherent",
+    "common",
+    "filter",
+    "noperspective",
+    "partition",
+    "patch",
+    "readonly",
+    "resource",
+    "restrict",
+    "sample",
+    "subroutine",
+    "writeonly",
+    // The lookup functions the ported ones become
+    "texture",
+    "textureLod",
+    "textureProj",
+    "textureProjLod",
+];
+
+/// The language the shaders are compiled in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GlslVersion {
+    /// The sources as written in the book
+    #[default]
+    Es100,
+    /// The sources ported to WebGL2's language
+    Es300,
+}
+
+impl fmt::Display for GlslVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GlslVersion::Es100 => "GLSL ES 1.00",
+            GlslVersion::Es300 => "GLSL ES 3.00",
+        })
+    }
+}
+
+/// Where a line of the preprocessed source comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Origin {
+    // `None` for the shader source itself
+    file: Option<&'static str>,
+    line: u32,
+}
+
+impl Origin {
+    fn error(self, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            file: self.file.map(String::from),
+            line: Some(self.line),
+            message,
+        }
+    }
+}
+
+/// A shader source ready to compile
+#[derive(Debug, Clone)]
+pub struct Preprocessed {
+    pub source: String,
+    // The origin of every line of `source`, `None` for the added headers
+    origins: Vec<Option<Origin>>,
+}
+
+impl Preprocessed {
+    /// Point the diagnostics about the preprocessed source to the lines they
+    /// come from
+    pub fn map_diagnostics(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| {
+                let index = match diagnostic.line {
+                    Some(line) if line >= 1 => line as usize - 1,
+                    _ => return diagnostic,
+                };
+                match self.origins.get(index) {
+                    Some(Some(origin)) => Diagnostic {
+                        file: origin.file.map(String::from),
+                        line: Some(origin.line),
+                        ..diagnostic
+                    },
+                    Some(None) => Diagnostic {
+                        line: None,
+                        ..diagnostic
+                    },
+                    None => diagnostic,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Turn the shader sources of the pages into what is compiled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preprocessor {
+    files: &'static [(&'static str, &'static str)],
+    defines: Vec<(String, String)>,
+    // Chosen in the shader panel when not set
+    version: Option<GlslVersion>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Self {
+            files: FILES,
+            defines: Vec::new(),
+            version: None,
+        }
+    }
+}
+
+impl Preprocessor {
+    pub fn define(mut self, name: &str, value: &str) -> Self {
+        self.defines.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn version(mut self, version: GlslVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Use `version` unless one was set
+    pub fn default_version(self, version: GlslVersion) -> Self {
+        match self.version {
+            Some(_) => self,
+            None => self.version(version),
+        }
+    }
+
+    /// Preprocess `source`, or return the errors about its directives
+    pub fn process(
+        &self,
+        stage: ShaderStage,
+        source: &str,
+    ) -> Result<Preprocessed, Vec<Diagnostic>> {
+        let mut expansion = Expansion::default();
+        self.expand(&mut expansion, None, source);
+        if !expansion.errors.is_empty() {
+            return Err(expansion.errors);
+        }
+
+        let mut lines: Vec<(String, Option<Origin>)> = Vec::new();
+        // The output declared in a fragment shader ported to GLSL ES 3.00
+        let mut output = None;
+        match expansion.version {
+            Some((version, origin)) => lines.push((version, Some(origin))),
+            None if self.version == Some(GlslVersion::Es300) => {
+                lines.push(("#version 300 es".to_string(), None));
+                let mut port = Port::new(stage);
+                let mut errors = Vec::new();
+                for (line, origin) in &mut expansion.lines {
+                    match port.line(line) {
+                        Ok(ported) => *line = ported,
+                        Err(message) => errors.push(origin.error(message)),
+                    }
+                }
+                if !errors.is_empty() {
+                    return Err(errors);
+                }
+                output = port
+                    .writes_color
+                    .then(|| format!("out vec4 {};", FRAG_COLOR));
+            }
+            None => {}
+        }
+        for (name, value) in &self.defines {
+            lines.push((
+                format!("#define {} {}", name, value).trim_end().to_string(),
+                None,
+            ));
+        }
+        let precision = expansion.lines.iter().position(|(line, _)| {
+            let line = line.trim_start();
+            line.starts_with("precision ") && line.contains("float")
+        });
+        if stage == ShaderStage::Fragment && precision.is_none() {
+            lines.push((DEFAULT_PRECISION.to_string(), None));
+            lines.extend(output.take().map(|output| (output, None)));
+        }
+        for (index, (line, origin)) in expansion.lines.into_iter().enumerate() {
+            lines.push((line, Some(origin)));
+            // The output needs the precision of floats
+            if Some(index) == precision {
+                lines.extend(output.take().map(|output| (output, None)));
+            }
+        }
+
+        let mut source = String::new();
+        let mut origins = Vec::with_capacity(lines.len());
+        for (line, origin) in lines {
+            source += &line;
+            source.push('\n');
+            origins.push(origin);
+        }
+        Ok(Preprocessed { source, origins })
+    }
+
+    fn expand(&self, expansion: &mut Expansion, file: Option<&'static str>, text: &str) {
+        for (line, text) in (1..).zip(text.lines()) {
+            let origin = Origin { file, line };
+            match directive(text) {
+                Some(("version", _)) if file.is_none() && expansion.version.is_none() => {
+                    expansion.version = Some((text.trim().to_string(), origin));
+                }
+                Some(("version", _)) if file.is_some() => {
+                    expansion
+                        .errors
+                        .push(origin.error("#version in an included file".to_string()));
+                }
+                Some(("include", argument)) => {
+                    let name = argument
+                        .strip_prefix('"')
+                        .and_then(|argument| argument.strip_suffix('"'));
+                    let name = match name {
+                        Some(name) => name,
+                        None => {
+                            expansion
+                                .errors
+                                .push(origin.error("expected #include \"file\"".to_string()));
+                            continue;
+                        }
+                    };
+                    match self.files.iter().find(|(file, _)| *file == name) {
+                        Some((name, _)) if expansion.included.contains(name) => {}
+                        Some((name, text)) => {
+                            expansion.included.push(name);
+                            self.expand(expansion, Some(name), text);
+                        }
+                        None => expansion
+                            .errors
+                            .push(origin.error(format!("unknown include file \"{}\"", name))),
+                    }
+                }
+                _ => expansion.lines.push((text.to_string(), origin)),
+            }
+        }
+    }
+}
+
+/// The sources being put together
+#[derive(Debug, Default)]
+struct Expansion {
+    version: Option<(String, Origin)>,
+    lines: Vec<(String, Origin)>,
+    included: Vec<&'static str>,
+    errors: Vec<Diagnostic>,
+}
+
+/// The translation of the lines of a GLSL ES 1.00 shader to GLSL ES 3.00
+struct Port {
+    stage: ShaderStage,
+    // The location of the next attribute
+    location: u32,
+    writes_color: bool,
+}
+
+impl Port {
+    fn new(stage: ShaderStage) -> Self {
+        Self {
+            stage,
+            location: 0,
+            writes_color: false,
+        }
+    }
+
+    /// Port a line, or tell why it cannot be
+    fn line(&mut self, text: &str) -> Result<String, String> {
+        if directive(text).is_some() {
+            return Ok(text.to_string());
+        }
+        // The comments are left as written
+        let (code, comment) = match text.find("//") {
+            Some(index) => text.split_at(index),
+            None => (text, ""),
+        };
+        // The locations taken by the attribute declared on the line
+        let locations = code
+            .split(|c: char| !is_word(c))
+            .find_map(|word| match word {
+                "mat2" => Some(2),
+                "mat3" => Some(3),
+                "mat4" => Some(4),
+                _ => None,
+            })
+            .unwrap_or(1);
+
+        let mut error = None;
+        let code = map_words(code, |word, rest| match word {
+            // Only the calls are renamed
+            "texture2D" | "textureCube" | "texture2DProj" | "texture2DLod" | "texture2DProjLod"
+            | "textureCubeLod"
+                if !rest.trim_start().starts_with('(') =>
+            {
+                error.get_or_insert(format!("'{}' is not called", word));
+                None
+            }
+            word if ES300_RESERVED.contains(&word) => {
+                error.get_or_insert(format!("'{}' is reserved in GLSL ES 3.00", word));
+                None
+            }
+            "attribute" if self.stage == ShaderStage::Vertex => {
+                let layout = format!("layout(location = {}) in", self.location);
+                self.location += locations;
+                Some(layout)
+            }
+            "varying" if self.stage == ShaderStage::Vertex => Some("out".to_string()),
+            "varying" => Some("in".to_string()),
+            "gl_FragColor" => {
+                self.writes_color = true;
+                Some(FRAG_COLOR.to_string())
+            }
+            "texture2D" | "textureCube" => Some("texture".to_string()),
+            "texture2DProj" => Some("textureProj".to_string()),
+            "texture2DLod" | "textureCubeLod" => Some("textureLod".to_string()),
+            "texture2DProjLod" => Some("textureProjLod".to_string()),
+            _ => None,
+        });
+        match error {
+            Some(error) => Err(error),
+            None => Ok(code + comment),
+        }
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Replace the identifiers of `code` for which `f` returns a replacement, `f`
+/// being given the identifier and the code following it
+fn map_words<F>(code: &str, mut f: F) -> String
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    let mut result = String::with_capacity(code.len());
+    let mut rest = code;
+    while let Some(start) = rest.find(is_word) {
+        result += &rest[..start];
+        rest = &rest[start..];
+        let end = rest.find(|c: char| !is_word(c)).unwrap_or(rest.len());
+        let word = &rest[..end];
+        // Numbers such as 1.0e5 are not identifiers
+        let replacement = if word.starts_with(|c: char| c.is_ascii_digit()) {
+            None
+        } else {
+            f(word, &rest[end..])
+        };
+        result += replacement.as_deref().unwrap_or(word);
+        rest = &rest[end..];
+    }
+    result + rest
+}
+
+/// The name and argument of a preprocessor directive, like `("include",
+/// "\"file\"")`
+fn directive(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim().strip_prefix('#')?.trim_start();
+    let end = text
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(text.len());
+    Some((&text[..end], text[end..].trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_FILES: &[(&str, &str)] = &[
+        ("a.glsl", "float a() {\n    return 1.0;\n}"),
+        ("b.glsl", "#include \"a.glsl\"\nfloat b() { return a(); }"),
+        ("bad.glsl", "#include \"missing.glsl\""),
+        ("version.glsl", "#version 300 es"),
+    ];
+
+    fn preprocessor() -> Preprocessor {
+        Preprocessor {
+            files: TEST_FILES,
+            defines: Vec::new(),
+            version: Some(GlslVersion::Es100),
+        }
+    }
+
+    #[test]
+    fn sources_without_directives_are_kept() {
+        let source =
+            "\nattribute vec4 a_Position;\nvoid main() {\n    gl_Position = a_Position;\n}\n";
+        let preprocessed = preprocessor().process(ShaderStage::Vertex, source).unwrap();
+        assert_eq!(preprocessed.source, source);
+    }
+
+    #[test]
+    fn includes_are_expanded_once() {
+        let source = "#include \"b.glsl\"\n  #  include \"a.glsl\"\nvoid main() {}";
+        let preprocessed = preprocessor().process(ShaderStage::Vertex, source).unwrap();
+        assert_eq!(
+            preprocessed.source,
+            "float a() {\n    return 1.0;\n}\nfloat b() { return a(); }\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn version_and_defines_come_first() {
+        let source = "\n#version 300 es\nin vec4 a_Position;\n";
+        let preprocessed = preprocessor()
+            .define("PACKED", "1")
+            .define("FLAT", "")
+            .process(ShaderStage::Vertex, source)
+            .unwrap();
+        assert_eq!(
+            preprocessed.source,
+            "#version 300 es\n#define PACKED 1\n#define FLAT\n\nin vec4 a_Position;\n"
+        );
+    }
+
+    #[test]
+    fn fragment_shaders_get_a_precision() {
+        let preprocessed = preprocessor()
+            .process(ShaderStage::Fragment, "void main() {}")
+            .unwrap();
+        assert_eq!(
+            preprocessed.source,
+            "precision mediump float;\nvoid main() {}\n"
+        );
+
+        // Unless they declare one
+        let source = "#version 300 es\nprecision highp float;\nvoid main() {}";
+        let preprocessed = preprocessor()
+            .process(ShaderStage::Fragment, source)
+            .unwrap();
+        assert_eq!(preprocessed.source, format!("{}\n", source));
+    }
+
+    #[test]
+    fn directive_errors_point_to_their_line() {
+        let source = "\n#include \"b.glsl\"\n#include <a.glsl>\n#include \"bad.glsl\"\n#include \"version.glsl\"\n";
+        let errors = preprocessor()
+            .process(ShaderStage::Vertex, source)
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                Diagnostic {
+                    severity: Severity::Error,
+                    file: None,
+                    line: Some(3),
+                    message: "expected #include \"file\"".to_string(),
+                },
+                Diagnostic {
+                    severity: Severity::Error,
+                    file: Some("bad.glsl".to_string()),
+                    line: Some(1),
+                    message: "unknown include file \"missing.glsl\"".to_string(),
+                },
+                Diagnostic {
+                    severity: Severity::Error,
+                    file: Some("version.glsl".to_string()),
+                    line: Some(1),
+                    message: "#version in an included file".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn diagnostics_are_mapped_to_their_file() {
+        let source = "#version 300 es\n#include \"b.glsl\"\nout vec4 color;\nvoid main() {}";
+        let preprocessed = preprocessor()
+            .define("N", "2")
+            .process(ShaderStage::Fragment, source)
+            .unwrap();
+        // #version, #define, precision, a.glsl (3 lines), b.glsl, then the
+        // rest of the source
+        let error = |line| Diagnostic {
+            severity: Severity::Error,
+            file: None,
+            line,
+            message: String::new(),
+        };
+        let mapped = preprocessed.map_diagnostics(vec![
+            error(Some(1)),
+            error(Some(2)),
+            error(Some(5)),
+            error(Some(7)),
+            error(Some(8)),
+            error(Some(99)),
+            error(None),
+        ]);
+        let at = |file: Option<&str>, line| Diagnostic {
+            file: file.map(String::from),
+            ..error(line)
+        };
+        assert_eq!(
+            mapped,
+            vec![
+                at(None, Some(1)),
+                at(None, None),
+                at(Some("a.glsl"), Some(2)),
+                at(Some("b.glsl"), Some(2)),
+                at(None, Some(3)),
+                at(None, Some(99)),
+                at(None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn vertex_shaders_are_ported() {
+        let source = "
+attribute vec4 a_Position;
+attribute mat4 a_Matrix; // An attribute per column
+attribute vec2 a_TexCoord;
+varying vec2 v_TexCoord; // The varying of the fragment shader
+void main() {
+    gl_Position = a_Matrix * a_Position;
+    v_TexCoord = a_TexCoord;
+}";
+        let preprocessed = preprocessor()
+            .version(GlslVersion::Es300)
+            .define("N", "1")
+            .process(ShaderStage::Vertex, source)
+            .unwrap();
+        assert_eq!(
+            preprocessed.source,
+            "#version 300 es
+#define N 1
+
+layout(location = 0) in vec4 a_Position;
+layout(location = 1) in mat4 a_Matrix; // An attribute per column
+layout(location = 5) in vec2 a_TexCoord;
+out vec2 v_TexCoord; // The varying of the fragment shader
+void main() {
+    gl_Position = a_Matrix * a_Position;
+    v_TexCoord = a_TexCoord;
+}
+"
+        );
+    }
+
+    #[test]
+    fn fragment_shaders_are_ported() {
+        let source = "
+precision mediump float;
+uniform sampler2D u_Sampler;
+varying vec2 v_TexCoord;
+void main() {
+    gl_FragColor = texture2D(u_Sampler, v_TexCoord) * 1.0e0;
+}";
+        let preprocessed = preprocessor()
+            .version(GlslVersion::Es300)
+            .process(ShaderStage::Fragment, source)
+            .unwrap();
+        assert_eq!(
+            preprocessed.source,
+            "#version 300 es
+
+precision mediump float;
+out vec4 fragColor;
+uniform sampler2D u_Sampler;
+in vec2 v_TexCoord;
+void main() {
+    fragColor = texture(u_Sampler, v_TexCoord) * 1.0e0;
+}
+"
+        );
+
+        // Without precision, nor color written
+        let preprocessed = preprocessor()
+            .version(GlslVersion::Es300)
+            .process(ShaderStage::Fragment, "void main() {}")
+            .unwrap();
+        assert_eq!(
+            preprocessed.source,
+            "#version 300 es\nprecision mediump float;\nvoid main() {}\n"
+        );
+
+        // The lines still point to the source
+        let error = Diagnostic {
+            severity: Severity::Error,
+            file: None,
+            line: Some(7),
+            message: String::new(),
+        };
+        let preprocessed = preprocessor()
+            .version(GlslVersion::Es300)
+            .process(ShaderStage::Fragment, source)
+            .unwrap();
+        assert_eq!(
+            preprocessed.map_diagnostics(vec![error.clone()]),
+            vec![Diagnostic {
+                line: Some(5),
+                ..error
+            }]
+        );
+    }
+
+    #[test]
+    fn names_the_port_cannot_handle_are_errors() {
+        let source = "
+uniform sampler2D texture;
+vec4 texture2D;
+void main() {
+    gl_FragColor = vec4(1.0); // texture
+}";
+        let errors = preprocessor()
+            .version(GlslVersion::Es300)
+            .process(ShaderStage::Fragment, source)
+            .unwrap_err();
+        let messages: Vec<_> = errors
+            .iter()
+            .map(|error| (error.line, error.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (Some(2), "'texture' is reserved in GLSL ES 3.00"),
+                (Some(3), "'texture2D' is not called"),
+            ]
+        );
+
+        // Nor in GLSL ES 1.00
+        assert!(preprocessor()
+            .process(ShaderStage::Fragment, source)
+            .is_ok());
+    }
+
+    #[test]
+    fn sources_with_a_version_are_not_ported() {
+        let source = "#version 100\nattribute vec4 a_Position;\n";
+        let preprocessed = preprocessor()
+            .version(GlslVersion::Es300)
+            .process(ShaderStage::Vertex, source)
+            .unwrap();
+        assert_eq!(preprocessed.source, source);
+    }
+
+    #[test]
+    fn embedded_files_exist() {
+        for version in [GlslVersion::Es100, GlslVersion::Es300] {
+            let preprocessor = Preprocessor::default().version(version);
+            for (name, _) in FILES {
+                let source = format!("#include \"{}\"\nvoid main() {{}}", name);
+                assert!(preprocessor.process(ShaderStage::Fragment, &source).is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use nalgebra as na;
+use wasm_bindgen::JsError;
+use web_sys::{WebGl2RenderingContext as GL, WebGlProgram, WebGlUniformLocation};
+
+use super::debug;
+use super::ShaderError;
+
+#[derive(Debug, Clone)]
+pub struct ActiveAttribute {
+    pub location: u32,
+    pub ty: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ActiveUniform {
+    pub location: WebGlUniformLocation,
+    pub ty: u32,
+}
+
+/// A linked program together with the locations and types of its active
+/// attributes and uniforms
+#[derive(Debug, Clone)]
+pub struct ShaderProgram {
+    gl: GL,
+    program: WebGlProgram,
+    attributes: HashMap<String, ActiveAttribute>,
+    uniforms: HashMap<String, ActiveUniform>,
+}
+
+impl ShaderProgram {
+    pub fn new(gl: &GL, program: WebGlProgram) -> Self {
+        let mut attributes = HashMap::new();
+        let count = gl
+            .get_program_parameter(&program, GL::ACTIVE_ATTRIBUTES)
+            .as_f64()
+            .unwrap_or(0.0) as u32;
+        for index in 0..count {
+            if let Some(info) = gl.get_active_attrib(&program, index) {
+                let name = info.name();
+                // Built-in attributes such as gl_VertexID have no location
+                let location = gl.get_attrib_location(&program, &name);
+                if location >= 0 {
+                    attributes.insert(
+                        name,
+                        ActiveAttribute {
+                            location: location as u32,
+                            ty: info.type_(),
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut uniforms = HashMap::new();
+        let count = gl
+            .get_program_parameter(&program, GL::ACTIVE_UNIFORMS)
+            .as_f64()
+            .unwrap_or(0.0) as u32;
+        for index in 0..count {
+            if let Some(info) = gl.get_active_uniform(&program, index) {
+                let name = info.name();
+                if let Some(location) = gl.get_uniform_location(&program, &name) {
+                    // Arrays are reported as `u_Name[0]`, register them as `u_Name`
+                    let name = name.strip_suffix("[0]").map(String::from).unwrap_or(name);
+                    uniforms.insert(
+                        name,
+                        ActiveUniform {
+                            location,
+                            ty: info.type_(),
+                        },
+                    );
+                }
+            }
+        }
+
+        Self {
+            gl: gl.clone(),
+            program,
+            attributes,
+            uniforms,
+        }
+    }
+
+    #[track_caller]
+    pub fn use_program(&self) {
+        self.gl.use_program(Some(&self.program));
+        debug::check_errors(&self.gl, || "useProgram".to_string());
+    }
+
+    pub fn attribute(&self, name: &str) -> Result<&ActiveAttribute, ShaderError> {
+        self.attributes
+            .get(name)
+            .ok_or_else(|| ShaderError::MissingAttribute(name.to_string()))
+    }
+
+    pub fn attrib_location(&self, name: &str) -> Result<u32, ShaderError> {
+        self.attribute(name).map(|info| info.location)
+    }
+
+    pub fn uniform(&self, name: &str) -> Result<&ActiveUniform, ShaderError> {
+        self.uniforms
+            .get(name)
+            .ok_or_else(|| ShaderError::MissingUniform(name.to_string()))
+    }
+
+    /// Set the uniform `name`, failing if its declared GLSL type does not accept
+    /// values of type `T`
+    #[track_caller]
+    pub fn set_uniform<T: Uniform + ?Sized>(&self, name: &str, value: &T) -> Result<(), JsError> {
+        let info = self.uniform(name)?;
+        if !T::TYPES.contains(&info.ty) {
+            return Err(JsError::new(&format!(
+                "Uniform {} is declared as {} but was set with {}",
+                name,
+                type_name(info.ty),
+                type_name(T::TYPES[0]),
+            )));
+        }
+        value.upload(&self.gl, &info.location);
+        debug::check_errors(&self.gl, || {
+            format!("uniform {} = {}", name, type_name(info.ty))
+        });
+        Ok(())
+    }
+}
+
+impl Deref for ShaderProgram {
+    type Target = WebGlProgram;
+
+    fn deref(&self) -> &Self::Target {
+        &self.program
+    }
+}
+
+/// A Rust value that can be uploaded to a uniform variable
+pub trait Uniform {
+    /// GLSL types this value can be assigned to, the first one is used in error
+    /// messages
+    const TYPES: &'static [u32];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation);
+}
+
+const SAMPLER_TYPES: &[u32] = &[
+    GL::INT,
+    GL::BOOL,
+    GL::SAMPLER_2D,
+    GL::SAMPLER_3D,
+    GL::SAMPLER_CUBE,
+    GL::SAMPLER_2D_SHADOW,
+    GL::SAMPLER_2D_ARRAY,
+    GL::SAMPLER_2D_ARRAY_SHADOW,
+    GL::SAMPLER_CUBE_SHADOW,
+    GL::INT_SAMPLER_2D,
+    GL::INT_SAMPLER_3D,
+    GL::INT_SAMPLER_CUBE,
+    GL::INT_SAMPLER_2D_ARRAY,
+    GL::UNSIGNED_INT_SAMPLER_2D,
+    GL::UNSIGNED_INT_SAMPLER_3D,
+    GL::UNSIGNED_INT_SAMPLER_CUBE,
+    GL::UNSIGNED_INT_SAMPLER_2D_ARRAY,
+];
+
+impl Uniform for f32 {
+    const TYPES: &'static [u32] = &[GL::FLOAT];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform1f(Some(location), *self);
+    }
+}
+
+impl Uniform for i32 {
+    const TYPES: &'static [u32] = SAMPLER_TYPES;
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform1i(Some(location), *self);
+    }
+}
+
+impl Uniform for u32 {
+    const TYPES: &'static [u32] = &[GL::UNSIGNED_INT];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform1ui(Some(location), *self);
+    }
+}
+
+impl Uniform for bool {
+    const TYPES: &'static [u32] = &[GL::BOOL];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform1i(Some(location), *self as i32);
+    }
+}
+
+impl Uniform for [f32; 2] {
+    const TYPES: &'static [u32] = &[GL::FLOAT_VEC2];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform2fv_with_f32_array(Some(location), self);
+    }
+}
+
+impl Uniform for [f32; 3] {
+    const TYPES: &'static [u32] = &[GL::FLOAT_VEC3];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform3fv_with_f32_array(Some(location), self);
+    }
+}
+
+impl Uniform for [f32; 4] {
+    const TYPES: &'static [u32] = &[GL::FLOAT_VEC4];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform4fv_with_f32_array(Some(location), self);
+    }
+}
+
+impl Uniform for na::Vector2<f32> {
+    const TYPES: &'static [u32] = &[GL::FLOAT_VEC2];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform2fv_with_f32_array(Some(location), self.as_slice());
+    }
+}
+
+impl Uniform for na::Vector3<f32> {
+    const TYPES: &'static [u32] = &[GL::FLOAT_VEC3];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform3fv_with_f32_array(Some(location), self.as_slice());
+    }
+}
+
+impl Uniform for na::Vector4<f32> {
+    const TYPES: &'static [u32] = &[GL::FLOAT_VEC4];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform4fv_with_f32_array(Some(location), self.as_slice());
+    }
+}
+
+impl Uniform for na::Matrix3<f32> {
+    const TYPES: &'static [u32] = &[GL::FLOAT_MAT3];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform_matrix3fv_with_f32_array(Some(location), false, self.as_slice());
+    }
+}
+
+impl Uniform for na::Matrix4<f32> {
+    const TYPES: &'static [u32] = &[GL::FLOAT_MAT4];
+
+    fn upload(&self, gl: &GL, location: &WebGlUniformLocation) {
+        gl.uniform_matrix4fv_with_f32_array(Some(location), false, self.as_slice());
+    }
+}
+
+/// Whether a GLSL type is fed through `vertexAttribIPointer`
+pub fn is_integer_type(ty: u32) -> bool {
+    matches!(
+        ty,
+        GL::INT
+            | GL::INT_VEC2
+            | GL::INT_VEC3
+            | GL::INT_VEC4
+            | GL::UNSIGNED_INT
+            | GL::UNSIGNED_INT_VEC2
+            | GL::UNSIGNED_INT_VEC3
+            | GL::UNSIGNED_INT_VEC4
+    )
+}
+
+/// GLSL spelling of a type enum returned by `getActiveUniform`/`getActiveAttrib`
+pub fn type_name(ty: u32) -> &'static str {
+    match ty {
+        GL::FLOAT => "float",
+        GL::FLOAT_VEC2 => "vec2",
+        GL::FLOAT_VEC3 => "vec3",
+        GL::FLOAT_VEC4 => "vec4",
+        GL::INT => "int",
+        GL::INT_VEC2 => "ivec2",
+        GL::INT_VEC3 => "ivec3",
+        GL::INT_VEC4 => "ivec4",
+        GL::UNSIGNED_INT => "uint",
+        GL::UNSIGNED_INT_VEC2 => "uvec2",
+        GL::UNSIGNED_INT_VEC3 => "uvec3",
+        GL::UNSIGNED_INT_VEC4 => "uvec4",
+        GL::BOOL => "bool",
+        GL::BOOL_VEC2 => "bvec2",
+        GL::BOOL_VEC3 => "bvec3",
+        GL::BOOL_VEC4 => "bvec4",
+        GL::FLOAT_MAT2 => "mat2",
+        GL::FLOAT_MAT3 => "mat3",
+        GL::FLOAT_MAT4 => "mat4",
+        GL::FLOAT_MAT2X3 => "mat2x3",
+        GL::FLOAT_MAT2X4 => "mat2x4",
+        GL::FLOAT_MAT3X2 => "mat3x2",
+        GL::FLOAT_MAT3X4 => "mat3x4",
+        GL::FLOAT_MAT4X2 => "mat4x2",
+        GL::FLOAT_MAT4X3 => "mat4x3",
+        GL::SAMPLER_2D => "sampler2D",
+        GL::SAMPLER_3D => "sampler3D",
+        GL::SAMPLER_CUBE => "samplerCube",
+        GL::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        GL::SAMPLER_2D_ARRAY => "sampler2DArray",
+        GL::SAMPLER_2D_ARRAY_SHADOW => "sampler2DArrayShadow",
+        GL::SAMPLER_CUBE_SHADOW => "samplerCubeShadow",
+        GL::INT_SAMPLER_2D => "isampler2D",
+        GL::INT_SAMPLER_3D => "isampler3D",
+        GL::INT_SAMPLER_CUBE => "isamplerCube",
+        GL::INT_SAMPLER_2D_ARRAY => "isampler2DArray",
+        GL::UNSIGNED_INT_SAMPLER_2D => "usampler2D",
+        GL::UNSIGNED_INT_SAMPLER_3D => "usampler3D",
+        GL::UNSIGNED_INT_SAMPLER_CUBE => "usamplerCube",
+        GL::UNSIGNED_INT_SAMPLER_2D_ARRAY => "usampler2DArray",
+        _ => "unknown",
+    }
+}
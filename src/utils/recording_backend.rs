@@ -0,0 +1,455 @@
+use nalgebra as na;
+use wasm_bindgen::JsError;
+
+use super::backend::{Indices, RenderBackend, UniformValue};
+use super::program::{type_name, Uniform};
+use super::texture::TextureBuilder;
+use super::{Preprocessor, ShaderError, ShaderStage, VertexLayout};
+
+/// A call to a `RecordingBackend`, the objects being numbered in the order they
+/// were created
+#[derive(Debug, Clone, PartialEq)]
+pub enum Call {
+    CreateProgram {
+        vertex: String,
+        fragment: String,
+    },
+    CreateVertexArray {
+        program: usize,
+        buffers: Vec<(VertexLayout, Vec<f32>)>,
+        indices: Option<Vec<u32>>,
+    },
+    CreateTexture {
+        options: TextureBuilder,
+        image: String,
+    },
+    SetUniform {
+        program: usize,
+        name: String,
+        value: UniformValue,
+    },
+    VertexAttrib {
+        program: usize,
+        name: String,
+        value: [f32; 4],
+    },
+    UseProgram(usize),
+    BindVertexArray(usize),
+    ClearColor([f32; 4]),
+    Enable(u32),
+    PolygonOffset(f32, f32),
+    Clear(u32),
+    DrawArrays {
+        mode: u32,
+        first: i32,
+        count: i32,
+    },
+    DrawElements {
+        mode: u32,
+        count: i32,
+        ty: u32,
+    },
+}
+
+/// A uniform variable declared by the sources of a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Declaration {
+    ty: String,
+    name: String,
+}
+
+/// A backend recording the calls of a scene instead of drawing it, its images
+/// being only names
+#[derive(Debug, Default)]
+pub struct RecordingBackend {
+    calls: Vec<Call>,
+    objects: usize,
+    // The uniforms declared by the programs created, by object number
+    uniforms: Vec<(usize, Vec<Declaration>)>,
+    // The attributes the vertex shaders of the programs read
+    attributes: Vec<(usize, Vec<String>)>,
+}
+
+impl RecordingBackend {
+    /// The calls recorded since the last time
+    pub fn take_calls(&mut self) -> Vec<Call> {
+        std::mem::take(&mut self.calls)
+    }
+
+    fn create(&mut self, call: Call) -> usize {
+        self.calls.push(call);
+        self.objects += 1;
+        self.objects - 1
+    }
+
+    /// The error `ShaderProgram::attribute` returns for an attribute that is
+    /// not active in the program
+    fn check_attribute(&self, program: usize, name: &str) -> Result<(), ShaderError> {
+        let attributes = self
+            .attributes
+            .iter()
+            .find(|(object, _)| *object == program)
+            .map(|(_, attributes)| attributes)
+            .unwrap_or_else(|| panic!("object {} is not a program", program));
+        if attributes.iter().any(|attribute| attribute == name) {
+            Ok(())
+        } else {
+            Err(ShaderError::MissingAttribute(name.to_string()))
+        }
+    }
+
+    fn check_uniform(&self, program: usize, name: &str, value: &UniformValue) {
+        let declarations = self
+            .uniforms
+            .iter()
+            .find(|(object, _)| *object == program)
+            .map(|(_, declarations)| declarations)
+            .unwrap_or_else(|| panic!("object {} is not a program", program));
+        // The elements of arrays and the fields of structures
+        let variable = name.split(['[', '.']).next().unwrap_or(name);
+        let declaration = declarations
+            .iter()
+            .find(|declaration| declaration.name == variable)
+            .unwrap_or_else(|| panic!("no uniform {} in program {}", name, program));
+        if variable == name && !accepts(&declaration.ty, value) {
+            panic!(
+                "uniform {} is declared as {} but was set with {:?}",
+                name, declaration.ty, value
+            );
+        }
+    }
+}
+
+/// Whether a uniform of GLSL type `ty` can be set to `value`, with the types
+/// `ShaderProgram::set_uniform` accepts
+fn accepts(ty: &str, value: &UniformValue) -> bool {
+    let types = match value {
+        UniformValue::Float(_) => <f32 as Uniform>::TYPES,
+        UniformValue::Int(_) => <i32 as Uniform>::TYPES,
+        UniformValue::UInt(_) => <u32 as Uniform>::TYPES,
+        UniformValue::Bool(_) => <bool as Uniform>::TYPES,
+        UniformValue::Vec2(_) => <na::Vector2<f32> as Uniform>::TYPES,
+        UniformValue::Vec3(_) => <na::Vector3<f32> as Uniform>::TYPES,
+        UniformValue::Vec4(_) => <na::Vector4<f32> as Uniform>::TYPES,
+        UniformValue::Mat3(_) => <na::Matrix3<f32> as Uniform>::TYPES,
+        UniformValue::Mat4(_) => <na::Matrix4<f32> as Uniform>::TYPES,
+    };
+    types.iter().any(|&declared| type_name(declared) == ty)
+}
+
+fn is_builtin_type(ty: &str) -> bool {
+    matches!(
+        ty,
+        "float" | "int" | "uint" | "bool" | "vec2" | "vec3" | "vec4" | "mat3" | "mat4"
+    ) || ty.starts_with(['i', 'u', 'b']) && ty.contains("vec")
+        || ty.contains("sampler")
+        || ty.starts_with("mat")
+}
+
+/// The code of `source` once its files are included, without the comments
+fn shader_code(stage: ShaderStage, source: &str) -> String {
+    let source = Preprocessor::default()
+        .process(stage, source)
+        .unwrap_or_else(|errors| panic!("invalid shader: {:?}", errors))
+        .source;
+    let mut code = String::new();
+    let mut rest = source.as_str();
+    while let Some(start) = rest.find('/') {
+        code += &rest[..start];
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.find("*/").map_or("", |end| &comment[end + 2..]);
+        } else {
+            code.push('/');
+            rest = &rest[1..];
+        }
+    }
+    code + rest
+}
+
+/// The variables of `code` declared with one of the `qualifiers`
+fn declarations(code: &str, qualifiers: &[&str]) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    for statement in code.split([';', '{', '}']) {
+        // After a `layout(location = 0)`
+        let statement = match statement.trim_start().strip_prefix("layout") {
+            Some(layout) => layout.split_once(')').map_or("", |(_, rest)| rest),
+            None => statement,
+        };
+        let mut words = statement.split_whitespace();
+        if !words.next().is_some_and(|word| qualifiers.contains(&word)) {
+            continue;
+        }
+        let mut words = words.skip_while(|word| matches!(*word, "lowp" | "mediump" | "highp"));
+        let ty = match words.next() {
+            Some(ty) => ty.to_string(),
+            None => continue,
+        };
+        let names = words.collect::<Vec<_>>().join(" ");
+        for name in names.split(',') {
+            let name = name.split('[').next().unwrap_or_default().trim();
+            if !name.is_empty() {
+                declarations.push(Declaration {
+                    ty: if is_builtin_type(&ty) {
+                        ty.clone()
+                    } else {
+                        // A structure
+                        String::new()
+                    },
+                    name: name.to_string(),
+                });
+            }
+        }
+    }
+    declarations
+}
+
+/// The uniforms declared by `source`
+fn declared_uniforms(stage: ShaderStage, source: &str) -> Vec<Declaration> {
+    declarations(&shader_code(stage, source), &["uniform"])
+}
+
+/// The attributes a vertex shader declares and reads, which are the active
+/// ones
+fn active_attributes(source: &str) -> Vec<String> {
+    let code = shader_code(ShaderStage::Vertex, source);
+    let words = code
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .collect::<Vec<_>>();
+    declarations(&code, &["attribute", "in"])
+        .into_iter()
+        .map(|declaration| declaration.name)
+        .filter(|name| words.iter().filter(|word| *word == name).count() > 1)
+        .collect()
+}
+
+impl RenderBackend for RecordingBackend {
+    type Program = usize;
+    type VertexArray = usize;
+    type Texture = usize;
+    type Image = String;
+
+    fn create_program(&mut self, vertex: &str, fragment: &str) -> Result<usize, JsError> {
+        let mut uniforms = declared_uniforms(ShaderStage::Vertex, vertex);
+        uniforms.extend(declared_uniforms(ShaderStage::Fragment, fragment));
+        let program = self.create(Call::CreateProgram {
+            vertex: vertex.to_string(),
+            fragment: fragment.to_string(),
+        });
+        self.uniforms.push((program, uniforms));
+        self.attributes.push((program, active_attributes(vertex)));
+        Ok(program)
+    }
+
+    fn create_vertex_array(
+        &mut self,
+        program: &usize,
+        buffers: &[(&VertexLayout, &[f32])],
+        indices: Option<Indices>,
+    ) -> Result<usize, JsError> {
+        for (layout, _) in buffers {
+            for attribute in layout.attributes() {
+                match self.check_attribute(*program, &attribute.name) {
+                    Err(_) if layout.skip_inactive => {}
+                    result => result?,
+                }
+            }
+        }
+        let indices = indices.map(|indices| match indices {
+            Indices::U8(indices) => indices.iter().map(|&index| index as u32).collect(),
+            Indices::U16(indices) => indices.iter().map(|&index| index as u32).collect(),
+            Indices::U32(indices) => indices.to_vec(),
+        });
+        Ok(self.create(Call::CreateVertexArray {
+            program: *program,
+            buffers: buffers
+                .iter()
+                .map(|(layout, data)| ((*layout).clone(), data.to_vec()))
+                .collect(),
+            indices,
+        }))
+    }
+
+    fn create_texture(
+        &mut self,
+        options: &TextureBuilder,
+        image: &String,
+    ) -> Result<usize, JsError> {
+        Ok(self.create(Call::CreateTexture {
+            options: options.clone(),
+            image: image.clone(),
+        }))
+    }
+
+    fn set_uniform(
+        &mut self,
+        program: &usize,
+        name: &str,
+        value: UniformValue,
+    ) -> Result<(), JsError> {
+        self.check_uniform(*program, name, &value);
+        self.calls.push(Call::SetUniform {
+            program: *program,
+            name: name.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    fn vertex_attrib(
+        &mut self,
+        program: &usize,
+        name: &str,
+        value: [f32; 4],
+    ) -> Result<(), JsError> {
+        self.check_attribute(*program, name)?;
+        self.calls.push(Call::VertexAttrib {
+            program: *program,
+            name: name.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    fn use_program(&mut self, program: &usize) {
+        self.calls.push(Call::UseProgram(*program));
+    }
+
+    fn bind_vertex_array(&mut self, vertex_array: &usize) {
+        self.calls.push(Call::BindVertexArray(*vertex_array));
+    }
+
+    fn clear_color(&mut self, red: f32, green: f32, blue: f32, alpha: f32) {
+        self.calls.push(Call::ClearColor([red, green, blue, alpha]));
+    }
+
+    fn enable(&mut self, capability: u32) {
+        self.calls.push(Call::Enable(capability));
+    }
+
+    fn polygon_offset(&mut self, factor: f32, units: f32) {
+        self.calls.push(Call::PolygonOffset(factor, units));
+    }
+
+    fn clear(&mut self, mask: u32) {
+        self.calls.push(Call::Clear(mask));
+    }
+
+    fn draw_arrays(&mut self, mode: u32, first: i32, count: i32) {
+        self.calls.push(Call::DrawArrays { mode, first, count });
+    }
+
+    fn draw_elements(&mut self, mode: u32, count: i32, ty: u32) {
+        self.calls.push(Call::DrawElements { mode, count, ty });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use web_sys::WebGl2RenderingContext as GL;
+
+    use super::*;
+
+    const VSHADER_SOURCE: &str = "
+attribute vec4 a_Position;
+uniform mat4 u_MvpMatrix; // uniform float u_Commented;
+uniform highp vec3 u_LightColor, u_LightPosition;
+uniform float u_Weights[4];
+void main() {
+    gl_Position = u_MvpMatrix * a_Position;
+}
+";
+
+    const FSHADER_SOURCE: &str = "
+precision mediump float;
+/* uniform int u_Disabled; */
+uniform sampler2D u_Sampler;
+void main() {
+    gl_FragColor = texture2D(u_Sampler, vec2(0.5));
+}
+";
+
+    #[test]
+    fn uniforms_are_collected_from_both_shaders() {
+        let declared = declared_uniforms(ShaderStage::Vertex, VSHADER_SOURCE)
+            .into_iter()
+            .chain(declared_uniforms(ShaderStage::Fragment, FSHADER_SOURCE))
+            .map(|declaration| (declaration.ty, declaration.name))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("mat4", "u_MvpMatrix"),
+            ("vec3", "u_LightColor"),
+            ("vec3", "u_LightPosition"),
+            ("float", "u_Weights"),
+            ("sampler2D", "u_Sampler"),
+        ]
+        .map(|(ty, name)| (ty.to_string(), name.to_string()));
+        assert_eq!(declared, expected);
+    }
+
+    #[test]
+    fn declared_uniforms_can_be_set() {
+        let mut backend = RecordingBackend::default();
+        let program = backend
+            .create_program(VSHADER_SOURCE, FSHADER_SOURCE)
+            .unwrap();
+        let uniforms = [
+            ("u_LightPosition", UniformValue::from([0.0, 1.0, 0.0])),
+            ("u_Weights[2]", 0.5.into()),
+            ("u_Sampler", 0.into()),
+        ];
+        for (name, value) in uniforms {
+            backend.set_uniform(&program, name, value).unwrap();
+        }
+    }
+
+    #[test]
+    fn attributes_must_be_active() {
+        let mut backend = RecordingBackend::default();
+        let program = backend
+            .create_program(VSHADER_SOURCE, FSHADER_SOURCE)
+            .unwrap();
+        assert_eq!(backend.check_attribute(program, "a_Position"), Ok(()));
+        // As WebGl2Backend, which fails with a JsError only wasm can create
+        assert_eq!(
+            backend.check_attribute(program, "a_Color"),
+            Err(ShaderError::MissingAttribute("a_Color".to_string()))
+        );
+        let color = VertexLayout::new()
+            .attribute("a_Color", 3, GL::FLOAT, false)
+            .skip_inactive();
+        assert!(backend
+            .create_vertex_array(&program, &[(&color, &[0.0; 3])], None)
+            .is_ok());
+    }
+
+    #[test]
+    fn uint_values_only_set_uint_uniforms() {
+        assert!(accepts("uint", &UniformValue::UInt(1)));
+        assert!(!accepts("bool", &UniformValue::UInt(1)));
+        assert!(accepts("bool", &UniformValue::Int(1)));
+        assert!(accepts("sampler2D", &UniformValue::Int(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "no uniform u_MvpMatrx in program 0")]
+    fn misspelled_uniforms_panic() {
+        let mut backend = RecordingBackend::default();
+        let program = backend
+            .create_program(VSHADER_SOURCE, FSHADER_SOURCE)
+            .unwrap();
+        let _ = backend.set_uniform(&program, "u_MvpMatrx", 1.0.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "uniform u_LightColor is declared as vec3")]
+    fn values_of_the_wrong_type_panic() {
+        let mut backend = RecordingBackend::default();
+        let program = backend
+            .create_program(VSHADER_SOURCE, FSHADER_SOURCE)
+            .unwrap();
+        let _ = backend.set_uniform(&program, "u_LightColor", 1.0.into());
+    }
+}
@@ -0,0 +1,284 @@
+use std::cell::Cell;
+
+use js_sys::Int32Array;
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use web_sys::{WebGl2RenderingContext as GL, WebGlFramebuffer, WebGlRenderbuffer, WebGlTexture};
+
+use super::debug::{self, enum_name};
+
+#[derive(Debug)]
+enum Attachments {
+    /// A color texture and an optional depth buffer
+    Color {
+        texture: WebGlTexture,
+        depth: Option<WebGlRenderbuffer>,
+    },
+    /// A depth texture only
+    Depth { texture: WebGlTexture },
+}
+
+/// A framebuffer object rendering into a color texture, with an optional depth
+/// buffer, or into a depth texture
+#[derive(Debug)]
+pub struct RenderTarget {
+    gl: GL,
+    framebuffer: WebGlFramebuffer,
+    attachments: Attachments,
+    width: i32,
+    height: i32,
+    // The viewport to restore on unbind()
+    saved_viewport: Cell<Option<[i32; 4]>>,
+}
+
+impl RenderTarget {
+    /// Create a target rendering into a color texture, with a depth buffer if
+    /// `depth` is set
+    #[track_caller]
+    pub fn new(gl: &GL, width: i32, height: i32, depth: bool) -> Result<Self, JsValue> {
+        let texture = create_texture(gl)?;
+        let depth = if depth {
+            let renderbuffer = gl.create_renderbuffer().ok_or_else(|| {
+                gl.delete_texture(Some(&texture));
+                JsError::new("Failed to create renderbuffer object")
+            })?;
+            Some(renderbuffer)
+        } else {
+            None
+        };
+        Self::with_attachments(gl, width, height, Attachments::Color { texture, depth })
+    }
+
+    /// Create a target rendering only the depth of the scene, into a
+    /// `DEPTH_COMPONENT24` texture
+    #[track_caller]
+    pub fn new_depth(gl: &GL, width: i32, height: i32) -> Result<Self, JsValue> {
+        let texture = create_texture(gl)?;
+        Self::with_attachments(gl, width, height, Attachments::Depth { texture })
+    }
+
+    #[track_caller]
+    fn with_attachments(
+        gl: &GL,
+        width: i32,
+        height: i32,
+        attachments: Attachments,
+    ) -> Result<Self, JsValue> {
+        let framebuffer = match gl.create_framebuffer() {
+            Some(framebuffer) => framebuffer,
+            None => {
+                delete_attachments(gl, &attachments);
+                return Err(JsError::new("Failed to create frame buffer object").into());
+            }
+        };
+
+        let mut target = Self {
+            gl: gl.clone(),
+            framebuffer,
+            attachments,
+            width: 0,
+            height: 0,
+            saved_viewport: Cell::new(None),
+        };
+        if let Err(error) = target.resize(width, height) {
+            target.delete();
+            return Err(error);
+        }
+
+        // Attach the textures and the renderbuffer object to the framebuffer object
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&target.framebuffer));
+        match &target.attachments {
+            Attachments::Color { texture, depth } => {
+                gl.framebuffer_texture_2d(
+                    GL::FRAMEBUFFER,
+                    GL::COLOR_ATTACHMENT0,
+                    GL::TEXTURE_2D,
+                    Some(texture),
+                    0,
+                );
+                if let Some(depth) = depth {
+                    gl.framebuffer_renderbuffer(
+                        GL::FRAMEBUFFER,
+                        GL::DEPTH_ATTACHMENT,
+                        GL::RENDERBUFFER,
+                        Some(depth),
+                    );
+                }
+            }
+            Attachments::Depth { texture } => {
+                gl.framebuffer_texture_2d(
+                    GL::FRAMEBUFFER,
+                    GL::DEPTH_ATTACHMENT,
+                    GL::TEXTURE_2D,
+                    Some(texture),
+                    0,
+                );
+            }
+        }
+
+        debug::check_errors(gl, || "framebufferTexture2D".to_string());
+        // Check if the framebuffer object is configured correctly
+        let status = gl.check_framebuffer_status(GL::FRAMEBUFFER);
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        if status != GL::FRAMEBUFFER_COMPLETE {
+            target.delete();
+            return Err(JsError::new(&format!(
+                "Frame buffer object is incomplete: {}",
+                framebuffer_status_name(status)
+            ))
+            .into());
+        }
+
+        Ok(target)
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// The texture the scene is rendered into: the color texture, or the depth
+    /// texture of targets created with `RenderTarget::new_depth`
+    pub fn texture(&self) -> &WebGlTexture {
+        match &self.attachments {
+            Attachments::Color { texture, .. } | Attachments::Depth { texture } => texture,
+        }
+    }
+
+    /// Reallocate the color and depth buffers, their content is lost
+    #[track_caller]
+    pub fn resize(&mut self, width: i32, height: i32) -> Result<(), JsValue> {
+        let gl = &self.gl;
+
+        let (texture, internal_format, format, ty, filter) = match &self.attachments {
+            Attachments::Color { texture, .. } => {
+                (texture, GL::RGBA, GL::RGBA, GL::UNSIGNED_BYTE, GL::LINEAR)
+            }
+            // Depth textures cannot be filtered
+            Attachments::Depth { texture } => (
+                texture,
+                GL::DEPTH_COMPONENT24,
+                GL::DEPTH_COMPONENT,
+                GL::UNSIGNED_INT,
+                GL::NEAREST,
+            ),
+        };
+        // Keep the texture the caller has bound on the active unit
+        let bound_texture = gl
+            .get_parameter(GL::TEXTURE_BINDING_2D)?
+            .dyn_into::<WebGlTexture>()
+            .ok();
+        gl.bind_texture(GL::TEXTURE_2D, Some(texture));
+        let result = gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            GL::TEXTURE_2D,
+            0,
+            internal_format as i32,
+            width,
+            height,
+            0,
+            format,
+            ty,
+            None,
+        );
+        if result.is_ok() {
+            // There are no mipmaps to sample from
+            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, filter as i32);
+            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, filter as i32);
+            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+            gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+        }
+        gl.bind_texture(GL::TEXTURE_2D, bound_texture.as_ref());
+        result?;
+
+        if let Attachments::Color {
+            depth: Some(depth), ..
+        } = &self.attachments
+        {
+            let bound_renderbuffer = gl
+                .get_parameter(GL::RENDERBUFFER_BINDING)?
+                .dyn_into::<WebGlRenderbuffer>()
+                .ok();
+            gl.bind_renderbuffer(GL::RENDERBUFFER, Some(depth));
+            gl.renderbuffer_storage(GL::RENDERBUFFER, GL::DEPTH_COMPONENT16, width, height);
+            gl.bind_renderbuffer(GL::RENDERBUFFER, bound_renderbuffer.as_ref());
+        }
+
+        debug::check_errors(gl, || {
+            format!(
+                "texImage2D({}, {}x{})",
+                enum_name(internal_format),
+                width,
+                height
+            )
+        });
+
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    /// Draw into the render target, with a viewport covering all of it
+    #[track_caller]
+    pub fn bind(&self) -> Result<(), JsValue> {
+        let gl = &self.gl;
+        let viewport = gl
+            .get_parameter(GL::VIEWPORT)?
+            .unchecked_into::<Int32Array>()
+            .to_vec();
+        if let [x, y, width, height] = viewport[..] {
+            self.saved_viewport.set(Some([x, y, width, height]));
+        }
+
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&self.framebuffer));
+        gl.viewport(0, 0, self.width, self.height);
+        debug::check_errors(gl, || "bindFramebuffer".to_string());
+        Ok(())
+    }
+
+    /// Draw into the canvas again, restoring the viewport saved by `bind()`
+    #[track_caller]
+    pub fn unbind(&self) {
+        let gl = &self.gl;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        match self.saved_viewport.take() {
+            Some([x, y, width, height]) => gl.viewport(x, y, width, height),
+            None => gl.viewport(0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height()),
+        }
+        debug::check_errors(gl, || "bindFramebuffer(null)".to_string());
+    }
+
+    fn delete(&self) {
+        self.gl.delete_framebuffer(Some(&self.framebuffer));
+        delete_attachments(&self.gl, &self.attachments);
+    }
+}
+
+fn delete_attachments(gl: &GL, attachments: &Attachments) {
+    match attachments {
+        Attachments::Color { texture, depth } => {
+            gl.delete_texture(Some(texture));
+            gl.delete_renderbuffer(depth.as_ref());
+        }
+        Attachments::Depth { texture } => gl.delete_texture(Some(texture)),
+    }
+}
+
+fn create_texture(gl: &GL) -> Result<WebGlTexture, JsError> {
+    gl.create_texture()
+        .ok_or_else(|| JsError::new("Failed to create texture object"))
+}
+
+fn framebuffer_status_name(status: u32) -> String {
+    match status {
+        GL::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "FRAMEBUFFER_INCOMPLETE_ATTACHMENT".to_string(),
+        GL::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
+            "FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT".to_string()
+        }
+        GL::FRAMEBUFFER_INCOMPLETE_DIMENSIONS => "FRAMEBUFFER_INCOMPLETE_DIMENSIONS".to_string(),
+        GL::FRAMEBUFFER_UNSUPPORTED => "FRAMEBUFFER_UNSUPPORTED".to_string(),
+        GL::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "FRAMEBUFFER_INCOMPLETE_MULTISAMPLE".to_string(),
+        _ => format!("0x{:x}", status),
+    }
+}
@@ -0,0 +1,197 @@
+use nalgebra as na;
+
+/// The current transform together with the saved ones, as in the book's
+/// `pushMatrix()`/`popMatrix()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixStack {
+    current: na::Matrix4<f32>,
+    saved: Vec<na::Matrix4<f32>>,
+}
+
+impl Default for MatrixStack {
+    fn default() -> Self {
+        Self::new(na::Matrix4::identity())
+    }
+}
+
+impl MatrixStack {
+    pub fn new(current: na::Matrix4<f32>) -> Self {
+        Self {
+            current,
+            saved: Vec::new(),
+        }
+    }
+
+    pub fn current(&self) -> &na::Matrix4<f32> {
+        &self.current
+    }
+
+    /// Save a copy of the current matrix
+    pub fn push(&mut self) {
+        self.saved.push(self.current);
+    }
+
+    /// Restore the last saved matrix, the current one is left unchanged when
+    /// nothing was saved
+    pub fn pop(&mut self) {
+        if let Some(matrix) = self.saved.pop() {
+            self.current = matrix;
+        }
+    }
+
+    /// Apply `transform` in the local frame of the current matrix
+    pub fn multiply(&mut self, transform: &na::Matrix4<f32>) {
+        self.current *= transform;
+    }
+}
+
+/// A node of a hierarchical model: a transform relative to the parent node,
+/// something to draw and child nodes that follow its transform
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<D> {
+    pub transform: na::Matrix4<f32>,
+    pub drawable: Option<D>,
+    pub children: Vec<Node<D>>,
+}
+
+impl<D> Node<D> {
+    pub fn new(transform: na::Matrix4<f32>) -> Self {
+        Self {
+            transform,
+            drawable: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_drawable(mut self, drawable: D) -> Self {
+        self.drawable = Some(drawable);
+        self
+    }
+
+    pub fn with_child(mut self, child: Node<D>) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Visit the drawables depth first, parents before their children, with the
+    /// matrix from their local coordinates to the coordinates of `stack`
+    pub fn traverse<E, F>(&self, stack: &mut MatrixStack, visit: &mut F) -> Result<(), E>
+    where
+        F: FnMut(&D, &na::Matrix4<f32>) -> Result<(), E>,
+    {
+        stack.push();
+        stack.multiply(&self.transform);
+        let result = self.traverse_children(stack, visit);
+        stack.pop();
+        result
+    }
+
+    fn traverse_children<E, F>(&self, stack: &mut MatrixStack, visit: &mut F) -> Result<(), E>
+    where
+        F: FnMut(&D, &na::Matrix4<f32>) -> Result<(), E>,
+    {
+        if let Some(drawable) = self.drawable.as_ref() {
+            visit(drawable, stack.current())?;
+        }
+        for child in &self.children {
+            child.traverse(stack, visit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(x: f32, y: f32, z: f32) -> na::Matrix4<f32> {
+        na::Matrix4::new_translation(&na::Vector3::new(x, y, z))
+    }
+
+    fn origin_of(matrix: &na::Matrix4<f32>) -> na::Point3<f32> {
+        matrix.transform_point(&na::Point3::origin())
+    }
+
+    fn collect(root: &Node<&'static str>) -> Vec<(&'static str, na::Matrix4<f32>)> {
+        let mut visited = Vec::new();
+        let mut stack = MatrixStack::default();
+        root.traverse::<(), _>(&mut stack, &mut |name, matrix| {
+            visited.push((*name, *matrix));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(stack, MatrixStack::default());
+        visited
+    }
+
+    #[test]
+    fn push_pop_restores_the_matrix() {
+        let mut stack = MatrixStack::default();
+        stack.multiply(&translation(1.0, 0.0, 0.0));
+        stack.push();
+        stack.multiply(&translation(0.0, 2.0, 0.0));
+        assert_eq!(origin_of(stack.current()), na::Point3::new(1.0, 2.0, 0.0));
+        stack.pop();
+        assert_eq!(origin_of(stack.current()), na::Point3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn pop_on_empty_stack_keeps_current() {
+        let mut stack = MatrixStack::new(translation(0.0, 0.0, 3.0));
+        stack.pop();
+        assert_eq!(stack.current(), &translation(0.0, 0.0, 3.0));
+    }
+
+    #[test]
+    fn children_are_relative_to_their_parent() {
+        // A joint rotated by 90 degrees around z moves its child from +y to -x
+        let rotation = na::Matrix4::new_rotation(na::Vector3::new(0.0, 0.0, 90f32.to_radians()));
+        let root = Node::new(translation(0.0, -1.0, 0.0))
+            .with_drawable("base")
+            .with_child(
+                Node::new(rotation)
+                    .with_drawable("joint")
+                    .with_child(Node::new(translation(0.0, 2.0, 0.0)).with_drawable("tip")),
+            );
+
+        let visited = collect(&root);
+        let names: Vec<_> = visited.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["base", "joint", "tip"]);
+
+        let tip = origin_of(&visited[2].1);
+        assert!((tip - na::Point3::new(-2.0, -1.0, 0.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn siblings_do_not_inherit_each_other() {
+        let root = Node::new(na::Matrix4::identity())
+            .with_child(Node::new(translation(1.0, 0.0, 0.0)).with_drawable("left"))
+            .with_child(Node::new(translation(0.0, 0.0, 1.0)).with_drawable("right"));
+
+        let visited = collect(&root);
+        assert_eq!(origin_of(&visited[0].1), na::Point3::new(1.0, 0.0, 0.0));
+        assert_eq!(origin_of(&visited[1].1), na::Point3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn errors_stop_the_traversal_and_unwind_the_stack() {
+        let root = Node::new(translation(1.0, 0.0, 0.0))
+            .with_drawable(1)
+            .with_child(Node::new(translation(1.0, 0.0, 0.0)).with_drawable(2))
+            .with_child(Node::new(translation(1.0, 0.0, 0.0)).with_drawable(3));
+
+        let mut visited = Vec::new();
+        let mut stack = MatrixStack::default();
+        let result = root.traverse(&mut stack, &mut |drawable, _| {
+            visited.push(*drawable);
+            if *drawable == 2 {
+                Err("failed")
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(result, Err("failed"));
+        assert_eq!(visited, [1, 2]);
+        assert_eq!(stack, MatrixStack::default());
+    }
+}
@@ -0,0 +1,329 @@
+use std::fmt;
+
+use wasm_bindgen::JsValue;
+use web_sys::WebGl2RenderingContext as GL;
+
+/// Lines shown around a line in error
+const EXCERPT_CONTEXT: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl ShaderStage {
+    /// The stage of a `createShader` type
+    pub fn from_type(shader_type: u32) -> Self {
+        match shader_type {
+            GL::VERTEX_SHADER => ShaderStage::Vertex,
+            _ => ShaderStage::Fragment,
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::Fragment => "fragment",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARNING",
+        })
+    }
+}
+
+/// A message of a shader info log, about `line` of the source when known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The included file the line is in, `None` for the shader source itself
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// The lines of `source` around the line of the diagnostic, numbered, the
+    /// line itself being marked by `>`
+    pub fn excerpt(&self, source: &str) -> Option<String> {
+        if self.file.is_some() {
+            return None;
+        }
+        let line = self.line?;
+        let first = line.saturating_sub(EXCERPT_CONTEXT).max(1);
+        let last = line + EXCERPT_CONTEXT;
+        let width = last.to_string().len();
+        let mut excerpt = String::new();
+        for (number, text) in (1..).zip(source.lines()) {
+            if number < first || number > last {
+                continue;
+            }
+            let mark = if number == line { '>' } else { ' ' };
+            excerpt += &format!("{} {:>width$} | {}\n", mark, number, text, width = width);
+        }
+        // The line may be past the end of an edited source
+        (!excerpt.is_empty()).then_some(excerpt)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.file.as_ref(), self.line) {
+            (Some(file), Some(line)) => {
+                write!(f, "{}: {}:{}: {}", self.severity, file, line, self.message)
+            }
+            (None, Some(line)) => write!(f, "{}: line {}: {}", self.severity, line, self.message),
+            (_, None) => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// Split the info log returned by `getShaderInfoLog` into its messages
+pub fn parse_info_log(log: &str) -> Vec<Diagnostic> {
+    log.lines()
+        // Some drivers end the log with a NUL
+        .map(|line| line.trim_matches(|c: char| c == '\0' || c.is_whitespace()))
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            parse_prefixed(line)
+                .or_else(|| parse_mesa(line))
+                .unwrap_or_else(|| parse_other(line))
+        })
+        .collect()
+}
+
+fn severity(text: &str) -> Option<Severity> {
+    let text = text.trim().to_ascii_lowercase();
+    if text.ends_with("error") {
+        Some(Severity::Error)
+    } else if text.ends_with("warning") {
+        Some(Severity::Warning)
+    } else {
+        None
+    }
+}
+
+/// `ERROR: 0:12: message`
+fn parse_prefixed(text: &str) -> Option<Diagnostic> {
+    let (severity, rest) = text.split_once(':')?;
+    let severity = self::severity(severity)?;
+    let mut parts = rest.splitn(3, ':');
+    let (_, line, message) = (parts.next()?, parts.next()?, parts.next()?);
+    Some(Diagnostic {
+        severity,
+        file: None,
+        line: Some(line.trim().parse().ok()?),
+        message: message.trim().to_string(),
+    })
+}
+
+/// `0:12(5): error: message`, the column in parentheses
+fn parse_mesa(text: &str) -> Option<Diagnostic> {
+    let (_, rest) = text.split_once(':')?;
+    let (line, rest) = rest.split_once('(')?;
+    let (_, rest) = rest.split_once("):")?;
+    let (severity, message) = rest.split_once(':')?;
+    Some(Diagnostic {
+        severity: self::severity(severity)?,
+        file: None,
+        line: Some(line.trim().parse().ok()?),
+        message: message.trim().to_string(),
+    })
+}
+
+/// A message about no line, such as `ERROR: 2 compilation errors.`
+fn parse_other(text: &str) -> Diagnostic {
+    match text.split_once(':') {
+        Some((severity, message)) if self::severity(severity).is_some() => Diagnostic {
+            severity: self::severity(severity).unwrap(),
+            file: None,
+            line: None,
+            message: message.trim().to_string(),
+        },
+        _ => Diagnostic {
+            severity: Severity::Error,
+            file: None,
+            line: None,
+            message: text.to_string(),
+        },
+    }
+}
+
+/// Why a shader program could not be created or used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderError {
+    /// `createShader` or `createProgram` returned null, naming the object
+    CreateFailed(&'static str),
+    Compile {
+        stage: ShaderStage,
+        source: String,
+        diagnostics: Vec<Diagnostic>,
+    },
+    Link {
+        log: String,
+    },
+    /// An attribute not declared, or not used so optimized out
+    MissingAttribute(String),
+    /// A uniform not declared, or not used so optimized out
+    MissingUniform(String),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::CreateFailed(object) => write!(f, "Unable to create {} object", object),
+            ShaderError::Compile {
+                stage,
+                source,
+                diagnostics,
+            } => {
+                write!(f, "Failed to compile the {} shader", stage)?;
+                if diagnostics.is_empty() {
+                    return f.write_str(": unknown error");
+                }
+                for diagnostic in diagnostics {
+                    write!(f, "\n{}", diagnostic)?;
+                    if let Some(excerpt) = diagnostic.excerpt(source) {
+                        write!(f, "\n{}", excerpt.trim_end())?;
+                    }
+                }
+                Ok(())
+            }
+            ShaderError::Link { log } if log.is_empty() => {
+                f.write_str("Failed to link the program: unknown error")
+            }
+            ShaderError::Link { log } => write!(f, "Failed to link the program: {}", log),
+            ShaderError::MissingAttribute(name) | ShaderError::MissingUniform(name) => {
+                write!(f, "Failed to get the storage location of {}", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+impl From<ShaderError> for JsValue {
+    fn from(error: ShaderError) -> Self {
+        js_sys::Error::new(&error.to_string()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(line: Option<u32>, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            file: None,
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn angle_messages() {
+        let log = "ERROR: 0:3: 'a_Positon' : undeclared identifier\n\
+                   WARNING: 0:5: 'x' : unused variable\n\
+                   ERROR: 2 compilation errors.  No code generated.\n\n\0";
+        assert_eq!(
+            parse_info_log(log),
+            vec![
+                error(Some(3), "'a_Positon' : undeclared identifier"),
+                Diagnostic {
+                    severity: Severity::Warning,
+                    file: None,
+                    line: Some(5),
+                    message: "'x' : unused variable".to_string(),
+                },
+                error(None, "2 compilation errors.  No code generated."),
+            ]
+        );
+    }
+
+    #[test]
+    fn mesa_messages() {
+        let log = "0:3(17): error: `a_Positon' undeclared\n\
+                   0:3(5): error: value of type float cannot be assigned to variable of type vec4\n\
+                   0:7(1): preprocessor error: syntax error, unexpected HASH_TOKEN\n\
+                   0:9(12): warning: `v' used uninitialized\n";
+        assert_eq!(
+            parse_info_log(log),
+            vec![
+                error(Some(3), "`a_Positon' undeclared"),
+                error(
+                    Some(3),
+                    "value of type float cannot be assigned to variable of type vec4"
+                ),
+                error(Some(7), "syntax error, unexpected HASH_TOKEN"),
+                Diagnostic {
+                    severity: Severity::Warning,
+                    file: None,
+                    line: Some(9),
+                    message: "`v' used uninitialized".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn other_messages_are_kept_whole() {
+        assert_eq!(
+            parse_info_log("Vertex shader is not compiled."),
+            vec![error(None, "Vertex shader is not compiled.")]
+        );
+        assert!(parse_info_log("").is_empty());
+    }
+
+    #[test]
+    fn excerpt_marks_the_line() {
+        let source = "\nvoid main() {\n    gl_Position = a_Positon;\n}\n";
+        let diagnostic = error(Some(3), "'a_Positon' : undeclared identifier");
+        assert_eq!(
+            diagnostic.excerpt(source).unwrap(),
+            "  2 | void main() {\n> 3 |     gl_Position = a_Positon;\n  4 | }\n"
+        );
+        // Past the end of the source
+        assert_eq!(error(Some(10), "").excerpt(source), None);
+        assert_eq!(error(None, "").excerpt(source), None);
+    }
+
+    #[test]
+    fn compile_error_display() {
+        let error = ShaderError::Compile {
+            stage: ShaderStage::Fragment,
+            source: "precision mediump float;\nvoid main() {\n    gl_FragColor = 1.0;\n}".to_string(),
+            diagnostics: parse_info_log(
+                "ERROR: 0:3: 'assign' : cannot convert from 'const float' to 'FragColor mediump 4-component vector of float'\n\
+                 ERROR: 1 compilation errors.  No code generated.",
+            ),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Failed to compile the fragment shader\n\
+             ERROR: line 3: 'assign' : cannot convert from 'const float' to 'FragColor mediump 4-component vector of float'\n  \
+             2 | void main() {\n\
+             > 3 |     gl_FragColor = 1.0;\n  \
+             4 | }\n\
+             ERROR: 1 compilation errors.  No code generated."
+        );
+        assert_eq!(
+            ShaderError::MissingUniform("u_Color".to_string()).to_string(),
+            "Failed to get the storage location of u_Color"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use web_sys::WebGl2RenderingContext as GL;
+use yew::Callback;
+
+use super::{GlslVersion, Preprocessor};
+
+/// The vertex and fragment shader sources of a program
+pub type Sources = (String, String);
+
+/// A program created by the page shown, with the context it was created in
+#[derive(Debug, Clone)]
+pub struct RegisteredProgram {
+    pub gl: GL,
+    /// The sources written in the page, and how they are preprocessed
+    pub original: Sources,
+    pub preprocessor: Preprocessor,
+}
+
+#[derive(Default)]
+struct Registry {
+    programs: Vec<RegisteredProgram>,
+    // The edited sources replacing the original ones
+    overrides: HashMap<Sources, Sources>,
+    version: GlslVersion,
+    listener: Option<Callback<()>>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+fn notify() {
+    // The listener may read the registry
+    let listener = REGISTRY.with(|registry| registry.borrow().listener.clone());
+    if let Some(listener) = listener {
+        listener.emit(());
+    }
+}
+
+/// Record a program created from the given sources, returning the edited sources
+/// to use instead if any
+pub fn register(
+    gl: &GL,
+    preprocessor: &Preprocessor,
+    vertex: &str,
+    fragment: &str,
+) -> Option<Sources> {
+    let original = (vertex.to_string(), fragment.to_string());
+    let edited = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let edited = registry.overrides.get(&original).cloned();
+        // A program created again replaces the previous one
+        registry
+            .programs
+            .retain(|program| program.original != original);
+        registry.programs.push(RegisteredProgram {
+            gl: gl.clone(),
+            original,
+            preprocessor: preprocessor.clone(),
+        });
+        edited
+    });
+    notify();
+    edited
+}
+
+pub fn programs() -> Vec<RegisteredProgram> {
+    REGISTRY.with(|registry| registry.borrow().programs.clone())
+}
+
+/// The sources a program is created from, edited or not
+pub fn sources(original: &Sources) -> Sources {
+    REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .overrides
+            .get(original)
+            .unwrap_or(original)
+            .clone()
+    })
+}
+
+/// Create the programs of `original` from `edited` from now on, or from the
+/// original sources again when `None`
+pub fn set_sources(original: &Sources, edited: Option<Sources>) {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        match edited {
+            Some(edited) => registry.overrides.insert(original.clone(), edited),
+            None => registry.overrides.remove(original),
+        };
+    });
+}
+
+/// The version of GLSL the programs are created in
+pub fn version() -> GlslVersion {
+    REGISTRY.with(|registry| registry.borrow().version)
+}
+
+/// Create the programs in `version` from now on, on every page
+pub fn set_version(version: GlslVersion) {
+    REGISTRY.with(|registry| registry.borrow_mut().version = version);
+}
+
+/// Forget the programs before the page creates them again
+pub fn forget_programs() {
+    REGISTRY.with(|registry| registry.borrow_mut().programs.clear());
+    notify();
+}
+
+/// Forget the programs and the edits of the page left
+pub fn clear() {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        registry.programs.clear();
+        registry.overrides.clear();
+    });
+    notify();
+}
+
+/// Call `listener` whenever the programs change, replacing the previous
+/// listener, or stop calling it when `None`
+pub fn listen(listener: Option<Callback<()>>) {
+    REGISTRY.with(|registry| registry.borrow_mut().listener = listener);
+}
@@ -0,0 +1,138 @@
+use nalgebra as na;
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use web_sys::WebGl2RenderingContext as GL;
+
+use super::{Preprocessor, RenderTarget};
+
+/// How the depth seen from the light is stored in a `ShadowMap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowFormat {
+    /// A `DEPTH_COMPONENT24` depth texture, written by the depth test itself
+    DepthTexture,
+    /// The book's trick: the depth packed into the 4 bytes of an RGBA texture by
+    /// the fragment shader
+    PackedRgba,
+}
+
+impl ShadowFormat {
+    /// Fragment shader of the depth pass
+    pub fn fragment_shader(self) -> &'static str {
+        match self {
+            // Only the depth buffer is written
+            Self::DepthTexture => {
+                "
+precision mediump float;
+void main() {
+}
+"
+            }
+            Self::PackedRgba => {
+                "
+precision highp float;
+void main() {
+    const vec4 bitShift = vec4(1.0, 256.0, 256.0 * 256.0, 256.0 * 256.0 * 256.0);
+    const vec4 bitMask = vec4(1.0 / 256.0, 1.0 / 256.0, 1.0 / 256.0, 0.0);
+    vec4 rgbaDepth = fract(gl_FragCoord.z * bitShift);
+    rgbaDepth -= rgbaDepth.gbaa * bitMask;
+    gl_FragColor = rgbaDepth;
+}
+"
+            }
+        }
+    }
+
+    /// The preprocessor of the shaders reading the shadow map with
+    /// `shadowDepth(shadowMap, texCoord)`, from `#include "shadow/depth.glsl"`
+    pub fn preprocessor(self) -> Preprocessor {
+        match self {
+            Self::DepthTexture => Preprocessor::default(),
+            Self::PackedRgba => Preprocessor::default().define("SHADOW_PACKED_RGBA", "1"),
+        }
+    }
+}
+
+/// A depth map of the scene seen from a light, for the main pass to tell which
+/// fragments the light does not reach
+#[derive(Debug)]
+pub struct ShadowMap {
+    gl: GL,
+    target: RenderTarget,
+    format: ShadowFormat,
+    light_view_proj: na::Matrix4<f32>,
+}
+
+impl ShadowMap {
+    /// Create a `size`x`size` shadow map, with the light at the origin until
+    /// `ShadowMap::look_at` is called
+    pub fn new(gl: &GL, size: i32, format: ShadowFormat) -> Result<Self, JsValue> {
+        let target = match format {
+            ShadowFormat::DepthTexture => RenderTarget::new_depth(gl, size, size)?,
+            ShadowFormat::PackedRgba => RenderTarget::new(gl, size, size, true)?,
+        };
+        Ok(Self {
+            gl: gl.clone(),
+            target,
+            format,
+            light_view_proj: na::Matrix4::identity(),
+        })
+    }
+
+    /// Place a spot light at `eye` pointing to `target`, lighting a cone of
+    /// `fovy` radians between `near` and `far`
+    pub fn look_at(
+        &mut self,
+        eye: &na::Point3<f32>,
+        target: &na::Point3<f32>,
+        fovy: f32,
+        near: f32,
+        far: f32,
+    ) {
+        let aspect = self.target.width() as f32 / self.target.height() as f32;
+        self.light_view_proj = na::Matrix4::new_perspective(aspect, fovy, near, far)
+            * na::Matrix4::look_at_rh(eye, target, &na::Vector3::y());
+    }
+
+    /// The view projection matrix of the light, to multiply with the model
+    /// matrices in both passes
+    pub fn light_view_proj(&self) -> &na::Matrix4<f32> {
+        &self.light_view_proj
+    }
+
+    /// Draw the depth of the scene from the light with `draw`, which is passed
+    /// the view projection matrix of the light
+    pub fn render<F>(&self, draw: F) -> Result<(), JsValue>
+    where
+        F: FnOnce(&na::Matrix4<f32>) -> Result<(), JsError>,
+    {
+        let gl = &self.gl;
+        self.target.bind()?;
+        let result = match self.format {
+            ShadowFormat::DepthTexture => {
+                gl.clear(GL::DEPTH_BUFFER_BIT);
+                draw(&self.light_view_proj).map_err(JsValue::from)
+            }
+            ShadowFormat::PackedRgba => {
+                let clear_color = gl
+                    .get_parameter(GL::COLOR_CLEAR_VALUE)?
+                    .unchecked_into::<js_sys::Float32Array>()
+                    .to_vec();
+                // White unpacks to the far plane where nothing was drawn
+                gl.clear_color(1.0, 1.0, 1.0, 1.0);
+                gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT);
+                if let [r, g, b, a] = clear_color[..] {
+                    gl.clear_color(r, g, b, a);
+                }
+                draw(&self.light_view_proj).map_err(JsValue::from)
+            }
+        };
+        self.target.unbind();
+        result
+    }
+
+    /// Bind the depth map to the texture unit `unit`
+    pub fn bind_texture(&self, unit: u32) {
+        self.gl.active_texture(GL::TEXTURE0 + unit);
+        self.gl
+            .bind_texture(GL::TEXTURE_2D, Some(self.target.texture()));
+    }
+}
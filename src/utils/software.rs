@@ -0,0 +1,781 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use nalgebra as na;
+use web_sys::WebGl2RenderingContext as GL;
+
+use super::backend::UniformValue;
+use super::recording_backend::Call;
+use super::VertexLayout;
+
+/// The primitives `drawArrays` and `drawElements` assemble the vertices into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawMode {
+    Points,
+    Lines,
+    LineStrip,
+    LineLoop,
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+impl DrawMode {
+    /// The mode of a `GL::POINTS`... constant
+    pub fn from_gl(mode: u32) -> Self {
+        match mode {
+            GL::POINTS => DrawMode::Points,
+            GL::LINES => DrawMode::Lines,
+            GL::LINE_STRIP => DrawMode::LineStrip,
+            GL::LINE_LOOP => DrawMode::LineLoop,
+            GL::TRIANGLES => DrawMode::Triangles,
+            GL::TRIANGLE_STRIP => DrawMode::TriangleStrip,
+            GL::TRIANGLE_FAN => DrawMode::TriangleFan,
+            _ => panic!("unknown draw mode {:#x}", mode),
+        }
+    }
+}
+
+/// What the vertex shader outputs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    /// `gl_Position`
+    pub position: na::Vector4<f32>,
+    /// The varying the fragments are colored with
+    pub color: na::Vector4<f32>,
+    /// `gl_PointSize`
+    pub point_size: f32,
+}
+
+impl Vertex {
+    pub fn new(position: na::Vector4<f32>, color: na::Vector4<f32>) -> Self {
+        Self {
+            position,
+            color,
+            point_size: 1.0,
+        }
+    }
+
+    pub fn point_size(mut self, point_size: f32) -> Self {
+        self.point_size = point_size;
+        self
+    }
+
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(&other.position, t),
+            color: self.color.lerp(&other.color, t),
+            point_size: self.point_size,
+        }
+    }
+}
+
+/// The values of an attribute in the vertex data of a page, `size` floats at
+/// `offset` every `stride` floats, completed to (0, 0, 0, 1) like WebGL does
+pub fn attribute(data: &[f32], stride: usize, offset: usize, size: usize) -> Vec<na::Vector4<f32>> {
+    data.chunks_exact(stride)
+        .map(|vertex| {
+            let mut value = na::Vector4::new(0.0, 0.0, 0.0, 1.0);
+            value.as_mut_slice()[..size].copy_from_slice(&vertex[offset..offset + size]);
+            value
+        })
+        .collect()
+}
+
+/// What a vertex shader reads, for `Rasterizer::replay`
+pub struct ShaderInputs<'a> {
+    attributes: HashMap<&'a str, na::Vector4<f32>>,
+    uniforms: &'a HashMap<String, UniformValue>,
+}
+
+impl ShaderInputs<'_> {
+    /// The attribute `name` of the vertex, (0, 0, 0, 1) if it has no data
+    pub fn attribute(&self, name: &str) -> na::Vector4<f32> {
+        self.attributes
+            .get(name)
+            .copied()
+            .unwrap_or_else(|| na::Vector4::new(0.0, 0.0, 0.0, 1.0))
+    }
+
+    pub fn uniform(&self, name: &str) -> UniformValue {
+        *self
+            .uniforms
+            .get(name)
+            .unwrap_or_else(|| panic!("uniform {} is not set", name))
+    }
+
+    pub fn mat4(&self, name: &str) -> na::Matrix4<f32> {
+        match self.uniform(name) {
+            UniformValue::Mat4(value) => value,
+            value => panic!("uniform {} is not a mat4: {:?}", name, value),
+        }
+    }
+
+    pub fn float(&self, name: &str) -> f32 {
+        match self.uniform(name) {
+            UniformValue::Float(value) => value,
+            value => panic!("uniform {} is not a float: {:?}", name, value),
+        }
+    }
+
+    pub fn vec4(&self, name: &str) -> na::Vector4<f32> {
+        match self.uniform(name) {
+            UniformValue::Vec4(value) => value,
+            value => panic!("uniform {} is not a vec4: {:?}", name, value),
+        }
+    }
+
+    pub fn vec3(&self, name: &str) -> na::Vector3<f32> {
+        match self.uniform(name) {
+            UniformValue::Vec3(value) => value,
+            value => panic!("uniform {} is not a vec3: {:?}", name, value),
+        }
+    }
+}
+
+/// A vertex in window coordinates
+#[derive(Debug, Clone, Copy)]
+struct WindowVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    // 1/w, to interpolate the color with perspective correction
+    inv_w: f32,
+    color: na::Vector4<f32>,
+}
+
+/// A drawing buffer with a depth buffer, drawn into following the rules of WebGL
+/// closely enough to check the scenes of the pages natively
+#[derive(Debug, Clone)]
+pub struct Rasterizer {
+    width: usize,
+    height: usize,
+    // The rows from the bottom, like the drawing buffer
+    color: Vec<[u8; 4]>,
+    depth: Vec<f32>,
+    depth_test: bool,
+}
+
+impl Rasterizer {
+    /// A drawing buffer of transparent black pixels
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            color: vec![[0; 4]; width * height],
+            depth: vec![1.0; width * height],
+            depth_test: false,
+        }
+    }
+
+    /// `gl.enable(GL::DEPTH_TEST)`, with the `LESS` function
+    pub fn enable_depth_test(&mut self) {
+        self.depth_test = true;
+    }
+
+    /// `gl.clear(GL::COLOR_BUFFER_BIT | GL::DEPTH_BUFFER_BIT)` with the clear
+    /// color `color`
+    pub fn clear(&mut self, color: [f32; 4]) {
+        let color = to_rgba8(&na::Vector4::from(color));
+        self.color.fill(color);
+        self.depth.fill(1.0);
+    }
+
+    /// The pixel at (`x`, `y`) from the bottom-left corner, like `readPixels`
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.color[y * self.width + x]
+    }
+
+    pub fn draw_arrays(&mut self, mode: DrawMode, vertices: &[Vertex]) {
+        let indices = (0..vertices.len() as u32).collect::<Vec<_>>();
+        self.draw_elements(mode, vertices, &indices);
+    }
+
+    pub fn draw_elements<I>(&mut self, mode: DrawMode, vertices: &[Vertex], indices: &[I])
+    where
+        I: Copy + Into<u32>,
+    {
+        let vertices = indices
+            .iter()
+            .map(|&index| vertices[index.into() as usize])
+            .collect::<Vec<_>>();
+        let count = vertices.len();
+        match mode {
+            DrawMode::Points => vertices.iter().for_each(|vertex| self.point(vertex)),
+            DrawMode::Lines => {
+                for line in vertices.chunks_exact(2) {
+                    self.line(&line[0], &line[1]);
+                }
+            }
+            DrawMode::LineStrip | DrawMode::LineLoop => {
+                for line in vertices.windows(2) {
+                    self.line(&line[0], &line[1]);
+                }
+                if mode == DrawMode::LineLoop && count > 2 {
+                    self.line(&vertices[count - 1], &vertices[0]);
+                }
+            }
+            DrawMode::Triangles => {
+                for triangle in vertices.chunks_exact(3) {
+                    self.triangle(&triangle[0], &triangle[1], &triangle[2]);
+                }
+            }
+            DrawMode::TriangleStrip => {
+                for i in 0..count.saturating_sub(2) {
+                    // Every other triangle is turned around to keep the winding
+                    if i % 2 == 0 {
+                        self.triangle(&vertices[i], &vertices[i + 1], &vertices[i + 2]);
+                    } else {
+                        self.triangle(&vertices[i + 1], &vertices[i], &vertices[i + 2]);
+                    }
+                }
+            }
+            DrawMode::TriangleFan => {
+                for i in 1..count.saturating_sub(1) {
+                    self.triangle(&vertices[0], &vertices[i], &vertices[i + 1]);
+                }
+            }
+        }
+    }
+
+    /// Draw the calls a page made on a `RecordingBackend`, its only program
+    /// being ported to `shader`
+    pub fn replay<F>(&mut self, calls: &[Call], shader: F)
+    where
+        F: Fn(&ShaderInputs) -> Vertex,
+    {
+        let mut vertex_arrays = HashMap::new();
+        let mut uniforms = HashMap::new();
+        let mut constants = HashMap::new();
+        let mut clear_color = [0.0; 4];
+        let mut bound = None;
+        // The number of the next object created
+        let mut objects = 0;
+        for call in calls {
+            match call {
+                Call::CreateProgram { .. } | Call::CreateTexture { .. } => objects += 1,
+                Call::CreateVertexArray {
+                    buffers, indices, ..
+                } => {
+                    vertex_arrays.insert(objects, (buffers, indices));
+                    bound = Some(objects);
+                    objects += 1;
+                }
+                Call::UseProgram(_) => {}
+                Call::SetUniform { name, value, .. } => {
+                    uniforms.insert(name.clone(), *value);
+                }
+                Call::VertexAttrib { name, value, .. } => {
+                    constants.insert(name.as_str(), na::Vector4::from(*value));
+                }
+                Call::BindVertexArray(object) => bound = Some(*object),
+                Call::ClearColor(color) => clear_color = *color,
+                Call::Enable(GL::DEPTH_TEST) => self.enable_depth_test(),
+                Call::Clear(_) => self.clear(clear_color),
+                Call::DrawArrays { mode, first, count } => {
+                    // Without a vertex array, every attribute is constant
+                    let buffers = bound.map_or(&[][..], |object| vertex_arrays[&object].0);
+                    let end = (first + count) as usize;
+                    let vertices = shade(buffers, end, &constants, &uniforms, &shader);
+                    let indices = (*first as u32..end as u32).collect::<Vec<_>>();
+                    self.draw_elements(DrawMode::from_gl(*mode), &vertices, &indices);
+                }
+                Call::DrawElements { mode, count, .. } => {
+                    let (buffers, indices) = vertex_arrays[&bound.expect("no vertex array bound")];
+                    let indices = indices.as_ref().expect("no index buffer");
+                    let vertices = shade(buffers, 0, &constants, &uniforms, &shader);
+                    self.draw_elements(
+                        DrawMode::from_gl(*mode),
+                        &vertices,
+                        &indices[..*count as usize],
+                    );
+                }
+                Call::Enable(_) | Call::PolygonOffset(..) => {
+                    panic!("{:?} is not supported by the rasterizer", call)
+                }
+            }
+        }
+    }
+
+    fn to_window(&self, vertex: &Vertex) -> WindowVertex {
+        let position = &vertex.position;
+        let inv_w = 1.0 / position.w;
+        WindowVertex {
+            x: (position.x * inv_w + 1.0) * self.width as f32 / 2.0,
+            y: (position.y * inv_w + 1.0) * self.height as f32 / 2.0,
+            z: (position.z * inv_w + 1.0) / 2.0,
+            inv_w,
+            color: vertex.color,
+        }
+    }
+
+    fn fragment(&mut self, x: i64, y: i64, z: f32, color: &na::Vector4<f32>) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let index = y as usize * self.width + x as usize;
+        if self.depth_test {
+            if z >= self.depth[index] {
+                return;
+            }
+            self.depth[index] = z;
+        }
+        self.color[index] = to_rgba8(color);
+    }
+
+    /// A square of `point_size` pixels, drawn if its center is in the clip
+    /// volume
+    fn point(&mut self, vertex: &Vertex) {
+        let position = &vertex.position;
+        let w = position.w;
+        if w <= 0.0 || position.xyz().iter().any(|coordinate| coordinate.abs() > w) {
+            return;
+        }
+        let center = self.to_window(vertex);
+        let half_size = vertex.point_size.max(1.0) / 2.0;
+        // The pixels whose center is in [center - size / 2, center + size / 2)
+        let first = |center: f32| (center - half_size - 0.5).ceil() as i64;
+        let last = |center: f32| (center + half_size - 0.5).ceil() as i64;
+        for y in first(center.y)..last(center.y) {
+            for x in first(center.x)..last(center.x) {
+                self.fragment(x, y, center.z, &center.color);
+            }
+        }
+    }
+
+    /// The pixels whose center the line crosses along its major axis, the last
+    /// one excluded so that a strip draws its joints once
+    fn line(&mut self, start: &Vertex, end: &Vertex) {
+        let (start, end) = match clip_line(start, end) {
+            Some(line) => line,
+            None => return,
+        };
+        let (start, end) = (self.to_window(&start), self.to_window(&end));
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let x_major = dx.abs() >= dy.abs();
+        let (from, delta) = if x_major {
+            (start.x, dx)
+        } else {
+            (start.y, dy)
+        };
+        if delta == 0.0 {
+            return;
+        }
+        // The centers at `from + delta * t`, with t in [0, 1)
+        let (mut center, step) = if delta > 0.0 {
+            ((from - 0.5).ceil() + 0.5, 1.0)
+        } else {
+            ((from - 0.5).floor() + 0.5, -1.0)
+        };
+        loop {
+            let t = (center - from) / delta;
+            if !(0.0..1.0).contains(&t) {
+                break;
+            }
+            let (x, y) = (start.x + dx * t, start.y + dy * t);
+            let z = start.z + (end.z - start.z) * t;
+            let color = interpolate(&[start, end], &[1.0 - t, t]);
+            self.fragment(x.floor() as i64, y.floor() as i64, z, &color);
+            center += step;
+        }
+    }
+
+    /// The pixels whose center is in the triangle, the ones on an edge belonging
+    /// to the triangle on its left or top only, like GPUs do
+    fn triangle(&mut self, a: &Vertex, b: &Vertex, c: &Vertex) {
+        let polygon = clip_polygon(vec![*a, *b, *c]);
+        let polygon = polygon
+            .iter()
+            .map(|vertex| self.to_window(vertex))
+            .collect::<Vec<_>>();
+        for i in 1..polygon.len().saturating_sub(1) {
+            self.window_triangle([polygon[0], polygon[i], polygon[i + 1]]);
+        }
+    }
+
+    fn window_triangle(&mut self, mut vertices: [WindowVertex; 3]) {
+        let mut area = edge(&vertices[0], &vertices[1], vertices[2].x, vertices[2].y);
+        if area == 0.0 {
+            return;
+        }
+        // Nothing is culled, the edges are walked counterclockwise
+        if area < 0.0 {
+            vertices.swap(1, 2);
+            area = -area;
+        }
+        let [a, b, c] = vertices;
+        let min = |f: fn(&WindowVertex) -> f32| f(&a).min(f(&b)).min(f(&c));
+        let max = |f: fn(&WindowVertex) -> f32| f(&a).max(f(&b)).max(f(&c));
+        let x_range = (min(|v| v.x).floor().max(0.0) as i64)
+            ..(max(|v| v.x).ceil().min(self.width as f32) as i64);
+        let y_range = (min(|v| v.y).floor().max(0.0) as i64)
+            ..(max(|v| v.y).ceil().min(self.height as f32) as i64);
+        let edges = [(b, c), (c, a), (a, b)];
+        for y in y_range {
+            for x in x_range.clone() {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let weights = edges.map(|(from, to)| edge(&from, &to, px, py));
+                let inside = weights.iter().zip(&edges).all(|(&weight, (from, to))| {
+                    weight > 0.0 || (weight == 0.0 && is_top_left(from, to))
+                });
+                if !inside {
+                    continue;
+                }
+                let weights = weights.map(|weight| weight / area);
+                let z = weights[0] * a.z + weights[1] * b.z + weights[2] * c.z;
+                let color = interpolate(&vertices, &weights);
+                self.fragment(x, y, z, &color);
+            }
+        }
+    }
+
+    /// The drawing buffer encoded as a PNG image
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&self.rows_from_top()).unwrap();
+        writer.finish().unwrap();
+        png
+    }
+
+    fn rows_from_top(&self) -> Vec<u8> {
+        self.color
+            .chunks_exact(self.width)
+            .rev()
+            .flatten()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Check the drawing buffer against `tests/golden/{name}.png`
+    pub fn assert_golden(&self, name: &str) {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let path = root.join("tests/golden").join(format!("{}.png", name));
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, self.to_png()).unwrap();
+            return;
+        }
+
+        let golden = fs::read(&path).unwrap_or_else(|_| {
+            panic!(
+                "Missing {}, run the tests with UPDATE_GOLDEN=1 to create it",
+                path.display()
+            )
+        });
+        let (width, height, expected) = decode_png(&golden);
+        let actual = self.rows_from_top();
+        if (width, height) == (self.width, self.height) && expected == actual {
+            return;
+        }
+        let output: PathBuf = root.join("target/golden").join(format!("{}.png", name));
+        fs::create_dir_all(output.parent().unwrap()).unwrap();
+        fs::write(&output, self.to_png()).unwrap();
+        let differing = if (width, height) == (self.width, self.height) {
+            let pixels = expected.chunks_exact(4).zip(actual.chunks_exact(4));
+            format!("{} pixels", pixels.filter(|(e, a)| e != a).count())
+        } else {
+            format!(
+                "size {}x{} instead of {}x{}",
+                self.width, self.height, width, height
+            )
+        };
+        panic!(
+            "{} differs from {} ({}), see {}",
+            name,
+            path.display(),
+            differing,
+            output.display()
+        );
+    }
+}
+
+fn to_rgba8(color: &na::Vector4<f32>) -> [u8; 4] {
+    color
+        .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+        .into()
+}
+
+/// The width, height and RGBA pixels of a PNG image
+fn decode_png(png: &[u8]) -> (usize, usize, Vec<u8>) {
+    let mut reader = png::Decoder::new(png).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgba, png::BitDepth::Eight)
+    );
+    pixels.truncate(info.buffer_size());
+    (info.width as usize, info.height as usize, pixels)
+}
+
+/// Twice the signed area of (`from`, `to`, `p`), positive when `p` is on the
+/// left of the edge
+fn edge(from: &WindowVertex, to: &WindowVertex, x: f32, y: f32) -> f32 {
+    (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x)
+}
+
+/// Whether a counterclockwise edge is at the top or on the left of its triangle,
+/// going left or down
+fn is_top_left(from: &WindowVertex, to: &WindowVertex) -> bool {
+    (from.y == to.y && to.x < from.x) || to.y < from.y
+}
+
+/// The color at the point of screen-space barycentric `weights`
+fn interpolate(vertices: &[WindowVertex], weights: &[f32]) -> na::Vector4<f32> {
+    let mut color = na::Vector4::zeros();
+    let mut total = 0.0;
+    for (vertex, weight) in vertices.iter().zip(weights) {
+        let weight = weight * vertex.inv_w;
+        color += vertex.color * weight;
+        total += weight;
+    }
+    color / total
+}
+
+/// The vertices of the float attributes in `buffers` run through `shader`, at
+/// least `count` of them
+fn shade<'a, F>(
+    buffers: &'a [(VertexLayout, Vec<f32>)],
+    count: usize,
+    constants: &HashMap<&'a str, na::Vector4<f32>>,
+    uniforms: &HashMap<String, UniformValue>,
+    shader: F,
+) -> Vec<Vertex>
+where
+    F: Fn(&ShaderInputs) -> Vertex,
+{
+    let mut attributes = vec![constants.clone(); count];
+    for (layout, data) in buffers {
+        let stride = layout.stride() as usize / 4;
+        for attribute in layout.attributes() {
+            assert_eq!(
+                attribute.ty,
+                GL::FLOAT,
+                "{} is not a float attribute",
+                attribute.name
+            );
+            let values = self::attribute(
+                data,
+                stride,
+                attribute.offset as usize / 4,
+                attribute.size as usize,
+            );
+            attributes.resize(attributes.len().max(values.len()), constants.clone());
+            for (vertex, value) in attributes.iter_mut().zip(values) {
+                vertex.insert(attribute.name.as_str(), value);
+            }
+        }
+    }
+    attributes
+        .into_iter()
+        .map(|attributes| {
+            shader(&ShaderInputs {
+                attributes,
+                uniforms,
+            })
+        })
+        .collect()
+}
+
+/// The distances to the near and far planes, positive inside
+const CLIP_PLANES: [fn(&na::Vector4<f32>) -> f32; 2] = [|p| p.w + p.z, |p| p.w - p.z];
+
+/// The part of the polygon between the near and far planes, the other planes
+/// being left to the bounds of the drawing buffer
+fn clip_polygon(mut polygon: Vec<Vertex>) -> Vec<Vertex> {
+    for distance in CLIP_PLANES {
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, current) in polygon.iter().enumerate() {
+            let next = &polygon[(i + 1) % polygon.len()];
+            let (d0, d1) = (distance(&current.position), distance(&next.position));
+            if d0 >= 0.0 {
+                clipped.push(*current);
+            }
+            if (d0 >= 0.0) != (d1 >= 0.0) {
+                clipped.push(current.lerp(next, d0 / (d0 - d1)));
+            }
+        }
+        polygon = clipped;
+    }
+    polygon
+}
+
+/// The part of the line between the near and far planes
+fn clip_line(start: &Vertex, end: &Vertex) -> Option<(Vertex, Vertex)> {
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for distance in CLIP_PLANES {
+        let (d0, d1) = (distance(&start.position), distance(&end.position));
+        match (d0 >= 0.0, d1 >= 0.0) {
+            (true, true) => {}
+            (false, false) => return None,
+            (true, false) => t1 = t1.min(d0 / (d0 - d1)),
+            (false, true) => t0 = t0.max(d0 / (d0 - d1)),
+        }
+    }
+    (t0 < t1).then(|| (start.lerp(end, t0), start.lerp(end, t1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: na::Vector4<f32> = na::Vector4::new(1.0, 0.0, 0.0, 1.0);
+    const GREEN: na::Vector4<f32> = na::Vector4::new(0.0, 1.0, 0.0, 1.0);
+
+    fn vertex(x: f32, y: f32, z: f32, color: na::Vector4<f32>) -> Vertex {
+        Vertex::new(na::Vector4::new(x, y, z, 1.0), color)
+    }
+
+    fn count(rasterizer: &Rasterizer, color: [u8; 4]) -> usize {
+        rasterizer
+            .color
+            .iter()
+            .filter(|&&pixel| pixel == color)
+            .count()
+    }
+
+    #[test]
+    fn attributes_are_completed() {
+        let data = [0.5, -0.5, 1.0, 0.0, 0.0, 0.0, 1.0, 0.5, 0.5, 0.5];
+        assert_eq!(
+            attribute(&data, 5, 2, 3),
+            vec![
+                na::Vector4::new(1.0, 0.0, 0.0, 1.0),
+                na::Vector4::new(0.5, 0.5, 0.5, 1.0)
+            ]
+        );
+        assert_eq!(
+            attribute(&data, 5, 0, 2)[1],
+            na::Vector4::new(0.0, 1.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_once() {
+        // A quad covering the buffer, made of two triangles
+        let mut rasterizer = Rasterizer::new(8, 8);
+        let vertices = [
+            vertex(-1.0, 1.0, 0.0, RED),
+            vertex(-1.0, -1.0, 0.0, RED),
+            vertex(1.0, 1.0, 0.0, GREEN),
+            vertex(1.0, -1.0, 0.0, GREEN),
+        ];
+        rasterizer.draw_arrays(DrawMode::TriangleStrip, &vertices);
+        assert!(rasterizer.color.iter().all(|pixel| pixel[3] == 255));
+
+        // The diagonal in a single triangle
+        let mut first = Rasterizer::new(8, 8);
+        first.draw_arrays(DrawMode::Triangles, &vertices[..3]);
+        let mut second = Rasterizer::new(8, 8);
+        second.draw_arrays(DrawMode::Triangles, &vertices[1..]);
+        assert_eq!(count(&first, [0; 4]) + count(&second, [0; 4]), 64);
+    }
+
+    #[test]
+    fn colors_are_interpolated() {
+        let mut rasterizer = Rasterizer::new(4, 1);
+        let vertices = [
+            vertex(-1.0, -1.0, 0.0, RED),
+            vertex(1.0, -1.0, 0.0, GREEN),
+            vertex(1.0, 1.0, 0.0, GREEN),
+            vertex(-1.0, 1.0, 0.0, RED),
+        ];
+        rasterizer.draw_arrays(DrawMode::TriangleFan, &vertices);
+        assert_eq!(
+            rasterizer.color,
+            vec![
+                [223, 32, 0, 255],
+                [159, 96, 0, 255],
+                [96, 159, 0, 255],
+                [32, 223, 0, 255]
+            ]
+        );
+    }
+
+    #[test]
+    fn depth_test_keeps_the_nearest() {
+        let mut rasterizer = Rasterizer::new(4, 4);
+        rasterizer.enable_depth_test();
+        rasterizer.clear([0.0, 0.0, 0.0, 1.0]);
+        let quad = |z, color| {
+            [
+                vertex(-1.0, -1.0, z, color),
+                vertex(1.0, -1.0, z, color),
+                vertex(1.0, 1.0, z, color),
+                vertex(-1.0, 1.0, z, color),
+            ]
+        };
+        rasterizer.draw_arrays(DrawMode::TriangleFan, &quad(-0.5, RED));
+        rasterizer.draw_arrays(DrawMode::TriangleFan, &quad(0.5, GREEN));
+        assert_eq!(count(&rasterizer, [255, 0, 0, 255]), 16);
+    }
+
+    #[test]
+    fn points_are_squares() {
+        let mut rasterizer = Rasterizer::new(16, 16);
+        let point = vertex(0.0, 0.0, 0.0, RED).point_size(4.0);
+        rasterizer.draw_arrays(DrawMode::Points, &[point]);
+        assert_eq!(count(&rasterizer, [255, 0, 0, 255]), 16);
+        assert_eq!(rasterizer.pixel(6, 6), [255, 0, 0, 255]);
+        assert_eq!(rasterizer.pixel(9, 9), [255, 0, 0, 255]);
+        assert_eq!(rasterizer.pixel(10, 10), [0; 4]);
+
+        // Out of the clip volume
+        let mut rasterizer = Rasterizer::new(16, 16);
+        rasterizer.draw_arrays(DrawMode::Points, &[vertex(0.0, 0.0, 2.0, RED)]);
+        assert_eq!(count(&rasterizer, [0; 4]), 256);
+    }
+
+    #[test]
+    fn lines_exclude_their_last_pixel() {
+        let mut rasterizer = Rasterizer::new(8, 8);
+        // Through the centers of the pixels of the corners
+        let vertices = [
+            vertex(-0.875, -0.875, 0.0, RED),
+            vertex(0.875, -0.875, 0.0, RED),
+            vertex(0.875, 0.875, 0.0, RED),
+        ];
+        rasterizer.draw_arrays(DrawMode::Lines, &vertices[..2]);
+        assert_eq!(count(&rasterizer, [255, 0, 0, 255]), 7);
+        assert_eq!(rasterizer.pixel(7, 0), [0; 4]);
+
+        // Every corner drawn once
+        let mut rasterizer = Rasterizer::new(8, 8);
+        rasterizer.draw_arrays(DrawMode::LineLoop, &vertices);
+        assert_eq!(count(&rasterizer, [255, 0, 0, 255]), 21);
+    }
+
+    #[test]
+    fn primitives_behind_the_camera_are_clipped() {
+        // A triangle going through the near plane
+        let mut rasterizer = Rasterizer::new(8, 8);
+        let vertices = [
+            Vertex::new(na::Vector4::new(-1.0, -1.0, 0.0, 1.0), RED),
+            Vertex::new(na::Vector4::new(1.0, -1.0, 0.0, 1.0), RED),
+            Vertex::new(na::Vector4::new(0.0, 1.0, -2.0, 0.5), RED),
+        ];
+        rasterizer.draw_arrays(DrawMode::Triangles, &vertices);
+        let drawn = count(&rasterizer, [255, 0, 0, 255]);
+        assert!(drawn > 0 && drawn < 64);
+
+        let mut rasterizer = Rasterizer::new(8, 8);
+        let line = [
+            Vertex::new(na::Vector4::new(-0.5, 0.0, 0.0, 1.0), RED),
+            Vertex::new(na::Vector4::new(1.0, 0.5, -3.0, -1.0), RED),
+        ];
+        rasterizer.draw_arrays(DrawMode::Lines, &line);
+        assert_eq!(count(&rasterizer, [255, 0, 0, 255]), 1);
+    }
+
+    #[test]
+    fn draw_modes_match_gl() {
+        assert_eq!(DrawMode::from_gl(GL::LINE_LOOP), DrawMode::LineLoop);
+        assert_eq!(DrawMode::from_gl(GL::TRIANGLE_FAN), DrawMode::TriangleFan);
+    }
+}
@@ -0,0 +1,249 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+
+use js_sys::{Array, Function, Promise, Uint8Array};
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Blob, BlobPropertyBag, HtmlImageElement, Url, WebGl2RenderingContext as GL, WebGlTexture,
+};
+
+use super::debug::{self, enum_name};
+
+thread_local! {
+    // Images are kept for the lifetime of the app, so that revisiting a page
+    // does not fetch its textures again.
+    static IMAGES: RefCell<HashMap<String, Promise>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadImageError {
+    url: String,
+}
+
+impl fmt::Display for LoadImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to load the image {}", self.url)
+    }
+}
+
+impl std::error::Error for LoadImageError {}
+
+/// Load the image at `url`, or reuse it if it was requested before
+pub fn load_image(url: &str) -> impl Future<Output = Result<HtmlImageElement, LoadImageError>> {
+    let url = url.to_string();
+    let promise = IMAGES.with(|images| {
+        images
+            .borrow_mut()
+            .entry(url.clone())
+            .or_insert_with(|| request_image(&url))
+            .clone()
+    });
+
+    async move {
+        match JsFuture::from(promise).await {
+            Ok(image) => Ok(image.unchecked_into()),
+            Err(_) => {
+                // Forget the failure so that the next visit tries again
+                IMAGES.with(|images| images.borrow_mut().remove(&url));
+                Err(LoadImageError { url })
+            }
+        }
+    }
+}
+
+/// Decode an image stored in memory, such as the images embedded in a glTF
+/// asset, `mime_type` being its type (`image/png`, ...)
+pub fn load_image_bytes(
+    data: &[u8],
+    mime_type: &str,
+) -> impl Future<Output = Result<HtmlImageElement, LoadImageError>> {
+    let mime_type = mime_type.to_string();
+    let parts = Array::of1(&Uint8Array::from(data));
+    let options = BlobPropertyBag::new();
+    options.set_type(&mime_type);
+    let url = Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .and_then(|blob| Url::create_object_url_with_blob(&blob));
+
+    async move {
+        let url = url.map_err(|_| LoadImageError {
+            url: format!("<{} data>", mime_type),
+        })?;
+        let result = JsFuture::from(request_image(&url)).await;
+        // The image keeps its decoded content
+        let _ = Url::revoke_object_url(&url);
+        match result {
+            Ok(image) => Ok(image.unchecked_into()),
+            Err(_) => Err(LoadImageError { url }),
+        }
+    }
+}
+
+fn request_image(url: &str) -> Promise {
+    Promise::new(&mut |resolve, reject| {
+        let image = HtmlImageElement::new().unwrap();
+        image.set_cross_origin(Some("anonymous"));
+        // Resolve with the image itself rather than the load event
+        let onload: Function = resolve.bind1(&JsValue::NULL, &image).unchecked_into();
+        image.set_onload(Some(&onload));
+        image.set_onerror(Some(&reject));
+        image.set_src(url);
+    })
+}
+
+/// A 2D texture object assigned to a texture unit
+#[derive(Debug, Clone)]
+pub struct Texture {
+    gl: GL,
+    texture: WebGlTexture,
+    unit: u32,
+}
+
+impl Texture {
+    pub fn builder() -> TextureBuilder {
+        TextureBuilder::default()
+    }
+
+    /// Index of the texture unit, the value to pass to the sampler uniform
+    pub fn unit(&self) -> i32 {
+        self.unit as i32
+    }
+
+    /// Make the texture unit active and bind the texture object to it
+    #[track_caller]
+    pub fn bind(&self) {
+        self.gl.active_texture(GL::TEXTURE0 + self.unit);
+        self.gl.bind_texture(GL::TEXTURE_2D, Some(&self.texture));
+        debug::check_errors(&self.gl, || format!("bindTexture(TEXTURE{})", self.unit));
+    }
+}
+
+/// Parameters of a `Texture`, uploaded together with its image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureBuilder {
+    unit: u32,
+    format: u32,
+    wrap_s: u32,
+    wrap_t: u32,
+    min_filter: u32,
+    mag_filter: u32,
+    flip_y: bool,
+}
+
+impl Default for TextureBuilder {
+    fn default() -> Self {
+        Self {
+            unit: 0,
+            format: GL::RGBA,
+            wrap_s: GL::REPEAT,
+            wrap_t: GL::REPEAT,
+            min_filter: GL::LINEAR,
+            mag_filter: GL::LINEAR,
+            flip_y: false,
+        }
+    }
+}
+
+impl TextureBuilder {
+    pub fn unit(mut self, unit: u32) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Internal format and format of the image (`GL::RGB`, `GL::RGBA`, ...)
+    pub fn format(mut self, format: u32) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn wrap(mut self, wrap_s: u32, wrap_t: u32) -> Self {
+        self.wrap_s = wrap_s;
+        self.wrap_t = wrap_t;
+        self
+    }
+
+    /// Mipmaps are generated when `min_filter` is one of the `*_MIPMAP_*`
+    /// filters
+    pub fn filter(mut self, min_filter: u32, mag_filter: u32) -> Self {
+        self.min_filter = min_filter;
+        self.mag_filter = mag_filter;
+        self
+    }
+
+    /// Flip the image's y axis, images have their origin at the top-left corner
+    /// while texture coordinates start at the bottom-left
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    #[track_caller]
+    pub fn build(&self, gl: &GL, image: &HtmlImageElement) -> Result<Texture, JsError> {
+        let texture = gl
+            .create_texture()
+            .ok_or_else(|| JsError::new("Failed to create the texture object"))?;
+        let texture = Texture {
+            gl: gl.clone(),
+            texture,
+            unit: self.unit,
+        };
+
+        gl.pixel_storei(GL::UNPACK_FLIP_Y_WEBGL, self.flip_y as i32);
+        texture.bind();
+
+        // Set the texture parameters
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, self.wrap_s as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, self.wrap_t as i32);
+        gl.tex_parameteri(
+            GL::TEXTURE_2D,
+            GL::TEXTURE_MIN_FILTER,
+            self.min_filter as i32,
+        );
+        gl.tex_parameteri(
+            GL::TEXTURE_2D,
+            GL::TEXTURE_MAG_FILTER,
+            self.mag_filter as i32,
+        );
+        // Set the texture image
+        gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
+            GL::TEXTURE_2D,
+            0,
+            self.format as i32,
+            self.format,
+            GL::UNSIGNED_BYTE,
+            image,
+        )
+        .map_err(|error| {
+            // The exception thrown for an image that cannot be uploaded
+            let message = error
+                .dyn_ref::<js_sys::Error>()
+                .map(|error| String::from(error.message()));
+            JsError::new(&message.unwrap_or_else(|| format!("{:?}", error)))
+        })?;
+        if uses_mipmaps(self.min_filter) {
+            gl.generate_mipmap(GL::TEXTURE_2D);
+        }
+        debug::check_errors(gl, || {
+            format!(
+                "texImage2D({}, {}, {})",
+                enum_name(self.format),
+                enum_name(self.wrap_s),
+                enum_name(self.min_filter)
+            )
+        });
+
+        Ok(texture)
+    }
+}
+
+fn uses_mipmaps(min_filter: u32) -> bool {
+    matches!(
+        min_filter,
+        GL::NEAREST_MIPMAP_NEAREST
+            | GL::LINEAR_MIPMAP_NEAREST
+            | GL::NEAREST_MIPMAP_LINEAR
+            | GL::LINEAR_MIPMAP_LINEAR
+    )
+}
@@ -0,0 +1,265 @@
+use js_sys::{Float32Array, Object, Uint16Array, Uint32Array, Uint8Array};
+use wasm_bindgen::JsError;
+use web_sys::{WebGl2RenderingContext as GL, WebGlVertexArrayObject};
+
+use super::debug::{self, enum_name};
+use super::program::{is_integer_type, ShaderProgram};
+
+/// Size in bytes of a single component of type `ty` (`GL::FLOAT`,
+/// `GL::UNSIGNED_BYTE`, ...)
+pub fn type_size(ty: u32) -> i32 {
+    match ty {
+        GL::BYTE | GL::UNSIGNED_BYTE => 1,
+        GL::SHORT | GL::UNSIGNED_SHORT | GL::HALF_FLOAT => 2,
+        _ => 4,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub name: String,
+    pub size: i32,
+    pub ty: u32,
+    /// Whether integer values are mapped to [0, 1] or [-1, 1]
+    pub normalized: bool,
+    pub offset: i32,
+}
+
+/// Describes how the attributes of one vertex are packed in a buffer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>,
+    stride: i32,
+    pub(super) skip_inactive: bool,
+}
+
+impl VertexLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an attribute of `size` components of type `ty`, `normalized` for
+    /// integer colors and the like
+    pub fn attribute(mut self, name: &str, size: i32, ty: u32, normalized: bool) -> Self {
+        let offset = align(self.stride, type_size(ty));
+        self.attributes.push(VertexAttribute {
+            name: name.to_string(),
+            size,
+            ty,
+            normalized,
+            offset,
+        });
+        let alignment = self
+            .attributes
+            .iter()
+            .map(|attribute| type_size(attribute.ty))
+            .max()
+            .unwrap_or(1);
+        self.stride = align(offset + size * type_size(ty), alignment);
+        self
+    }
+
+    /// Skip the attributes a program does not use rather than failing, for the
+    /// layouts shared by programs reading only some of them
+    pub fn skip_inactive(mut self) -> Self {
+        self.skip_inactive = true;
+        self
+    }
+
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    /// Size of one vertex in bytes
+    pub fn stride(&self) -> i32 {
+        self.stride
+    }
+}
+
+fn align(offset: i32, alignment: i32) -> i32 {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+/// Element types that can be uploaded into a buffer object
+pub trait BufferData: Sized {
+    /// # Safety
+    ///
+    /// The returned view borrows `data` directly from the wasm memory, it must
+    /// not outlive `data` and no allocation may happen while it is in use.
+    unsafe fn view(data: &[Self]) -> Object;
+}
+
+impl BufferData for f32 {
+    unsafe fn view(data: &[Self]) -> Object {
+        Float32Array::view(data).into()
+    }
+}
+
+impl BufferData for u8 {
+    unsafe fn view(data: &[Self]) -> Object {
+        Uint8Array::view(data).into()
+    }
+}
+
+impl BufferData for u16 {
+    unsafe fn view(data: &[Self]) -> Object {
+        Uint16Array::view(data).into()
+    }
+}
+
+impl BufferData for u32 {
+    unsafe fn view(data: &[Self]) -> Object {
+        Uint32Array::view(data).into()
+    }
+}
+
+/// A vertex array object recording the buffers and attribute pointers of a mesh
+#[derive(Debug, Clone)]
+pub struct VertexArray {
+    gl: GL,
+    vao: WebGlVertexArrayObject,
+}
+
+impl VertexArray {
+    /// Create a vertex array object and bind it
+    #[track_caller]
+    pub fn new(gl: &GL) -> Result<Self, JsError> {
+        let vao = gl
+            .create_vertex_array()
+            .ok_or_else(|| JsError::new("Failed to create the vertex array object"))?;
+        gl.bind_vertex_array(Some(&vao));
+        debug::check_errors(gl, || "bindVertexArray".to_string());
+        Ok(Self {
+            gl: gl.clone(),
+            vao,
+        })
+    }
+
+    #[track_caller]
+    pub fn bind(&self) {
+        self.gl.bind_vertex_array(Some(&self.vao));
+        debug::check_errors(&self.gl, || "bindVertexArray".to_string());
+    }
+
+    /// Upload `data` into a new buffer and point the attributes of `layout` that
+    /// `program` uses at it
+    #[track_caller]
+    pub fn buffer<T: BufferData>(
+        self,
+        program: &ShaderProgram,
+        layout: &VertexLayout,
+        data: &[T],
+    ) -> Result<Self, JsError> {
+        let gl = &self.gl;
+        let buffer = gl
+            .create_buffer()
+            .ok_or_else(|| JsError::new("Failed to create the buffer object"))?;
+
+        self.bind();
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&buffer));
+        // use view() instead of from() to avoid additional memory allocation
+        let view = unsafe { T::view(data) };
+        gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &view, GL::STATIC_DRAW);
+        debug::check_errors(gl, || "bufferData(ARRAY_BUFFER)".to_string());
+
+        for attribute in layout.attributes() {
+            // The attributes the shaders do not read are not active in the
+            // program
+            let info = match program.attribute(&attribute.name) {
+                Ok(info) => info,
+                Err(_) if layout.skip_inactive => continue,
+                Err(error) => return Err(error.into()),
+            };
+            if is_integer_type(info.ty) {
+                // int/uint attributes are not converted to float by the pipeline
+                gl.vertex_attrib_i_pointer_with_i32(
+                    info.location,
+                    attribute.size,
+                    attribute.ty,
+                    layout.stride(),
+                    attribute.offset,
+                );
+            } else {
+                gl.vertex_attrib_pointer_with_i32(
+                    info.location,
+                    attribute.size,
+                    attribute.ty,
+                    attribute.normalized,
+                    layout.stride(),
+                    attribute.offset,
+                );
+            }
+            gl.enable_vertex_attrib_array(info.location);
+            debug::check_errors(gl, || {
+                format!(
+                    "vertexAttribPointer({}, {}, {})",
+                    attribute.name,
+                    attribute.size,
+                    enum_name(attribute.ty)
+                )
+            });
+        }
+
+        Ok(self)
+    }
+
+    /// Upload `indices` into a new element array buffer recorded by the vertex
+    /// array object, for use with `drawElements`
+    #[track_caller]
+    pub fn indices<T: BufferData>(self, indices: &[T]) -> Result<Self, JsError> {
+        let gl = &self.gl;
+        let buffer = gl
+            .create_buffer()
+            .ok_or_else(|| JsError::new("Failed to create the buffer object"))?;
+
+        self.bind();
+        gl.bind_buffer(GL::ELEMENT_ARRAY_BUFFER, Some(&buffer));
+        let view = unsafe { T::view(indices) };
+        gl.buffer_data_with_array_buffer_view(GL::ELEMENT_ARRAY_BUFFER, &view, GL::STATIC_DRAW);
+        debug::check_errors(gl, || "bufferData(ELEMENT_ARRAY_BUFFER)".to_string());
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_attribute() {
+        let layout = VertexLayout::new().attribute("a_Position", 2, GL::FLOAT, false);
+        assert_eq!(layout.stride(), 8);
+        assert_eq!(layout.attributes()[0].offset, 0);
+    }
+
+    #[test]
+    fn interleaved_attributes() {
+        let layout = VertexLayout::new()
+            .attribute("a_Position", 3, GL::FLOAT, false)
+            .attribute("a_Color", 3, GL::FLOAT, false)
+            .attribute("a_TexCoord", 2, GL::FLOAT, false);
+        let offsets: Vec<_> = layout.attributes().iter().map(|a| a.offset).collect();
+        assert_eq!(offsets, [0, 12, 24]);
+        assert_eq!(layout.stride(), 32);
+    }
+
+    #[test]
+    fn mixed_types_are_aligned() {
+        let layout = VertexLayout::new()
+            .attribute("a_Color", 3, GL::UNSIGNED_BYTE, true)
+            .attribute("a_Position", 3, GL::FLOAT, false)
+            .attribute("a_Id", 1, GL::UNSIGNED_SHORT, false);
+        let offsets: Vec<_> = layout.attributes().iter().map(|a| a.offset).collect();
+        assert_eq!(offsets, [0, 4, 16]);
+        // The stride is padded so that the float of the next vertex is aligned too
+        assert_eq!(layout.stride(), 20);
+        assert!(layout.attributes()[0].normalized);
+        assert!(!layout.attributes()[1].normalized);
+    }
+
+    #[test]
+    fn empty_layout() {
+        assert_eq!(VertexLayout::new().stride(), 0);
+    }
+}